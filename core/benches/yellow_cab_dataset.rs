@@ -3,15 +3,17 @@
 use arrow::array::ArrayIter;
 use arrow::datatypes::{DataType, Schema, TimeUnit};
 use arrow::record_batch::RecordBatchReader;
-use arrow_array::RecordBatch;
+use arrow_array::{RecordBatch, RecordBatchIterator};
 use bytes::BytesMut;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use parquet::arrow::arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder};
+use pgpq::writer::DEFAULT_FLUSH_THRESHOLD;
 use pgpq::ArrowToPostgresBinaryEncoder;
 use std::fs;
 use std::fs::File;
-use std::io;
+use std::io::{self, Write};
 use std::path::PathBuf;
+use std::sync::Arc;
 
 fn download_dataset() -> File {
     let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -45,14 +47,14 @@ fn setup(row_limit: Option<usize>) -> (Vec<RecordBatch>, Schema) {
     (data, schema)
 }
 
-fn bench(batches: &Vec<RecordBatch>, schema: &Schema) {
-    let mut encoder = ArrowToPostgresBinaryEncoder::try_new(schema).unwrap();
-    let mut buff = BytesMut::new();
-    encoder.write_header(&mut buff);
-    for batch in batches {
-        encoder.write_batch(batch, &mut buff);
+fn bench(batches: &[RecordBatch], schema: &Schema) {
+    let reader =
+        RecordBatchIterator::new(batches.iter().cloned().map(Ok), Arc::new(schema.clone()));
+    let chunks =
+        ArrowToPostgresBinaryEncoder::encode_reader(reader, DEFAULT_FLUSH_THRESHOLD).unwrap();
+    for chunk in chunks {
+        io::sink().write_all(&chunk.unwrap()).unwrap();
     }
-    encoder.write_footer(&mut buff);
 }
 
 pub fn benchmark_nyc_taxi_small(c: &mut Criterion) {
@@ -0,0 +1,229 @@
+use arrow_array::RecordBatch;
+use bytes::Bytes;
+use futures_util::stream::{self, Stream, StreamExt};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::error::ErrorKind;
+use crate::writer::{sink_failed, ChunkEncoder, Output, DEFAULT_FLUSH_THRESHOLD};
+use crate::ArrowToPostgresBinaryEncoder;
+
+/// Writes a complete COPY stream to an `AsyncWrite` sink.
+/// The async counterpart of [`crate::writer::CopyWriter`].
+#[derive(Debug)]
+pub struct AsyncCopyWriter<W: AsyncWrite + Unpin> {
+    chunks: ChunkEncoder,
+    inner: W,
+    // set once a write to the sink fails, after which the stream is incomplete
+    failed: bool,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncCopyWriter<W> {
+    pub fn try_new(encoder: ArrowToPostgresBinaryEncoder, inner: W) -> Result<Self, ErrorKind> {
+        let mut chunks = ChunkEncoder::new(encoder, DEFAULT_FLUSH_THRESHOLD);
+        chunks.start()?;
+        Ok(Self {
+            chunks,
+            inner,
+            failed: false,
+        })
+    }
+
+    /// Writes to the sink whenever at least `flush_threshold` bytes are buffered
+    pub fn with_flush_threshold(mut self, flush_threshold: usize) -> Self {
+        self.chunks.set_chunk_size(flush_threshold);
+        self
+    }
+
+    pub fn encoder(&self) -> &ArrowToPostgresBinaryEncoder {
+        self.chunks.encoder()
+    }
+
+    pub fn encoder_mut(&mut self) -> &mut ArrowToPostgresBinaryEncoder {
+        self.chunks.encoder_mut()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Fails without writing anything once a write to the sink has failed
+    pub async fn write_batch(&mut self, batch: &RecordBatch) -> Result<(), ErrorKind> {
+        if self.failed {
+            return Err(sink_failed().into());
+        }
+        self.chunks.push_batch(batch.clone());
+        self.write_output().await
+    }
+
+    /// Writes everything buffered so far to the sink and flushes it
+    pub async fn flush(&mut self) -> Result<(), ErrorKind> {
+        let buffered = self.chunks.take_buffered();
+        self.write_to_sink(&buffered).await?;
+        self.inner.flush().await?;
+        Ok(())
+    }
+
+    /// Writes the footer and returns the sink
+    pub async fn finish(mut self) -> Result<W, ErrorKind> {
+        if self.failed {
+            return Err(sink_failed().into());
+        }
+        self.chunks.end_input();
+        self.write_output().await?;
+        self.flush().await?;
+        Ok(self.inner)
+    }
+
+    async fn write_output(&mut self) -> Result<(), ErrorKind> {
+        while let Output::Chunk(chunk) = self.chunks.next_chunk()? {
+            self.write_to_sink(&chunk).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_to_sink(&mut self, chunk: &[u8]) -> Result<(), ErrorKind> {
+        if self.failed {
+            return Err(sink_failed().into());
+        }
+        // a chunk that was partly written is gone from the buffer, so it is never resent
+        if let Err(e) = self.inner.write_all(chunk).await {
+            self.failed = true;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+struct EncodeStreamState<S> {
+    chunks: ChunkEncoder,
+    batches: S,
+    done: bool,
+}
+
+/// Encodes a stream of batches into a stream of COPY chunks, starting with the header
+/// and ending with the footer.
+/// Each chunk holds at least `flush_threshold` bytes except for the last one.
+/// Batches are only pulled from `batches` when the next chunk is requested,
+/// so a slow consumer such as `tokio_postgres::Client::copy_in` applies backpressure.
+/// The stream ends after the first error.
+pub fn encode_stream<S>(
+    encoder: ArrowToPostgresBinaryEncoder,
+    batches: S,
+    flush_threshold: usize,
+) -> impl Stream<Item = Result<Bytes, ErrorKind>>
+where
+    S: Stream<Item = RecordBatch> + Unpin,
+{
+    let state = EncodeStreamState {
+        chunks: ChunkEncoder::new(encoder, flush_threshold),
+        batches,
+        done: false,
+    };
+    stream::unfold(state, |mut state| async move {
+        if state.done {
+            return None;
+        }
+        loop {
+            match state.chunks.next_chunk() {
+                Ok(Output::Chunk(chunk)) => return Some((Ok(chunk), state)),
+                Ok(Output::NeedsInput) => match state.batches.next().await {
+                    Some(batch) => state.chunks.push_batch(batch),
+                    None => state.chunks.end_input(),
+                },
+                Ok(Output::LargeRow(..) | Output::Done) => return None,
+                Err(e) => {
+                    state.done = true;
+                    return Some((Err(e), state));
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::Int32Array;
+    use arrow_schema::{DataType, Field, Schema};
+    use bytes::BytesMut;
+
+    use super::*;
+    use crate::writer::tests::FailingSink;
+
+    fn make_batch(values: Vec<i32>) -> RecordBatch {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(Int32Array::from(values))]).unwrap()
+    }
+
+    fn encode_all(batches: &[RecordBatch]) -> Vec<u8> {
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&batches[0].schema()).unwrap();
+        let mut buf = BytesMut::new();
+        encoder.write_header(&mut buf).unwrap();
+        for batch in batches {
+            encoder.write_batch(batch, &mut buf).unwrap();
+        }
+        encoder.write_footer(&mut buf).unwrap();
+        buf.to_vec()
+    }
+
+    #[tokio::test]
+    async fn test_async_copy_writer() {
+        let batches = vec![make_batch(vec![1, 2]), make_batch(vec![3])];
+        let encoder = ArrowToPostgresBinaryEncoder::try_new(&batches[0].schema()).unwrap();
+        let mut writer = AsyncCopyWriter::try_new(encoder, vec![])
+            .unwrap()
+            .with_flush_threshold(30);
+        writer.write_batch(&batches[0]).await.unwrap();
+        assert_eq!(writer.get_ref().len(), 19 + 2 * 10);
+        writer.write_batch(&batches[1]).await.unwrap();
+        let out = writer.finish().await.unwrap();
+        assert_eq!(out, encode_all(&batches));
+    }
+
+    #[tokio::test]
+    async fn test_async_copy_writer_sink_failure() {
+        let batch = make_batch(vec![1, 2]);
+        let encoder = ArrowToPostgresBinaryEncoder::try_new(&batch.schema()).unwrap();
+        let mut writer = AsyncCopyWriter::try_new(encoder, FailingSink::new(25))
+            .unwrap()
+            .with_flush_threshold(1);
+        assert!(matches!(
+            writer.write_batch(&batch).await,
+            Err(ErrorKind::Io(_))
+        ));
+        // the partly written chunk is not sent again
+        assert!(writer.write_batch(&batch).await.is_err());
+        assert!(writer.flush().await.is_err());
+        assert_eq!(writer.get_ref().data.len(), 25);
+        assert!(writer.finish().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_encode_stream() {
+        let batches = vec![make_batch(vec![1, 2]), make_batch(vec![3])];
+        let encoder = ArrowToPostgresBinaryEncoder::try_new(&batches[0].schema()).unwrap();
+        let chunks: Vec<Bytes> = encode_stream(encoder, stream::iter(batches.clone()), 30)
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        // the header and the first batch, then the second batch and the footer
+        assert_eq!(
+            chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+            vec![19 + 2 * 10, 10 + 2]
+        );
+        assert_eq!(chunks.concat(), encode_all(&batches));
+    }
+
+    #[tokio::test]
+    async fn test_encode_stream_stops_after_error() {
+        let batches = vec![make_batch(vec![1]), make_batch(vec![2])];
+        let other = Schema::new(vec![Field::new("a", DataType::Utf8, false)]);
+        let encoder = ArrowToPostgresBinaryEncoder::try_new(&other).unwrap();
+        let chunks: Vec<_> = encode_stream(encoder, stream::iter(batches), 1)
+            .collect()
+            .await;
+        assert_eq!(chunks.len(), 1);
+        assert!(matches!(chunks[0], Err(ErrorKind::SchemaMismatch { .. })));
+    }
+}
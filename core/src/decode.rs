@@ -0,0 +1,383 @@
+//! Decoding of binary COPY data, as produced by `COPY ... TO STDOUT WITH (FORMAT BINARY)`,
+//! back into Arrow.
+
+use std::sync::Arc;
+
+use arrow_array::builder::{
+    BinaryBuilder, BooleanBuilder, Date32Builder, FixedSizeBinaryBuilder, Float32Builder,
+    Float64Builder, Int16Builder, Int32Builder, Int64Builder, Int8Builder,
+    IntervalMonthDayNanoBuilder, StringBuilder, Time64MicrosecondBuilder,
+    TimestampMicrosecondBuilder, UInt32Builder,
+};
+use arrow_array::types::IntervalMonthDayNanoType;
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{Field, Schema};
+use bytes::Buf;
+
+use crate::encoders::{PG_BASE_DATE_OFFSET, PG_BASE_TIMESTAMP_OFFSET_US};
+use crate::error::ErrorKind;
+use crate::pg_schema::{PostgresSchema, PostgresType};
+use crate::HEADER_MAGIC_BYTES;
+
+fn decode_error(reason: String) -> ErrorKind {
+    ErrorKind::Protocol { reason }
+}
+
+fn fixed<const N: usize>(value: &[u8]) -> Result<[u8; N], String> {
+    value
+        .try_into()
+        .map_err(|_| format!("expected {N} bytes but got {}", value.len()))
+}
+
+/// Collects the values of one column.
+/// Types without a dedicated decoder keep their binary representation.
+enum ColumnDecoder {
+    Bool(BooleanBuilder),
+    Char(Int8Builder),
+    Int2(Int16Builder),
+    Int4(Int32Builder),
+    Int8(Int64Builder),
+    Oid(UInt32Builder),
+    Float4(Float32Builder),
+    Float8(Float64Builder),
+    Text { builder: StringBuilder, jsonb: bool },
+    Date(Date32Builder),
+    Time(Time64MicrosecondBuilder),
+    Timestamp(TimestampMicrosecondBuilder),
+    Interval(IntervalMonthDayNanoBuilder),
+    Uuid(FixedSizeBinaryBuilder),
+    Binary(BinaryBuilder),
+}
+
+impl ColumnDecoder {
+    fn new(tp: &PostgresType) -> Self {
+        match tp {
+            PostgresType::Bool => ColumnDecoder::Bool(BooleanBuilder::new()),
+            PostgresType::Char => ColumnDecoder::Char(Int8Builder::new()),
+            PostgresType::Int2 => ColumnDecoder::Int2(Int16Builder::new()),
+            PostgresType::Int4 => ColumnDecoder::Int4(Int32Builder::new()),
+            PostgresType::Int8 | PostgresType::Money => ColumnDecoder::Int8(Int64Builder::new()),
+            PostgresType::Oid | PostgresType::Regclass => ColumnDecoder::Oid(UInt32Builder::new()),
+            PostgresType::Float4 => ColumnDecoder::Float4(Float32Builder::new()),
+            PostgresType::Float8 => ColumnDecoder::Float8(Float64Builder::new()),
+            PostgresType::Text | PostgresType::Json | PostgresType::Xml => ColumnDecoder::Text {
+                builder: StringBuilder::new(),
+                jsonb: false,
+            },
+            PostgresType::Jsonb => ColumnDecoder::Text {
+                builder: StringBuilder::new(),
+                jsonb: true,
+            },
+            PostgresType::Date => ColumnDecoder::Date(Date32Builder::new()),
+            PostgresType::Time => ColumnDecoder::Time(Time64MicrosecondBuilder::new()),
+            PostgresType::Timestamp => ColumnDecoder::Timestamp(TimestampMicrosecondBuilder::new()),
+            PostgresType::Interval => ColumnDecoder::Interval(IntervalMonthDayNanoBuilder::new()),
+            PostgresType::Uuid => ColumnDecoder::Uuid(FixedSizeBinaryBuilder::new(16)),
+            _ => ColumnDecoder::Binary(BinaryBuilder::new()),
+        }
+    }
+
+    fn append_null(&mut self) {
+        match self {
+            ColumnDecoder::Bool(b) => b.append_null(),
+            ColumnDecoder::Char(b) => b.append_null(),
+            ColumnDecoder::Int2(b) => b.append_null(),
+            ColumnDecoder::Int4(b) => b.append_null(),
+            ColumnDecoder::Int8(b) => b.append_null(),
+            ColumnDecoder::Oid(b) => b.append_null(),
+            ColumnDecoder::Float4(b) => b.append_null(),
+            ColumnDecoder::Float8(b) => b.append_null(),
+            ColumnDecoder::Text { builder, .. } => builder.append_null(),
+            ColumnDecoder::Date(b) => b.append_null(),
+            ColumnDecoder::Time(b) => b.append_null(),
+            ColumnDecoder::Timestamp(b) => b.append_null(),
+            ColumnDecoder::Interval(b) => b.append_null(),
+            ColumnDecoder::Uuid(b) => b.append_null(),
+            ColumnDecoder::Binary(b) => b.append_null(),
+        }
+    }
+
+    fn append_value(&mut self, value: &[u8]) -> Result<(), String> {
+        match self {
+            ColumnDecoder::Bool(b) => b.append_value(fixed::<1>(value)?[0] != 0),
+            ColumnDecoder::Char(b) => b.append_value(i8::from_be_bytes(fixed(value)?)),
+            ColumnDecoder::Int2(b) => b.append_value(i16::from_be_bytes(fixed(value)?)),
+            ColumnDecoder::Int4(b) => b.append_value(i32::from_be_bytes(fixed(value)?)),
+            ColumnDecoder::Int8(b) => b.append_value(i64::from_be_bytes(fixed(value)?)),
+            ColumnDecoder::Oid(b) => b.append_value(u32::from_be_bytes(fixed(value)?)),
+            ColumnDecoder::Float4(b) => b.append_value(f32::from_be_bytes(fixed(value)?)),
+            ColumnDecoder::Float8(b) => b.append_value(f64::from_be_bytes(fixed(value)?)),
+            ColumnDecoder::Text { builder, jsonb } => {
+                let value = if *jsonb {
+                    match value.split_first() {
+                        Some((1, rest)) => rest,
+                        _ => return Err("JSONB value does not start with version 1".to_string()),
+                    }
+                } else {
+                    value
+                };
+                let value = std::str::from_utf8(value).map_err(|e| e.to_string())?;
+                builder.append_value(value)
+            }
+            // infinity and -infinity are kept as the largest and smallest values
+            ColumnDecoder::Date(b) => b.append_value(match i32::from_be_bytes(fixed(value)?) {
+                days @ (i32::MAX | i32::MIN) => days,
+                days => days
+                    .checked_add(PG_BASE_DATE_OFFSET)
+                    .ok_or_else(|| format!("date {days} is out of range for Date32"))?,
+            }),
+            ColumnDecoder::Time(b) => b.append_value(i64::from_be_bytes(fixed(value)?)),
+            ColumnDecoder::Timestamp(b) => {
+                b.append_value(match i64::from_be_bytes(fixed(value)?) {
+                    micros @ (i64::MAX | i64::MIN) => micros,
+                    micros => micros
+                        .checked_add(PG_BASE_TIMESTAMP_OFFSET_US)
+                        .ok_or_else(|| format!("timestamp {micros} is out of range"))?,
+                })
+            }
+            ColumnDecoder::Interval(b) => {
+                let mut buf = &fixed::<16>(value)?[..];
+                let micros = buf.get_i64();
+                let days = buf.get_i32();
+                let months = buf.get_i32();
+                let nanos = micros.checked_mul(1_000).ok_or_else(|| {
+                    format!("interval of {micros} microseconds is out of range for nanoseconds")
+                })?;
+                b.append_value(IntervalMonthDayNanoType::make_value(months, days, nanos))
+            }
+            ColumnDecoder::Uuid(b) => b
+                .append_value(fixed::<16>(value)?)
+                .map_err(|e| e.to_string())?,
+            ColumnDecoder::Binary(b) => b.append_value(value),
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self) -> ArrayRef {
+        use arrow_array::builder::ArrayBuilder;
+        match self {
+            ColumnDecoder::Bool(b) => ArrayBuilder::finish(b),
+            ColumnDecoder::Char(b) => ArrayBuilder::finish(b),
+            ColumnDecoder::Int2(b) => ArrayBuilder::finish(b),
+            ColumnDecoder::Int4(b) => ArrayBuilder::finish(b),
+            ColumnDecoder::Int8(b) => ArrayBuilder::finish(b),
+            ColumnDecoder::Oid(b) => ArrayBuilder::finish(b),
+            ColumnDecoder::Float4(b) => ArrayBuilder::finish(b),
+            ColumnDecoder::Float8(b) => ArrayBuilder::finish(b),
+            ColumnDecoder::Text { builder, .. } => ArrayBuilder::finish(builder),
+            ColumnDecoder::Date(b) => ArrayBuilder::finish(b),
+            ColumnDecoder::Time(b) => ArrayBuilder::finish(b),
+            ColumnDecoder::Timestamp(b) => ArrayBuilder::finish(b),
+            ColumnDecoder::Interval(b) => ArrayBuilder::finish(b),
+            ColumnDecoder::Uuid(b) => ArrayBuilder::finish(b),
+            ColumnDecoder::Binary(b) => ArrayBuilder::finish(b),
+        }
+    }
+}
+
+/// Validates a complete binary COPY stream against `schema` and decodes it into a single batch.
+/// Types without a dedicated decoder, such as `NUMERIC` and arrays,
+/// are returned as `Binary` columns holding the raw values.
+pub fn decode_copy_binary(data: &[u8], schema: &PostgresSchema) -> Result<RecordBatch, ErrorKind> {
+    let mut buf = data;
+    if !buf.starts_with(HEADER_MAGIC_BYTES) || buf.len() < HEADER_MAGIC_BYTES.len() + 8 {
+        return Err(decode_error("missing binary COPY header".to_string()));
+    }
+    buf.advance(HEADER_MAGIC_BYTES.len());
+    let _flags = buf.get_i32();
+    let extension_len = buf.get_i32();
+    if extension_len < 0 || buf.remaining() < extension_len as usize {
+        return Err(decode_error("truncated header extension".to_string()));
+    }
+    buf.advance(extension_len as usize);
+
+    let mut decoders = schema
+        .columns
+        .iter()
+        .map(|c| ColumnDecoder::new(&c.data_type))
+        .collect::<Vec<_>>();
+    let mut row = 0;
+    loop {
+        if buf.remaining() < 2 {
+            return Err(decode_error(format!("row {row}: missing tuple or trailer")));
+        }
+        let n_fields = buf.get_i16();
+        if n_fields == -1 {
+            break;
+        }
+        if n_fields as usize != schema.columns.len() {
+            return Err(decode_error(format!(
+                "row {row}: got {n_fields} fields but the schema has {} columns",
+                schema.columns.len()
+            )));
+        }
+        for (column, decoder) in schema.columns.iter().zip(decoders.iter_mut()) {
+            let column_error = |reason: String| {
+                decode_error(format!("row {row}: {reason}")).in_field(&column.name, || None)
+            };
+            if buf.remaining() < 4 {
+                return Err(column_error("truncated field length".to_string()));
+            }
+            let len = buf.get_i32();
+            if len == -1 {
+                if !column.nullable {
+                    return Err(column_error("NULL in a NOT NULL column".to_string()));
+                }
+                decoder.append_null();
+                continue;
+            }
+            if len < 0 || buf.remaining() < len as usize {
+                return Err(column_error(format!("invalid field length {len}")));
+            }
+            decoder
+                .append_value(&buf[..len as usize])
+                .map_err(column_error)?;
+            buf.advance(len as usize);
+        }
+        row += 1;
+    }
+    if buf.has_remaining() {
+        return Err(decode_error(format!(
+            "{} bytes after the trailer",
+            buf.remaining()
+        )));
+    }
+
+    let columns = decoders.iter_mut().map(|d| d.finish()).collect::<Vec<_>>();
+    let fields = schema
+        .columns
+        .iter()
+        .zip(&columns)
+        .map(|(c, a)| Field::new(&c.name, a.data_type().clone(), c.nullable))
+        .collect::<Vec<_>>();
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).map_err(ErrorKind::from)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use arrow_array::cast::AsArray;
+    use arrow_array::types::{Date32Type, TimestampMicrosecondType};
+    use arrow_array::{
+        BinaryArray, BooleanArray, Date32Array, Float64Array, Int16Array, Int32Array, Int64Array,
+        StringArray, Time64MicrosecondArray, TimestampMicrosecondArray,
+    };
+    use arrow_schema::{DataType, TimeUnit};
+    use bytes::{BufMut, BytesMut};
+
+    use super::*;
+    use crate::pg_schema::Column;
+    use crate::ArrowToPostgresBinaryEncoder;
+
+    /// A batch with a column of every type the decoder turns back into the same Arrow type
+    pub(crate) fn make_batch() -> RecordBatch {
+        let schema = Schema::new(vec![
+            Field::new("bool", DataType::Boolean, false),
+            Field::new("int2", DataType::Int16, false),
+            Field::new("int4", DataType::Int32, true),
+            Field::new("int8", DataType::Int64, false),
+            Field::new("float8", DataType::Float64, false),
+            Field::new("text", DataType::Utf8, true),
+            Field::new("bytea", DataType::Binary, false),
+            Field::new("date", DataType::Date32, false),
+            Field::new(
+                "timestamp",
+                DataType::Timestamp(TimeUnit::Microsecond, None),
+                false,
+            ),
+            Field::new("time", DataType::Time64(TimeUnit::Microsecond), false),
+        ]);
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(BooleanArray::from(vec![true, false])),
+            Arc::new(Int16Array::from(vec![1, -2])),
+            Arc::new(Int32Array::from(vec![Some(3), None])),
+            Arc::new(Int64Array::from(vec![i64::MIN, i64::MAX])),
+            Arc::new(Float64Array::from(vec![0.5, -1.25])),
+            Arc::new(StringArray::from(vec![None, Some("héllo")])),
+            Arc::new(BinaryArray::from(vec![&b"\x00\x01"[..], &b""[..]])),
+            Arc::new(Date32Array::from(vec![0, 19_000])),
+            Arc::new(TimestampMicrosecondArray::from(vec![
+                0,
+                1_700_000_000_000_000,
+            ])),
+            Arc::new(Time64MicrosecondArray::from(vec![0, 86_399_999_999])),
+        ];
+        RecordBatch::try_new(Arc::new(schema), columns).unwrap()
+    }
+
+    /// The schema and complete COPY stream of `batch`
+    pub(crate) fn encode(batch: &RecordBatch) -> (PostgresSchema, Vec<u8>) {
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&batch.schema()).unwrap();
+        let mut buf = BytesMut::new();
+        encoder.write_header(&mut buf).unwrap();
+        encoder.write_batch(batch, &mut buf).unwrap();
+        encoder.write_footer(&mut buf).unwrap();
+        (encoder.schema(), buf.to_vec())
+    }
+
+    #[test]
+    fn test_decode_copy_binary() {
+        let batch = make_batch();
+        let (schema, data) = encode(&batch);
+        assert_eq!(decode_copy_binary(&data, &schema).unwrap(), batch);
+
+        // a NULL in a NOT NULL column
+        let mut strict = schema.clone();
+        strict.columns[2].nullable = false;
+        let err = decode_copy_binary(&data, &strict).unwrap_err();
+        assert_eq!(err.location().unwrap().path, "int4");
+
+        // a truncated stream
+        assert!(decode_copy_binary(&data[..data.len() - 2], &schema).is_err());
+    }
+
+    /// A stream of one row holding `value` in a single column of type `tp`
+    fn single_value(tp: PostgresType, value: &[u8]) -> (PostgresSchema, Vec<u8>) {
+        let schema = PostgresSchema {
+            columns: vec![Column {
+                name: "v".to_string(),
+                data_type: tp,
+                nullable: false,
+            }],
+        };
+        let mut data = BytesMut::new();
+        data.put(HEADER_MAGIC_BYTES);
+        data.put_i32(0);
+        data.put_i32(0);
+        data.put_i16(1);
+        data.put_i32(value.len() as i32);
+        data.put_slice(value);
+        data.put_i16(-1);
+        (schema, data.to_vec())
+    }
+
+    #[test]
+    fn test_decode_infinity() {
+        for days in [i32::MAX, i32::MIN] {
+            let (schema, data) = single_value(PostgresType::Date, &days.to_be_bytes());
+            let batch = decode_copy_binary(&data, &schema).unwrap();
+            assert_eq!(batch.column(0).as_primitive::<Date32Type>().value(0), days);
+        }
+        for micros in [i64::MAX, i64::MIN] {
+            let (schema, data) = single_value(PostgresType::Timestamp, &micros.to_be_bytes());
+            let batch = decode_copy_binary(&data, &schema).unwrap();
+            let column = batch.column(0).as_primitive::<TimestampMicrosecondType>();
+            assert_eq!(column.value(0), micros);
+        }
+
+        // finite values next to the sentinels are out of range for Arrow
+        let (schema, data) = single_value(PostgresType::Date, &(i32::MAX - 1).to_be_bytes());
+        assert!(matches!(
+            decode_copy_binary(&data, &schema).unwrap_err().root_cause(),
+            ErrorKind::Protocol { .. }
+        ));
+        let mut interval = BytesMut::new();
+        interval.put_i64(i64::MAX);
+        interval.put_i32(0);
+        interval.put_i32(0);
+        let (schema, data) = single_value(PostgresType::Interval, &interval);
+        assert!(matches!(
+            decode_copy_binary(&data, &schema).unwrap_err().root_cause(),
+            ErrorKind::Protocol { .. }
+        ));
+    }
+}
@@ -1,10 +1,17 @@
 #![allow(clippy::redundant_closure_call)]
 
+use arrow_array::cast::AsArray;
+use arrow_array::types::{
+    Int16Type, Int32Type, Int64Type, Int8Type, UInt16Type, UInt32Type, UInt8Type,
+};
 use arrow_array::{self, Array, ArrowNativeTypeOp, OffsetSizeTrait};
 use arrow_schema::{DataType, Field, TimeUnit};
 use bytes::{BufMut, BytesMut};
 use enum_dispatch::enum_dispatch;
-use std::{any::type_name, convert::identity, sync::Arc};
+use std::{
+    any::type_name, collections::BTreeMap, convert::identity, mem::MaybeUninit, ops::Range,
+    sync::Arc,
+};
 
 use crate::error::ErrorKind;
 use crate::pg_schema::{Column, PostgresType, TypeSize};
@@ -24,7 +31,112 @@ fn downcast_checked<'a, T: 'static>(arr: &'a dyn Array, field: &str) -> Result<&
 #[enum_dispatch]
 pub trait Encode: std::fmt::Debug {
     fn encode(&self, row: usize, buf: &mut BytesMut) -> Result<(), ErrorKind>;
+    /// The exact number of bytes `encode` writes for all rows, length prefixes included.
+    /// Rows that fail to encode may be counted.
+    /// Most encoders compute it from the Arrow offsets or the values without encoding them;
+    /// `TSVECTOR` values and floats written as `NUMERIC` are counted at the largest size
+    /// they can take, since their size depends on parsing or formatting the value,
+    /// so for them this is an upper bound.
     fn byte_size_hint(&self) -> Result<usize, ErrorKind>;
+    /// Adds the encoded size of each row in `rows`, including its length prefix, to `sizes`.
+    /// Returns false when the sizes are not known without encoding,
+    /// in which case the batch is encoded row by row.
+    fn add_column_sizes(
+        &self,
+        _rows: Range<usize>,
+        _sizes: &mut [usize],
+    ) -> Result<bool, ErrorKind> {
+        Ok(false)
+    }
+    /// Adds an upper bound on the encoded size of each row in `rows`, including its length
+    /// prefix, to `sizes` without encoding any value. This is the exact size wherever
+    /// `add_column_sizes` knows it.
+    fn add_column_size_bounds(
+        &self,
+        rows: Range<usize>,
+        sizes: &mut [usize],
+    ) -> Result<(), ErrorKind> {
+        let sized = self.add_column_sizes(rows, sizes)?;
+        debug_assert!(sized, "encoders that cannot size a column bound it instead");
+        Ok(())
+    }
+    /// Writes the field of each row in `rows` at `offsets[i]` and moves the offset past it,
+    /// see [`write_at`]. Only called after `add_column_sizes` returned true for the same rows.
+    fn scatter_column(
+        &self,
+        rows: Range<usize>,
+        out: &mut [MaybeUninit<u8>],
+        offsets: &mut [usize],
+    ) -> Result<(), ErrorKind> {
+        scatter_encoded(self, rows, out, offsets)
+    }
+    /// The encoded value of `row` without its length prefix, borrowed from the Arrow
+    /// values buffer so large values can be written out without copying them.
+    /// None for nulls and for values that are encoded some other way.
+    fn borrowed_value(&self, _row: usize) -> Option<BorrowedValue<'_>> {
+        None
+    }
+}
+
+/// A value that is encoded as a short prefix followed by bytes from an Arrow buffer
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BorrowedValue<'a> {
+    pub prefix: &'static [u8],
+    pub bytes: &'a [u8],
+}
+
+impl BorrowedValue<'_> {
+    /// The encoded size without the length prefix
+    pub fn len(&self) -> usize {
+        self.prefix.len() + self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Writes into `out` at `offset` and moves the offset past exactly the bytes `write` put there,
+/// so every byte before the offset has been written
+#[inline(always)]
+pub(crate) fn write_at<R>(
+    out: &mut [MaybeUninit<u8>],
+    offset: &mut usize,
+    write: impl FnOnce(&mut &mut [MaybeUninit<u8>]) -> R,
+) -> R {
+    let mut dst = &mut out[*offset..];
+    let available = dst.len();
+    let result = write(&mut dst);
+    *offset += available - dst.len();
+    result
+}
+
+/// Scatters a column by encoding each value into scratch space and copying it into place
+fn scatter_encoded<E: Encode + ?Sized>(
+    encoder: &E,
+    rows: Range<usize>,
+    out: &mut [MaybeUninit<u8>],
+    offsets: &mut [usize],
+) -> Result<(), ErrorKind> {
+    let mut scratch = BytesMut::new();
+    for (row, offset) in rows.zip(offsets.iter_mut()) {
+        scratch.clear();
+        encoder.encode(row, &mut scratch)?;
+        write_at(out, offset, |dst| dst.put_slice(&scratch));
+    }
+    Ok(())
+}
+
+/// Adds the size of a fixed-width field with its length prefix to `sizes` for each row
+/// in `rows`, or just the length prefix for nulls
+fn add_fixed_sizes(arr: &dyn Array, field_size: usize, rows: Range<usize>, sizes: &mut [usize]) {
+    if arr.null_count() == 0 {
+        sizes.iter_mut().for_each(|size| *size += 4 + field_size);
+    } else {
+        for (row, size) in rows.zip(sizes.iter_mut()) {
+            *size += if arr.is_null(row) { 4 } else { 4 + field_size };
+        }
+    }
 }
 
 #[enum_dispatch(Encode)]
@@ -34,6 +146,7 @@ pub enum Encoder<'a> {
     UInt8(UInt8Encoder<'a>),
     UInt16(UInt16Encoder<'a>),
     UInt32(UInt32Encoder<'a>),
+    Oid(OidEncoder<'a>),
     Int8(Int8Encoder<'a>),
     Int16(Int16Encoder<'a>),
     Int32(Int32Encoder<'a>),
@@ -41,6 +154,12 @@ pub enum Encoder<'a> {
     Float16(Float16Encoder<'a>),
     Float32(Float32Encoder<'a>),
     Float64(Float64Encoder<'a>),
+    Decimal128(Decimal128Encoder<'a>),
+    Numeric(NumericEncoder<'a>),
+    IntCast(IntCastEncoder),
+    Float16AsFloat8(Float16AsFloat8Encoder<'a>),
+    Float32AsFloat8(Float32AsFloat8Encoder<'a>),
+    Date32AsTimestamp(Date32AsTimestampEncoder<'a>),
     TimestampMicrosecond(TimestampMicrosecondEncoder<'a>),
     TimestampMillisecond(TimestampMillisecondEncoder<'a>),
     TimestampSecond(TimestampSecondEncoder<'a>),
@@ -58,36 +177,55 @@ pub enum Encoder<'a> {
     List(ListEncoder<'a>),
     LargeList(LargeListEncoder<'a>),
     Struct(StructEncoder<'a>),
+    Point(PointEncoder<'a>),
+    PointPair(PointPairEncoder<'a>),
+    Circle(CircleEncoder<'a>),
+    PointList(PointListEncoder<'a>),
+    LargePointList(LargePointListEncoder<'a>),
 }
 
 #[inline]
-const fn type_size_fixed(size: TypeSize) -> usize {
+fn type_size_fixed(size: TypeSize) -> Result<usize, ErrorKind> {
     match size {
-        TypeSize::Fixed(v) => v,
-        _ => panic!("attempted to extract a fixed size for a variable sized type"),
+        TypeSize::Fixed(v) => Ok(v),
+        _ => Err(ErrorKind::Encode {
+            reason: "attempted to extract a fixed size for a variable sized type".to_string(),
+        }),
     }
 }
 
+const MAX_RENDERED_VALUE_LEN: usize = 256;
+
+/// Renders a value as text for error messages, truncating long values
+pub(crate) fn render_value(arr: &dyn Array, row: usize) -> Option<String> {
+    let mut value = arrow_cast::display::array_value_to_string(arr, row).ok()?;
+    if value.len() > MAX_RENDERED_VALUE_LEN {
+        let mut end = MAX_RENDERED_VALUE_LEN;
+        while !value.is_char_boundary(end) {
+            end -= 1;
+        }
+        value.truncate(end);
+        value.push_str("...");
+    }
+    Some(value)
+}
+
+#[inline]
+fn type_oid(field: &str, tp: &PostgresType) -> Result<u32, ErrorKind> {
+    tp.oid().ok_or_else(|| ErrorKind::TypeOidUnknown {
+        field: field.to_string(),
+        tp: tp.clone(),
+    })
+}
+
 macro_rules! impl_encode {
     ($struct_name:ident, $field_size:expr, $transform:expr, $write:expr) => {
-        impl<'a> Encode for $struct_name<'a> {
-            fn encode(&self, row: usize, buf: &mut BytesMut) -> Result<(), ErrorKind> {
-                if self.arr.is_null(row) {
-                    buf.put_i32(-1)
-                } else {
-                    buf.put_i32($field_size as i32);
-                    let v = self.arr.value(row);
-                    let tv = $transform(v);
-                    $write(buf, tv);
-                }
-                Ok(())
-            }
-            fn byte_size_hint(&self) -> Result<usize, ErrorKind> {
-                let null_count = self.arr.null_count();
-                let item_count = self.arr.len();
-                Ok((item_count - null_count) * $field_size + item_count)
-            }
-        }
+        impl_encode_fallible!(
+            $struct_name,
+            $field_size,
+            |v| Ok::<_, ErrorKind>($transform(v)),
+            $write
+        );
     };
 }
 
@@ -100,7 +238,7 @@ macro_rules! impl_encode_fallible {
                 } else {
                     buf.put_i32($field_size as i32);
                     let v = self.arr.value(row);
-                    let tv = $transform(&self.field, v)?;
+                    let tv = $transform(v)?;
                     $write(buf, tv);
                 }
                 Ok(())
@@ -108,7 +246,38 @@ macro_rules! impl_encode_fallible {
             fn byte_size_hint(&self) -> Result<usize, ErrorKind> {
                 let null_count = self.arr.null_count();
                 let item_count = self.arr.len();
-                Ok((item_count - null_count) * $field_size + item_count)
+                Ok((item_count - null_count) * $field_size + item_count * 4)
+            }
+            fn add_column_sizes(
+                &self,
+                rows: Range<usize>,
+                sizes: &mut [usize],
+            ) -> Result<bool, ErrorKind> {
+                add_fixed_sizes(self.arr, $field_size, rows, sizes);
+                Ok(true)
+            }
+            fn scatter_column(
+                &self,
+                rows: Range<usize>,
+                out: &mut [MaybeUninit<u8>],
+                offsets: &mut [usize],
+            ) -> Result<(), ErrorKind> {
+                let field_size: usize = $field_size;
+                // columns without nulls skip the validity lookups
+                let has_nulls = self.arr.null_count() != 0;
+                for (row, offset) in rows.zip(offsets.iter_mut()) {
+                    write_at(out, offset, |dst| {
+                        if has_nulls && self.arr.is_null(row) {
+                            dst.put_i32(-1);
+                        } else {
+                            dst.put_i32(field_size as i32);
+                            let tv = $transform(self.arr.value(row))?;
+                            $write(dst, tv);
+                        }
+                        Ok::<_, ErrorKind>(())
+                    })?;
+                }
+                Ok(())
             }
         }
     };
@@ -120,7 +289,7 @@ pub struct BooleanEncoder<'a> {
 }
 impl_encode!(
     BooleanEncoder,
-    type_size_fixed(PostgresType::Bool.size()),
+    type_size_fixed(PostgresType::Bool.size())?,
     u8::from,
     BufMut::put_u8
 );
@@ -131,7 +300,7 @@ pub struct UInt8Encoder<'a> {
 }
 impl_encode!(
     UInt8Encoder,
-    type_size_fixed(PostgresType::Int2.size()),
+    type_size_fixed(PostgresType::Int2.size())?,
     i16::from,
     BufMut::put_i16
 );
@@ -142,7 +311,7 @@ pub struct UInt16Encoder<'a> {
 }
 impl_encode!(
     UInt16Encoder,
-    type_size_fixed(PostgresType::Int4.size()),
+    type_size_fixed(PostgresType::Int4.size())?,
     i32::from,
     BufMut::put_i32
 );
@@ -153,18 +322,30 @@ pub struct UInt32Encoder<'a> {
 }
 impl_encode!(
     UInt32Encoder,
-    type_size_fixed(PostgresType::Int8.size()),
+    type_size_fixed(PostgresType::Int8.size())?,
     i64::from,
     BufMut::put_i64
 );
 
+/// Writes UInt32 values unchanged, as used by `OID` and `REGCLASS`
+#[derive(Debug)]
+pub struct OidEncoder<'a> {
+    arr: &'a arrow_array::UInt32Array,
+}
+impl_encode!(
+    OidEncoder,
+    type_size_fixed(PostgresType::Oid.size())?,
+    identity,
+    BufMut::put_u32
+);
+
 #[derive(Debug)]
 pub struct Int8Encoder<'a> {
     arr: &'a arrow_array::Int8Array,
 }
 impl_encode!(
     Int8Encoder,
-    type_size_fixed(PostgresType::Int2.size()),
+    type_size_fixed(PostgresType::Int2.size())?,
     i16::from,
     BufMut::put_i16
 );
@@ -175,7 +356,7 @@ pub struct Int16Encoder<'a> {
 }
 impl_encode!(
     Int16Encoder,
-    type_size_fixed(PostgresType::Int2.size()),
+    type_size_fixed(PostgresType::Int2.size())?,
     identity,
     BufMut::put_i16
 );
@@ -186,7 +367,7 @@ pub struct Int32Encoder<'a> {
 }
 impl_encode!(
     Int32Encoder,
-    type_size_fixed(PostgresType::Int4.size()),
+    type_size_fixed(PostgresType::Int4.size())?,
     identity,
     BufMut::put_i32
 );
@@ -197,7 +378,7 @@ pub struct Int64Encoder<'a> {
 }
 impl_encode!(
     Int64Encoder,
-    type_size_fixed(PostgresType::Int8.size()),
+    type_size_fixed(PostgresType::Int8.size())?,
     identity,
     BufMut::put_i64
 );
@@ -208,7 +389,7 @@ pub struct Float16Encoder<'a> {
 }
 impl_encode!(
     Float16Encoder,
-    type_size_fixed(PostgresType::Float4.size()),
+    type_size_fixed(PostgresType::Float4.size())?,
     f32::from,
     BufMut::put_f32
 );
@@ -219,7 +400,7 @@ pub struct Float32Encoder<'a> {
 }
 impl_encode!(
     Float32Encoder,
-    type_size_fixed(PostgresType::Float4.size()),
+    type_size_fixed(PostgresType::Float4.size())?,
     identity,
     BufMut::put_f32
 );
@@ -230,18 +411,382 @@ pub struct Float64Encoder<'a> {
 }
 impl_encode!(
     Float64Encoder,
-    type_size_fixed(PostgresType::Float8.size()),
+    type_size_fixed(PostgresType::Float8.size())?,
     identity,
     BufMut::put_f64
 );
 
-const PG_BASE_TIMESTAMP_OFFSET_US: i64 = 946_684_800_000_000; // microseconds between 2000-01-01 at midnight (Postgres's epoch) and 1970-01-01 (Arrow's / UNIX epoch)
+// Postgres' money type stores an integer number of the locale's smallest currency unit.
+// We assume a locale with two fractional digits (e.g. cents), which is the default.
+const PG_MONEY_SCALE: i8 = 2;
+
+#[inline(always)]
+fn convert_decimal_to_pg_money(field: &str, v: i128, scale: i8) -> Result<i64, ErrorKind> {
+    let cents = if scale <= PG_MONEY_SCALE {
+        10_i128
+            .checked_pow((PG_MONEY_SCALE - scale) as u32)
+            .and_then(|factor| v.checked_mul(factor))
+    } else {
+        let factor = 10_i128.pow((scale - PG_MONEY_SCALE) as u32);
+        if v % factor != 0 {
+            return Err(ErrorKind::Encode {
+                reason: format!(
+                    "Converting decimal with scale {scale} in field {field} to money would lose precision"
+                ),
+            });
+        }
+        Some(v / factor)
+    };
+    cents
+        .and_then(|v| i64::try_from(v).ok())
+        .ok_or_else(|| ErrorKind::Encode {
+            reason: format!("Overflow converting decimal in field {field} to money"),
+        })
+}
+
+#[derive(Debug)]
+pub struct Decimal128Encoder<'a> {
+    arr: &'a arrow_array::Decimal128Array,
+    field: String,
+    scale: i8,
+}
+
+impl Decimal128Encoder<'_> {
+    fn put(&self, row: usize, buf: &mut impl BufMut) -> Result<(), ErrorKind> {
+        if self.arr.is_null(row) {
+            buf.put_i32(-1)
+        } else {
+            buf.put_i32(type_size_fixed(PostgresType::Money.size())? as i32);
+            let v = convert_decimal_to_pg_money(&self.field, self.arr.value(row), self.scale)?;
+            buf.put_i64(v);
+        }
+        Ok(())
+    }
+}
+
+impl Encode for Decimal128Encoder<'_> {
+    fn encode(&self, row: usize, buf: &mut BytesMut) -> Result<(), ErrorKind> {
+        self.put(row, buf)
+    }
+    fn byte_size_hint(&self) -> Result<usize, ErrorKind> {
+        let null_count = self.arr.null_count();
+        let item_count = self.arr.len();
+        Ok(
+            (item_count - null_count) * type_size_fixed(PostgresType::Money.size())?
+                + item_count * 4,
+        )
+    }
+    fn add_column_sizes(&self, rows: Range<usize>, sizes: &mut [usize]) -> Result<bool, ErrorKind> {
+        let field_size = type_size_fixed(PostgresType::Money.size())?;
+        add_fixed_sizes(self.arr, field_size, rows, sizes);
+        Ok(true)
+    }
+    fn scatter_column(
+        &self,
+        rows: Range<usize>,
+        out: &mut [MaybeUninit<u8>],
+        offsets: &mut [usize],
+    ) -> Result<(), ErrorKind> {
+        for (row, offset) in rows.zip(offsets.iter_mut()) {
+            write_at(out, offset, |dst| self.put(row, dst))?;
+        }
+        Ok(())
+    }
+}
+
+// numeric sign flags, see src/backend/utils/adt/numeric.c in the Postgres source
+const NUMERIC_POS: u16 = 0x0000;
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+
+/// Writes the number `digits * 10^-scale` (`digits` being ASCII decimal digits)
+/// as a Postgres numeric, which stores base 10000 digits
+fn write_numeric(buf: &mut BytesMut, negative: bool, digits: &[u8], scale: i32) {
+    let n = digits.len() as i32;
+    let n_int = (n - scale).max(0) as usize;
+    let n_frac = scale.max(0) as usize;
+    // pad the integer part on the left and the fractional part on the right
+    // so that both are made up of whole groups of 4 decimal digits
+    let int_pad = (4 - n_int % 4) % 4;
+    let frac_pad = (4 - n_frac % 4) % 4;
+    let mut padded = Vec::with_capacity(int_pad + n_int + n_frac + frac_pad);
+    padded.resize(int_pad, b'0');
+    if scale <= 0 {
+        padded.extend_from_slice(digits);
+        padded.resize(int_pad + n_int, b'0');
+    } else {
+        padded.resize(
+            int_pad + (n_int + n_frac).saturating_sub(digits.len()),
+            b'0',
+        );
+        padded.extend_from_slice(digits);
+    }
+    padded.resize(int_pad + n_int + n_frac + frac_pad, b'0');
+
+    let mut groups: Vec<i16> = padded
+        .chunks(4)
+        .map(|c| c.iter().fold(0, |acc, d| acc * 10 + (d - b'0') as i16))
+        .collect();
+    let mut weight = ((int_pad + n_int) / 4) as i16 - 1;
+    let leading_zeros = groups.iter().take_while(|g| **g == 0).count();
+    groups.drain(..leading_zeros);
+    weight -= leading_zeros as i16;
+    while groups.last() == Some(&0) {
+        groups.pop();
+    }
+    let sign = if groups.is_empty() {
+        weight = 0;
+        NUMERIC_POS
+    } else if negative {
+        NUMERIC_NEG
+    } else {
+        NUMERIC_POS
+    };
+
+    buf.put_i32(8 + 2 * groups.len() as i32);
+    buf.put_i16(groups.len() as i16);
+    buf.put_i16(weight);
+    buf.put_u16(sign);
+    buf.put_i16(n_frac as i16);
+    for group in groups {
+        buf.put_i16(group);
+    }
+}
+
+#[inline]
+fn write_special_numeric(buf: &mut BytesMut, sign: u16) {
+    buf.put_i32(8);
+    buf.put_i16(0); // ndigits
+    buf.put_i16(0); // weight
+    buf.put_u16(sign);
+    buf.put_i16(0); // dscale
+}
+
+/// Writes a float as numeric using its shortest round-tripping decimal representation
+fn write_float_numeric(buf: &mut BytesMut, v: impl std::fmt::LowerExp + Into<f64> + Copy) {
+    let f: f64 = v.into();
+    if f.is_nan() {
+        return write_special_numeric(buf, NUMERIC_NAN);
+    }
+    if f.is_infinite() {
+        let sign = if f > 0.0 { NUMERIC_PINF } else { NUMERIC_NINF };
+        return write_special_numeric(buf, sign);
+    }
+    // formats as e.g. -1.2345e-7
+    let repr = format!("{v:e}");
+    let (mantissa, exponent) = repr.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+    let (negative, mantissa) = match mantissa.strip_prefix('-') {
+        Some(m) => (true, m),
+        None => (false, mantissa),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+    let digits = [int.as_bytes(), frac.as_bytes()].concat();
+    write_numeric(buf, negative, &digits, frac.len() as i32 - exponent);
+}
+
+/// The size of a `NUMERIC` written by `write_numeric` for the integer `magnitude`
+/// times 10^-`scale`, length prefix included, computed from its decimal digits
+fn numeric_size(magnitude: u128, scale: i32) -> usize {
+    if magnitude == 0 {
+        return 4 + 8;
+    }
+    let n_digits = magnitude.ilog10() as usize + 1;
+    let mut trailing_zeros = 0;
+    let mut rest = magnitude;
+    while rest.is_multiple_of(10) {
+        rest /= 10;
+        trailing_zeros += 1;
+    }
+    // the zeros that align the fractional part, or that a negative scale appends,
+    // to whole groups of 4 decimal digits counted from the decimal point
+    let shift = if scale > 0 {
+        ((4 - scale % 4) % 4) as usize
+    } else {
+        scale.unsigned_abs() as usize
+    };
+    // the groups from the first to the last non-zero one
+    let groups = (n_digits + shift).div_ceil(4) - (trailing_zeros + shift) / 4;
+    4 + 8 + 2 * groups
+}
+
+#[derive(Debug)]
+enum NumericValues<'a> {
+    Int(arrow_array::Int64Array),
+    Float32(&'a arrow_array::Float32Array),
+    Float64(&'a arrow_array::Float64Array),
+    Decimal128(&'a arrow_array::Decimal128Array),
+}
+
+#[derive(Debug)]
+pub struct NumericEncoder<'a> {
+    values: NumericValues<'a>,
+}
+
+impl NumericEncoder<'_> {
+    fn arr(&self) -> &dyn Array {
+        match &self.values {
+            NumericValues::Int(arr) => arr,
+            NumericValues::Float32(arr) => *arr,
+            NumericValues::Float64(arr) => *arr,
+            NumericValues::Decimal128(arr) => *arr,
+        }
+    }
+}
+
+impl Encode for NumericEncoder<'_> {
+    fn encode(&self, row: usize, buf: &mut BytesMut) -> Result<(), ErrorKind> {
+        if self.arr().is_null(row) {
+            buf.put_i32(-1);
+            return Ok(());
+        }
+        match &self.values {
+            NumericValues::Int(arr) => {
+                let v = arr.value(row);
+                write_numeric(buf, v < 0, v.unsigned_abs().to_string().as_bytes(), 0)
+            }
+            NumericValues::Float32(arr) => write_float_numeric(buf, arr.value(row)),
+            NumericValues::Float64(arr) => write_float_numeric(buf, arr.value(row)),
+            NumericValues::Decimal128(arr) => {
+                let v = arr.value(row);
+                let digits = v.unsigned_abs().to_string();
+                write_numeric(buf, v < 0, digits.as_bytes(), arr.scale() as i32)
+            }
+        }
+        Ok(())
+    }
+    fn byte_size_hint(&self) -> Result<usize, ErrorKind> {
+        let mut sizes = vec![0; self.arr().len()];
+        self.add_column_size_bounds(0..sizes.len(), &mut sizes)?;
+        Ok(sizes.iter().sum())
+    }
+    fn add_column_sizes(&self, rows: Range<usize>, sizes: &mut [usize]) -> Result<bool, ErrorKind> {
+        let arr = self.arr();
+        for (row, size) in rows.zip(sizes.iter_mut()) {
+            *size += if arr.is_null(row) {
+                4
+            } else {
+                match &self.values {
+                    NumericValues::Int(arr) => {
+                        numeric_size(arr.value(row).unsigned_abs() as u128, 0)
+                    }
+                    NumericValues::Decimal128(arr) => {
+                        numeric_size(arr.value(row).unsigned_abs(), arr.scale() as i32)
+                    }
+                    // formatting a float to size it would format it twice,
+                    // so the batch is encoded row by row
+                    NumericValues::Float32(_) | NumericValues::Float64(_) => return Ok(false),
+                }
+            };
+        }
+        Ok(true)
+    }
+    fn add_column_size_bounds(
+        &self,
+        rows: Range<usize>,
+        sizes: &mut [usize],
+    ) -> Result<(), ErrorKind> {
+        // the digits of a float are only known once it is formatted, so count the most
+        // significant digits its shortest representation can have
+        let max_digits: usize = match &self.values {
+            NumericValues::Float32(_) => 9,
+            NumericValues::Float64(_) => 17,
+            NumericValues::Int(_) | NumericValues::Decimal128(_) => {
+                self.add_column_sizes(rows, sizes)?;
+                return Ok(());
+            }
+        };
+        // the header, then digits spanning at most one more base 10000 group than they fill
+        add_fixed_sizes(
+            self.arr(),
+            8 + 2 * (max_digits + 3).div_ceil(4),
+            rows,
+            sizes,
+        );
+        Ok(())
+    }
+}
+
+/// Widens any integer column to `INT2`, `INT4` or `INT8`
+#[derive(Debug)]
+pub struct IntCastEncoder {
+    arr: arrow_array::Int64Array,
+    output_size: usize,
+}
+
+impl IntCastEncoder {
+    fn put(&self, row: usize, buf: &mut impl BufMut) -> Result<(), ErrorKind> {
+        if self.arr.is_null(row) {
+            buf.put_i32(-1);
+        } else {
+            buf.put_i32(self.output_size as i32);
+            let v = self.arr.value(row);
+            match self.output_size {
+                2 => buf.put_i16(v as i16),
+                4 => buf.put_i32(v as i32),
+                _ => buf.put_i64(v),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Encode for IntCastEncoder {
+    fn encode(&self, row: usize, buf: &mut BytesMut) -> Result<(), ErrorKind> {
+        self.put(row, buf)
+    }
+    fn byte_size_hint(&self) -> Result<usize, ErrorKind> {
+        let null_count = self.arr.null_count();
+        let item_count = self.arr.len();
+        Ok((item_count - null_count) * self.output_size + item_count * 4)
+    }
+    fn add_column_sizes(&self, rows: Range<usize>, sizes: &mut [usize]) -> Result<bool, ErrorKind> {
+        add_fixed_sizes(&self.arr, self.output_size, rows, sizes);
+        Ok(true)
+    }
+    fn scatter_column(
+        &self,
+        rows: Range<usize>,
+        out: &mut [MaybeUninit<u8>],
+        offsets: &mut [usize],
+    ) -> Result<(), ErrorKind> {
+        for (row, offset) in rows.zip(offsets.iter_mut()) {
+            write_at(out, offset, |dst| self.put(row, dst))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct Float16AsFloat8Encoder<'a> {
+    arr: &'a arrow_array::Float16Array,
+}
+impl_encode!(
+    Float16AsFloat8Encoder,
+    type_size_fixed(PostgresType::Float8.size())?,
+    f64::from,
+    BufMut::put_f64
+);
+
+#[derive(Debug)]
+pub struct Float32AsFloat8Encoder<'a> {
+    arr: &'a arrow_array::Float32Array,
+}
+impl_encode!(
+    Float32AsFloat8Encoder,
+    type_size_fixed(PostgresType::Float8.size())?,
+    f64::from,
+    BufMut::put_f64
+);
+
+pub(crate) const PG_BASE_TIMESTAMP_OFFSET_US: i64 = 946_684_800_000_000; // microseconds between 2000-01-01 at midnight (Postgres's epoch) and 1970-01-01 (Arrow's / UNIX epoch)
 const PG_BASE_TIMESTAMP_OFFSET_MS: i64 = 946_684_800_000; // milliseconds between 2000-01-01 at midnight (Postgres's epoch) and 1970-01-01 (Arrow's / UNIX epoch)
 const PG_BASE_TIMESTAMP_OFFSET_S: i64 = 946_684_800; // seconds between 2000-01-01 at midnight (Postgres's epoch) and 1970-01-01 (Arrow's / UNIX epoch)
 
 #[inline(always)]
 fn convert_arrow_timestamp_microseconds_to_pg_timestamp(
-    _field: &str,
     timestamp_us: i64,
 ) -> Result<i64, ErrorKind> {
     // adjust the timestamp from microseconds since 1970-01-01 to microseconds since 2000-01-01 checking for overflows and underflow
@@ -255,7 +800,6 @@ fn convert_arrow_timestamp_microseconds_to_pg_timestamp(
 /// Convert from Arrow timestamps (milliseconds since 1970-01-01) to Postgres timestamps (microseconds since 2000-01-01)
 #[inline(always)]
 fn convert_arrow_timestamp_milliseconds_to_pg_timestamp(
-    _field: &str,
     timestamp_ms: i64,
 ) -> Result<i64, ErrorKind> {
     let timestamp_ms = timestamp_ms.checked_sub(PG_BASE_TIMESTAMP_OFFSET_MS).ok_or_else(|| ErrorKind::Encode {
@@ -270,10 +814,7 @@ fn convert_arrow_timestamp_milliseconds_to_pg_timestamp(
 }
 
 #[inline(always)]
-fn convert_arrow_timestamp_seconds_to_pg_timestamp(
-    _field: &str,
-    timestamp_s: i64,
-) -> Result<i64, ErrorKind> {
+fn convert_arrow_timestamp_seconds_to_pg_timestamp(timestamp_s: i64) -> Result<i64, ErrorKind> {
     let timestamp_s = timestamp_s.checked_sub(PG_BASE_TIMESTAMP_OFFSET_S).ok_or_else(|| ErrorKind::Encode {
         reason: "Underflow converting seconds since 1970-01-01 (Arrow) to microseconds since 2000-01-01 (Postgres)".to_string(),
     })?;
@@ -288,11 +829,10 @@ fn convert_arrow_timestamp_seconds_to_pg_timestamp(
 #[derive(Debug)]
 pub struct TimestampMicrosecondEncoder<'a> {
     arr: &'a arrow_array::TimestampMicrosecondArray,
-    field: String,
 }
 impl_encode_fallible!(
     TimestampMicrosecondEncoder,
-    type_size_fixed(PostgresType::Timestamp.size()),
+    type_size_fixed(PostgresType::Timestamp.size())?,
     convert_arrow_timestamp_microseconds_to_pg_timestamp,
     BufMut::put_i64
 );
@@ -300,11 +840,10 @@ impl_encode_fallible!(
 #[derive(Debug)]
 pub struct TimestampMillisecondEncoder<'a> {
     arr: &'a arrow_array::TimestampMillisecondArray,
-    field: String,
 }
 impl_encode_fallible!(
     TimestampMillisecondEncoder,
-    type_size_fixed(PostgresType::Timestamp.size()),
+    type_size_fixed(PostgresType::Timestamp.size())?,
     convert_arrow_timestamp_milliseconds_to_pg_timestamp,
     BufMut::put_i64
 );
@@ -312,19 +851,18 @@ impl_encode_fallible!(
 #[derive(Debug)]
 pub struct TimestampSecondEncoder<'a> {
     arr: &'a arrow_array::TimestampSecondArray,
-    field: String,
 }
 impl_encode_fallible!(
     TimestampSecondEncoder,
-    type_size_fixed(PostgresType::Timestamp.size()),
+    type_size_fixed(PostgresType::Timestamp.size())?,
     convert_arrow_timestamp_seconds_to_pg_timestamp,
     BufMut::put_i64
 );
 
-const PG_BASE_DATE_OFFSET: i32 = 10_957; // Number of days between PostgreSQL's epoch (2000-01-01) and Arrow's / UNIX epoch (1970-01-01)
+pub(crate) const PG_BASE_DATE_OFFSET: i32 = 10_957; // Number of days between PostgreSQL's epoch (2000-01-01) and Arrow's / UNIX epoch (1970-01-01)
 
 #[inline(always)]
-fn convert_arrow_date32_to_postgres_date(_field: &str, date: i32) -> Result<i32, ErrorKind> {
+fn convert_arrow_date32_to_postgres_date(date: i32) -> Result<i32, ErrorKind> {
     // adjust the date from days since 1970-01-01 to days since 2000-01-01 checking for overflows and underflow
     date.checked_sub(PG_BASE_DATE_OFFSET).ok_or_else(|| ErrorKind::Encode {
         reason: "Underflow converting days since 1970-01-01 (Arrow) to days since 2000-01-01 (Postgres)".to_string(),
@@ -334,7 +872,6 @@ fn convert_arrow_date32_to_postgres_date(_field: &str, date: i32) -> Result<i32,
 #[derive(Debug)]
 pub struct Date32Encoder<'a> {
     arr: &'a arrow_array::Date32Array,
-    field: String,
 }
 impl_encode_fallible!(
     Date32Encoder,
@@ -343,10 +880,30 @@ impl_encode_fallible!(
     BufMut::put_i32
 );
 
-fn convert_arrow_time_seconds_to_postgres_time(
-    _field: &str,
-    time_s: i32,
-) -> Result<i64, ErrorKind> {
+const NUM_US_PER_DAY: i64 = 86_400_000_000;
+
+#[inline(always)]
+fn convert_arrow_date32_to_postgres_timestamp(date: i32) -> Result<i64, ErrorKind> {
+    // midnight of the date in microseconds since 2000-01-01
+    let date = convert_arrow_date32_to_postgres_date(date)? as i64;
+    date.checked_mul(NUM_US_PER_DAY)
+        .ok_or_else(|| ErrorKind::Encode {
+            reason: "Overflow converting days to microseconds".to_string(),
+        })
+}
+
+#[derive(Debug)]
+pub struct Date32AsTimestampEncoder<'a> {
+    arr: &'a arrow_array::Date32Array,
+}
+impl_encode_fallible!(
+    Date32AsTimestampEncoder,
+    type_size_fixed(PostgresType::Timestamp.size())?,
+    convert_arrow_date32_to_postgres_timestamp,
+    BufMut::put_i64
+);
+
+fn convert_arrow_time_seconds_to_postgres_time(time_s: i32) -> Result<i64, ErrorKind> {
     // convert to microseconds, checking for overflows
     let time_s = time_s as i64;
     time_s
@@ -356,10 +913,7 @@ fn convert_arrow_time_seconds_to_postgres_time(
         })
 }
 
-fn convert_arrow_time_milliseconds_to_postgres_time(
-    _field: &str,
-    time_ms: i32,
-) -> Result<i64, ErrorKind> {
+fn convert_arrow_time_milliseconds_to_postgres_time(time_ms: i32) -> Result<i64, ErrorKind> {
     // convert to microseconds, checking for overflows
     let time_ms = time_ms as i64;
     time_ms.checked_mul(1_000).ok_or_else(|| ErrorKind::Encode {
@@ -370,11 +924,10 @@ fn convert_arrow_time_milliseconds_to_postgres_time(
 #[derive(Debug)]
 pub struct Time32MillisecondEncoder<'a> {
     arr: &'a arrow_array::Time32MillisecondArray,
-    field: String,
 }
 impl_encode_fallible!(
     Time32MillisecondEncoder,
-    type_size_fixed(PostgresType::Time.size()),
+    type_size_fixed(PostgresType::Time.size())?,
     convert_arrow_time_milliseconds_to_postgres_time,
     BufMut::put_i64
 );
@@ -382,11 +935,10 @@ impl_encode_fallible!(
 #[derive(Debug)]
 pub struct Time32SecondEncoder<'a> {
     arr: &'a arrow_array::Time32SecondArray,
-    field: String,
 }
 impl_encode_fallible!(
     Time32SecondEncoder,
-    type_size_fixed(PostgresType::Time.size()),
+    type_size_fixed(PostgresType::Time.size())?,
     convert_arrow_time_seconds_to_postgres_time,
     BufMut::put_i64
 );
@@ -407,7 +959,7 @@ const NUM_US_PER_MS: i64 = 1_000;
 const NUM_US_PER_S: i64 = 1_000_000;
 
 #[inline]
-fn write_duration(buf: &mut BytesMut, duration_us: i64) {
+fn write_duration<B: BufMut>(buf: &mut B, duration_us: i64) {
     buf.put_i64(duration_us);
     buf.put_i32(0); // days
     buf.put_i32(0); // months
@@ -416,12 +968,11 @@ fn write_duration(buf: &mut BytesMut, duration_us: i64) {
 #[derive(Debug)]
 pub struct DurationMillisecondEncoder<'a> {
     arr: &'a arrow_array::DurationMillisecondArray,
-    field: String,
 }
 impl_encode_fallible!(
     DurationMillisecondEncoder,
-    type_size_fixed(PostgresType::Interval.size()),
-    |_: &str, v: i64| v.mul_checked(NUM_US_PER_MS).map_err(|_| {
+    type_size_fixed(PostgresType::Interval.size())?,
+    |v: i64| v.mul_checked(NUM_US_PER_MS).map_err(|_| {
         ErrorKind::Encode {
             reason: "Overflow encoding millisecond duration as microseconds".to_string(),
         }
@@ -432,13 +983,12 @@ impl_encode_fallible!(
 #[derive(Debug)]
 pub struct DurationSecondEncoder<'a> {
     arr: &'a arrow_array::DurationSecondArray,
-    field: String,
 }
 
 impl_encode_fallible!(
     DurationSecondEncoder,
-    type_size_fixed(PostgresType::Interval.size()),
-    |_: &str, v: i64| v.mul_checked(NUM_US_PER_S).map_err(|_| {
+    type_size_fixed(PostgresType::Interval.size())?,
+    |v: i64| v.mul_checked(NUM_US_PER_S).map_err(|_| {
         ErrorKind::Encode {
             reason: "Overflow encoding seconds duration as microseconds".to_string(),
         }
@@ -468,17 +1018,207 @@ impl<T: OffsetSizeTrait> Encode for GenericBinaryEncoder<'_, T> {
         Ok(())
     }
     fn byte_size_hint(&self) -> Result<usize, ErrorKind> {
-        let mut total = 0;
-        for row in 0..self.arr.len() {
-            total += self.arr.value(row).len();
+        Ok(offset_byte_size(self.arr, self.arr.value_offsets(), 0))
+    }
+    fn add_column_sizes(&self, rows: Range<usize>, sizes: &mut [usize]) -> Result<bool, ErrorKind> {
+        add_offset_sizes(self.arr, self.arr.value_offsets(), rows, sizes, 0)
+    }
+    fn scatter_column(
+        &self,
+        rows: Range<usize>,
+        out: &mut [MaybeUninit<u8>],
+        offsets: &mut [usize],
+    ) -> Result<(), ErrorKind> {
+        for (row, offset) in rows.zip(offsets.iter_mut()) {
+            write_at(out, offset, |dst| {
+                if self.arr.is_null(row) {
+                    dst.put_i32(-1);
+                } else {
+                    let v = self.arr.value(row);
+                    dst.put_i32(v.len() as i32);
+                    dst.put_slice(v);
+                }
+            });
         }
-        Ok(total)
+        Ok(())
+    }
+    fn borrowed_value(&self, row: usize) -> Option<BorrowedValue<'_>> {
+        if self.arr.is_null(row) {
+            return None;
+        }
+        Some(BorrowedValue {
+            prefix: &[],
+            bytes: self.arr.value(row),
+        })
+    }
+}
+
+/// The encoded size of variable-length values, read from the Arrow offsets,
+/// plus `extra` bytes per non-null value
+fn offset_byte_size<T: OffsetSizeTrait>(
+    arr: &dyn Array,
+    value_offsets: &[T],
+    extra: usize,
+) -> usize {
+    let n = arr.len();
+    if arr.null_count() == 0 {
+        return (value_offsets[n] - value_offsets[0]).as_usize() + n * (4 + extra);
+    }
+    // null slots may still span bytes in the values buffer
+    (0..n)
+        .filter(|row| !arr.is_null(*row))
+        .map(|row| (value_offsets[row + 1] - value_offsets[row]).as_usize() + extra)
+        .sum::<usize>()
+        + n * 4
+}
+
+/// Adds the sizes of variable-length values, read from the Arrow offsets, plus `extra`
+/// bytes per non-null value. Returns false if a value is too large to encode.
+fn add_offset_sizes<T: OffsetSizeTrait>(
+    arr: &dyn Array,
+    value_offsets: &[T],
+    rows: Range<usize>,
+    sizes: &mut [usize],
+    extra: usize,
+) -> Result<bool, ErrorKind> {
+    for (row, size) in rows.zip(sizes.iter_mut()) {
+        if arr.is_null(row) {
+            *size += 4;
+            continue;
+        }
+        let len = (value_offsets[row + 1] - value_offsets[row]).as_usize() + extra;
+        if len > i32::MAX as usize {
+            // leave the error to the row by row path
+            return Ok(false);
+        }
+        *size += 4 + len;
     }
+    Ok(true)
 }
 
 type BinaryEncoder<'a> = GenericBinaryEncoder<'a, i32>;
 type LargeBinaryEncoder<'a> = GenericBinaryEncoder<'a, i64>;
 
+/// Parses the hexadecimal text form of a UUID, ignoring hyphens and optional braces
+fn parse_uuid(field: &str, v: &str) -> Result<[u8; 16], ErrorKind> {
+    let invalid = || ErrorKind::Encode {
+        reason: format!("invalid UUID {v:?} in field {field}"),
+    };
+    let hex = v
+        .strip_prefix('{')
+        .and_then(|v| v.strip_suffix('}'))
+        .unwrap_or(v);
+    let mut nibbles = hex.chars().filter(|c| *c != '-').map(|c| c.to_digit(16));
+    let mut uuid = [0u8; 16];
+    for byte in uuid.iter_mut() {
+        match (nibbles.next(), nibbles.next()) {
+            (Some(Some(hi)), Some(Some(lo))) => *byte = (hi << 4 | lo) as u8,
+            _ => return Err(invalid()),
+        }
+    }
+    if nibbles.next().is_some() {
+        return Err(invalid());
+    }
+    Ok(uuid)
+}
+
+// tsvector limits, see src/include/tsearch/ts_type.h in the Postgres source
+const TSVECTOR_MAX_LEXEME_LEN: usize = (1 << 11) - 1;
+const TSVECTOR_MAX_POSITION: u16 = (1 << 14) - 1;
+const TSVECTOR_MAX_POSITIONS: usize = 256;
+
+/// Writes a tsvector given in a simplified text form: whitespace separated lexemes,
+/// each optionally followed by `:` and a comma separated list of positions with an
+/// optional weight (`A`-`D`), e.g. `fat:2,4A cat:3 rat`.
+/// Lexemes are sorted and deduplicated the same way Postgres does.
+fn write_tsvector(field: &str, v: &str, buf: &mut BytesMut) -> Result<(), ErrorKind> {
+    let invalid = |reason: &str| ErrorKind::Encode {
+        reason: format!("invalid tsvector {v:?} in field {field}: {reason}"),
+    };
+    let mut lexemes: BTreeMap<&str, Vec<u16>> = BTreeMap::new();
+    for token in v.split_ascii_whitespace() {
+        let (lexeme, positions) = match token.split_once(':') {
+            Some((lexeme, positions)) => (lexeme, Some(positions)),
+            None => (token, None),
+        };
+        if lexeme.is_empty() || lexeme.contains('\0') {
+            return Err(invalid("lexemes must be non-empty and may not contain NUL"));
+        }
+        if lexeme.len() > TSVECTOR_MAX_LEXEME_LEN {
+            return Err(invalid("lexeme is too long"));
+        }
+        let entry = lexemes.entry(lexeme).or_default();
+        for position in positions.into_iter().flat_map(|p| p.split(',')) {
+            let (digits, weight) = match position.as_bytes().last() {
+                Some(b'A' | b'a') => (&position[..position.len() - 1], 3),
+                Some(b'B' | b'b') => (&position[..position.len() - 1], 2),
+                Some(b'C' | b'c') => (&position[..position.len() - 1], 1),
+                Some(b'D' | b'd') => (&position[..position.len() - 1], 0),
+                _ => (position, 0),
+            };
+            match digits.parse::<u16>() {
+                Ok(p) if (1..=TSVECTOR_MAX_POSITION).contains(&p) => entry.push(weight << 14 | p),
+                _ => return Err(invalid("positions must be between 1 and 16383")),
+            }
+        }
+    }
+    buf.put_i32(lexemes.len() as i32);
+    for (lexeme, mut positions) in lexemes {
+        positions.sort_by_key(|p| p & TSVECTOR_MAX_POSITION);
+        positions.dedup_by_key(|p| *p & TSVECTOR_MAX_POSITION);
+        if positions.len() > TSVECTOR_MAX_POSITIONS {
+            return Err(invalid("too many positions for a single lexeme"));
+        }
+        buf.put_slice(lexeme.as_bytes());
+        buf.put_u8(0);
+        buf.put_u16(positions.len() as u16);
+        for position in positions {
+            buf.put_u16(position);
+        }
+    }
+    Ok(())
+}
+
+/// Checks that `v` is well-formed XML content (Postgres' default `XMLOPTION`),
+/// i.e. an optional declaration followed by text and balanced elements
+fn check_xml_well_formed(field: &str, v: &str) -> Result<(), ErrorKind> {
+    let invalid = |reason: String| ErrorKind::Encode {
+        reason: format!("malformed XML in field {field}: {reason}"),
+    };
+    let mut start = 0;
+    if v.starts_with("<?xml ") {
+        match xmlparser::Tokenizer::from(v).next() {
+            Some(Ok(xmlparser::Token::Declaration { span, .. })) => start = span.end(),
+            Some(Err(e)) => return Err(invalid(e.to_string())),
+            _ => return Err(invalid("invalid XML declaration".to_string())),
+        }
+    }
+    let mut open_elements = Vec::new();
+    for token in xmlparser::Tokenizer::from_fragment(v, start..v.len()) {
+        match token.map_err(|e| invalid(e.to_string()))? {
+            xmlparser::Token::ElementStart { prefix, local, .. } => {
+                open_elements.push((prefix.as_str(), local.as_str()))
+            }
+            xmlparser::Token::ElementEnd { end, .. } => match end {
+                xmlparser::ElementEnd::Open => {}
+                xmlparser::ElementEnd::Empty => {
+                    open_elements.pop();
+                }
+                xmlparser::ElementEnd::Close(prefix, local) => {
+                    if open_elements.pop() != Some((prefix.as_str(), local.as_str())) {
+                        return Err(invalid(format!("unexpected closing tag {local}")));
+                    }
+                }
+            },
+            _ => {}
+        }
+    }
+    if let Some((_, local)) = open_elements.pop() {
+        return Err(invalid(format!("unclosed tag {local}")));
+    }
+    Ok(())
+}
+
 #[derive(Debug)]
 pub struct GenericStringEncoder<'a, T: OffsetSizeTrait> {
     arr: &'a arrow_array::GenericStringArray<T>,
@@ -491,6 +1231,29 @@ impl<T: OffsetSizeTrait> Encode for GenericStringEncoder<'_, T> {
         if self.arr.is_null(row) {
             buf.put_i32(-1);
         } else {
+            match self.output {
+                StringOutputType::Tsvector => {
+                    let base_idx = buf.len();
+                    buf.put_i32(0); // the total number of bytes this element takes up, insert later
+                    write_tsvector(&self.field, self.arr.value(row), buf)?;
+                    let total_len = buf.len() - base_idx - 4;
+                    match i32::try_from(total_len) {
+                        Ok(v) => buf[base_idx..base_idx + 4].copy_from_slice(&v.to_be_bytes()),
+                        Err(_) => return Err(ErrorKind::field_too_large(&self.field, total_len)),
+                    };
+                    return Ok(());
+                }
+                StringOutputType::Uuid => {
+                    let uuid = parse_uuid(&self.field, self.arr.value(row))?;
+                    buf.put_i32(uuid.len() as i32);
+                    buf.put_slice(&uuid);
+                    return Ok(());
+                }
+                StringOutputType::Xml {
+                    check_well_formed: true,
+                } => check_xml_well_formed(&self.field, self.arr.value(row))?,
+                _ => {}
+            }
             let v = self.arr.value(row).as_bytes();
             let mut len = v.len();
             if matches!(self.output, StringOutputType::Jsonb) {
@@ -508,20 +1271,117 @@ impl<T: OffsetSizeTrait> Encode for GenericStringEncoder<'_, T> {
         Ok(())
     }
     fn byte_size_hint(&self) -> Result<usize, ErrorKind> {
-        let mut total = 0;
-        for row in 0..self.arr.len() {
-            total += self.arr.value(row).len();
+        let offsets = self.arr.value_offsets();
+        match self.output {
+            StringOutputType::Tsvector => {
+                let mut sizes = vec![0; self.arr.len()];
+                self.add_column_size_bounds(0..sizes.len(), &mut sizes)?;
+                Ok(sizes.iter().sum())
+            }
+            StringOutputType::Uuid => {
+                let non_null = self.arr.len() - self.arr.null_count();
+                Ok(non_null * 16 + self.arr.len() * 4)
+            }
+            // the JSONB format version
+            StringOutputType::Jsonb => Ok(offset_byte_size(self.arr, offsets, 1)),
+            _ => Ok(offset_byte_size(self.arr, offsets, 0)),
         }
-        if matches!(self.output, StringOutputType::Jsonb) {
-            total += self.arr.len() // For JSONB format version
+    }
+    fn add_column_sizes(&self, rows: Range<usize>, sizes: &mut [usize]) -> Result<bool, ErrorKind> {
+        let offsets = self.arr.value_offsets();
+        match self.output {
+            StringOutputType::Tsvector => Ok(false),
+            StringOutputType::Uuid => {
+                for (row, size) in rows.zip(sizes.iter_mut()) {
+                    *size += if self.arr.is_null(row) { 4 } else { 4 + 16 };
+                }
+                Ok(true)
+            }
+            StringOutputType::Jsonb => add_offset_sizes(self.arr, offsets, rows, sizes, 1),
+            _ => add_offset_sizes(self.arr, offsets, rows, sizes, 0),
         }
-        Ok(total)
+    }
+    fn add_column_size_bounds(
+        &self,
+        rows: Range<usize>,
+        sizes: &mut [usize],
+    ) -> Result<(), ErrorKind> {
+        if !matches!(self.output, StringOutputType::Tsvector) {
+            self.add_column_sizes(rows, sizes)?;
+            return Ok(());
+        }
+        // lexemes are parsed and deduplicated, but a whitespace separated token of `t` bytes
+        // is written in at most `t + 3` bytes and tokens are at least a byte apart,
+        // so a value of `len` bytes takes at most `2 * len + 2` bytes after the lexeme count
+        let offsets = self.arr.value_offsets();
+        for (row, size) in rows.zip(sizes.iter_mut()) {
+            *size += if self.arr.is_null(row) {
+                4
+            } else {
+                let len = (offsets[row + 1] - offsets[row]).as_usize();
+                4 + 4 + 2 * len + 2
+            };
+        }
+        Ok(())
+    }
+    fn scatter_column(
+        &self,
+        rows: Range<usize>,
+        out: &mut [MaybeUninit<u8>],
+        offsets: &mut [usize],
+    ) -> Result<(), ErrorKind> {
+        let jsonb = match self.output {
+            StringOutputType::Text
+            | StringOutputType::Json
+            | StringOutputType::Xml {
+                check_well_formed: false,
+            } => false,
+            StringOutputType::Jsonb => true,
+            // values that are parsed or checked go through encode
+            _ => return scatter_encoded(self, rows, out, offsets),
+        };
+        for (row, offset) in rows.zip(offsets.iter_mut()) {
+            write_at(out, offset, |dst| {
+                if self.arr.is_null(row) {
+                    dst.put_i32(-1);
+                    return;
+                }
+                let v = self.arr.value(row).as_bytes();
+                dst.put_i32((v.len() + jsonb as usize) as i32);
+                if jsonb {
+                    dst.put_u8(1) // JSONB format version
+                }
+                dst.put_slice(v);
+            });
+        }
+        Ok(())
+    }
+    fn borrowed_value(&self, row: usize) -> Option<BorrowedValue<'_>> {
+        let prefix: &'static [u8] = match self.output {
+            StringOutputType::Text
+            | StringOutputType::Json
+            | StringOutputType::Xml {
+                check_well_formed: false,
+            } => &[],
+            StringOutputType::Jsonb => &[1], // JSONB format version
+            _ => return None,
+        };
+        if self.arr.is_null(row) {
+            return None;
+        }
+        Some(BorrowedValue {
+            prefix,
+            bytes: self.arr.value(row).as_bytes(),
+        })
     }
 }
 
 type StringEncoder<'a> = GenericStringEncoder<'a, i32>;
 type LargeStringEncoder<'a> = GenericStringEncoder<'a, i64>;
 
+// the length, dimension count, nulls flag, element OID, dimension length and lower bound
+const ARRAY_HEADER_SIZE: usize = 4 * 6;
+
 #[derive(Debug)]
 pub struct GenericListEncoder<'a, T: OffsetSizeTrait> {
     arr: &'a arrow_array::GenericListArray<T>,
@@ -541,7 +1401,8 @@ impl<T: OffsetSizeTrait> Encode for GenericListEncoder<'_, T> {
             buf.put_i32(0); // the total number of bytes this element takes up, insert later
             buf.put_i32(1); // num dimensions, we only support 1
             buf.put_i32((val.null_count() != 0) as i32); // nulls flag, true if any item is null
-            let inner_tp_oid = self.inner_encoder_builder.schema().data_type.oid().unwrap();
+            let inner_tp_oid =
+                type_oid(&self.field, &self.inner_encoder_builder.schema().data_type)?;
             buf.put_i32(inner_tp_oid as i32);
             // put the dimension length
             buf.put_i32(val.len() as i32);
@@ -549,7 +1410,9 @@ impl<T: OffsetSizeTrait> Encode for GenericListEncoder<'_, T> {
             buf.put_i32(1);
 
             for inner_row in 0..val.len() {
-                inner_encoder.encode(inner_row, buf)?;
+                inner_encoder.encode(inner_row, buf).map_err(|e| {
+                    e.in_field(&format!("[{inner_row}]"), || render_value(&val, inner_row))
+                })?;
             }
 
             let total_len = buf.len() - base_idx - 4; // end - start - 4 bytes for the size i32 itself
@@ -562,17 +1425,76 @@ impl<T: OffsetSizeTrait> Encode for GenericListEncoder<'_, T> {
         Ok(())
     }
     fn byte_size_hint(&self) -> Result<usize, ErrorKind> {
+        let n = self.arr.len();
+        if self.arr.null_count() == 0 {
+            // measure all the elements at once
+            let offsets = self.arr.value_offsets();
+            let start = offsets[0].as_usize();
+            let values = self
+                .arr
+                .values()
+                .slice(start, offsets[n].as_usize() - start);
+            let inner_encoder = self.inner_encoder_builder.try_new(&values)?;
+            return Ok(n * ARRAY_HEADER_SIZE + inner_encoder.byte_size_hint()?);
+        }
         let mut total = 0;
-        for row in 0..self.arr.len() {
-            if !self.arr.is_null(row) {
+        for row in 0..n {
+            if self.arr.is_null(row) {
+                total += 4;
+            } else {
                 let val = self.arr.value(row);
                 let inner_encoder = self.inner_encoder_builder.try_new(&val)?;
-                let size = inner_encoder.byte_size_hint()?;
-                total += size;
+                total += ARRAY_HEADER_SIZE + inner_encoder.byte_size_hint()?;
             }
         }
         Ok(total)
     }
+    fn add_column_sizes(&self, rows: Range<usize>, sizes: &mut [usize]) -> Result<bool, ErrorKind> {
+        self.add_list_sizes(rows, sizes, false)
+    }
+    fn add_column_size_bounds(
+        &self,
+        rows: Range<usize>,
+        sizes: &mut [usize],
+    ) -> Result<(), ErrorKind> {
+        self.add_list_sizes(rows, sizes, true)?;
+        Ok(())
+    }
+}
+
+impl<T: OffsetSizeTrait> GenericListEncoder<'_, T> {
+    /// Adds the size of each row in `rows` to `sizes` like `add_column_sizes`,
+    /// or an upper bound on it like `add_column_size_bounds` if `bound` is set
+    fn add_list_sizes(
+        &self,
+        rows: Range<usize>,
+        sizes: &mut [usize],
+        bound: bool,
+    ) -> Result<bool, ErrorKind> {
+        // size the elements of all the rows at once, then add up each row's elements
+        let offsets = self.arr.value_offsets();
+        let start = offsets[rows.start].as_usize();
+        let values = self
+            .arr
+            .values()
+            .slice(start, offsets[rows.end].as_usize() - start);
+        let inner_encoder = self.inner_encoder_builder.try_new(&values)?;
+        let mut element_sizes = vec![0; values.len()];
+        if bound {
+            inner_encoder.add_column_size_bounds(0..values.len(), &mut element_sizes)?;
+        } else if !inner_encoder.add_column_sizes(0..values.len(), &mut element_sizes)? {
+            return Ok(false);
+        }
+        for (row, size) in rows.zip(sizes.iter_mut()) {
+            *size += if self.arr.is_null(row) {
+                4
+            } else {
+                let elements = offsets[row].as_usize() - start..offsets[row + 1].as_usize() - start;
+                ARRAY_HEADER_SIZE + element_sizes[elements].iter().sum::<usize>()
+            };
+        }
+        Ok(true)
+    }
 }
 
 type ListEncoder<'a> = GenericListEncoder<'a, i32>;
@@ -593,10 +1515,19 @@ impl Encode for StructEncoder<'_> {
         // Put the number of fields
         buf.put_i32(self.field_encoder_builders.len() as i32);
 
-        for (field, encoder) in self.arr.columns().iter().zip(&self.field_encoder_builders) {
-            let oid = encoder.schema().data_type.oid().unwrap();
+        for ((field, name), encoder) in self
+            .arr
+            .columns()
+            .iter()
+            .zip(self.arr.column_names())
+            .zip(&self.field_encoder_builders)
+        {
+            let oid = type_oid(&self.field, &encoder.schema().data_type)?;
             buf.put_u32(oid);
-            encoder.try_new(field).unwrap().encode(row, buf)?;
+            encoder
+                .try_new(field)
+                .and_then(|encoder| encoder.encode(row, buf))
+                .map_err(|e| e.in_field(&format!(".{name}"), || render_value(field, row)))?;
         }
 
         let total_len = buf.len() - base_idx - 4;
@@ -608,14 +1539,300 @@ impl Encode for StructEncoder<'_> {
     }
 
     fn byte_size_hint(&self) -> Result<usize, ErrorKind> {
-        let mut total = 4 + 4; // 4 bytes for the length, 4 bytes for the number of fields
+        // 4 bytes for the length, 4 bytes for the number of fields and 4 bytes per field OID
+        let n_fields = self.field_encoder_builders.len();
+        let mut total = self.arr.len() * (4 + 4 + 4 * n_fields);
         for (field, encoder) in self.arr.columns().iter().zip(&self.field_encoder_builders) {
             total += encoder.try_new(field)?.byte_size_hint()?;
         }
         Ok(total)
     }
+    fn add_column_sizes(&self, rows: Range<usize>, sizes: &mut [usize]) -> Result<bool, ErrorKind> {
+        let n_fields = self.field_encoder_builders.len();
+        sizes
+            .iter_mut()
+            .for_each(|size| *size += 4 + 4 + 4 * n_fields);
+        for (field, encoder) in self.arr.columns().iter().zip(&self.field_encoder_builders) {
+            if !encoder
+                .try_new(field)?
+                .add_column_sizes(rows.clone(), sizes)?
+            {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+    fn add_column_size_bounds(
+        &self,
+        rows: Range<usize>,
+        sizes: &mut [usize],
+    ) -> Result<(), ErrorKind> {
+        let n_fields = self.field_encoder_builders.len();
+        sizes
+            .iter_mut()
+            .for_each(|size| *size += 4 + 4 + 4 * n_fields);
+        for (field, encoder) in self.arr.columns().iter().zip(&self.field_encoder_builders) {
+            encoder
+                .try_new(field)?
+                .add_column_size_bounds(rows.clone(), sizes)?;
+        }
+        Ok(())
+    }
 }
 
+/// A view over a `struct<x: float64, y: float64>` array, the Arrow representation
+/// used for all of Postgres' geometric types
+#[derive(Debug)]
+struct PointArray<'a> {
+    arr: &'a arrow_array::StructArray,
+    x: &'a arrow_array::Float64Array,
+    y: &'a arrow_array::Float64Array,
+}
+
+impl<'a> PointArray<'a> {
+    fn try_new(arr: &'a dyn Array, field: &str) -> Result<Self, ErrorKind> {
+        let arr: &arrow_array::StructArray = downcast_checked(arr, field)?;
+        if !is_point_type(arr.data_type()) {
+            return Err(ErrorKind::mismatched_column_type(
+                field,
+                "struct<x: float64, y: float64>",
+                arr.data_type(),
+            ));
+        }
+        Ok(Self {
+            arr,
+            x: downcast_checked(arr.column(0).as_ref(), field)?,
+            y: downcast_checked(arr.column(1).as_ref(), field)?,
+        })
+    }
+
+    #[inline]
+    fn is_null(&self, row: usize) -> bool {
+        self.arr.is_null(row)
+    }
+
+    #[inline]
+    fn write(&self, field: &str, row: usize, buf: &mut impl BufMut) -> Result<(), ErrorKind> {
+        if self.arr.is_null(row) || self.x.is_null(row) || self.y.is_null(row) {
+            return Err(ErrorKind::Encode {
+                reason: format!("null point or coordinate in geometric field {field}"),
+            });
+        }
+        buf.put_f64(self.x.value(row));
+        buf.put_f64(self.y.value(row));
+        Ok(())
+    }
+}
+
+const POINT_SIZE: usize = 16; // two FLOAT8 coordinates
+
+#[derive(Debug)]
+pub struct PointEncoder<'a> {
+    points: PointArray<'a>,
+    field: String,
+}
+
+impl PointEncoder<'_> {
+    fn put(&self, row: usize, buf: &mut impl BufMut) -> Result<(), ErrorKind> {
+        if self.points.is_null(row) {
+            buf.put_i32(-1);
+        } else {
+            buf.put_i32(POINT_SIZE as i32);
+            self.points.write(&self.field, row, buf)?;
+        }
+        Ok(())
+    }
+}
+
+impl Encode for PointEncoder<'_> {
+    fn encode(&self, row: usize, buf: &mut BytesMut) -> Result<(), ErrorKind> {
+        self.put(row, buf)
+    }
+    fn byte_size_hint(&self) -> Result<usize, ErrorKind> {
+        let null_count = self.points.arr.null_count();
+        let item_count = self.points.arr.len();
+        Ok((item_count - null_count) * POINT_SIZE + item_count * 4)
+    }
+    fn add_column_sizes(&self, rows: Range<usize>, sizes: &mut [usize]) -> Result<bool, ErrorKind> {
+        add_fixed_sizes(self.points.arr, POINT_SIZE, rows, sizes);
+        Ok(true)
+    }
+    fn scatter_column(
+        &self,
+        rows: Range<usize>,
+        out: &mut [MaybeUninit<u8>],
+        offsets: &mut [usize],
+    ) -> Result<(), ErrorKind> {
+        for (row, offset) in rows.zip(offsets.iter_mut()) {
+            write_at(out, offset, |dst| self.put(row, dst))?;
+        }
+        Ok(())
+    }
+}
+
+/// Encodes `LSEG` and `BOX` values, which share the same binary layout of two points
+#[derive(Debug)]
+pub struct PointPairEncoder<'a> {
+    arr: &'a arrow_array::StructArray,
+    start: PointArray<'a>,
+    end: PointArray<'a>,
+    field: String,
+}
+
+impl PointPairEncoder<'_> {
+    fn put(&self, row: usize, buf: &mut impl BufMut) -> Result<(), ErrorKind> {
+        if self.arr.is_null(row) {
+            buf.put_i32(-1);
+        } else {
+            buf.put_i32(2 * POINT_SIZE as i32);
+            self.start.write(&self.field, row, buf)?;
+            self.end.write(&self.field, row, buf)?;
+        }
+        Ok(())
+    }
+}
+
+impl Encode for PointPairEncoder<'_> {
+    fn encode(&self, row: usize, buf: &mut BytesMut) -> Result<(), ErrorKind> {
+        self.put(row, buf)
+    }
+    fn byte_size_hint(&self) -> Result<usize, ErrorKind> {
+        let null_count = self.arr.null_count();
+        let item_count = self.arr.len();
+        Ok((item_count - null_count) * 2 * POINT_SIZE + item_count * 4)
+    }
+    fn add_column_sizes(&self, rows: Range<usize>, sizes: &mut [usize]) -> Result<bool, ErrorKind> {
+        add_fixed_sizes(self.arr, 2 * POINT_SIZE, rows, sizes);
+        Ok(true)
+    }
+    fn scatter_column(
+        &self,
+        rows: Range<usize>,
+        out: &mut [MaybeUninit<u8>],
+        offsets: &mut [usize],
+    ) -> Result<(), ErrorKind> {
+        for (row, offset) in rows.zip(offsets.iter_mut()) {
+            write_at(out, offset, |dst| self.put(row, dst))?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct CircleEncoder<'a> {
+    arr: &'a arrow_array::StructArray,
+    center: PointArray<'a>,
+    radius: &'a arrow_array::Float64Array,
+    field: String,
+}
+
+impl CircleEncoder<'_> {
+    fn put(&self, row: usize, buf: &mut impl BufMut) -> Result<(), ErrorKind> {
+        if self.arr.is_null(row) {
+            buf.put_i32(-1);
+        } else {
+            if self.radius.is_null(row) || self.radius.value(row) < 0.0 {
+                return Err(ErrorKind::Encode {
+                    reason: format!("invalid radius in circle field {}", self.field),
+                });
+            }
+            buf.put_i32(type_size_fixed(PostgresType::Circle.size())? as i32);
+            self.center.write(&self.field, row, buf)?;
+            buf.put_f64(self.radius.value(row));
+        }
+        Ok(())
+    }
+}
+
+impl Encode for CircleEncoder<'_> {
+    fn encode(&self, row: usize, buf: &mut BytesMut) -> Result<(), ErrorKind> {
+        self.put(row, buf)
+    }
+    fn byte_size_hint(&self) -> Result<usize, ErrorKind> {
+        let null_count = self.arr.null_count();
+        let item_count = self.arr.len();
+        Ok(
+            (item_count - null_count) * type_size_fixed(PostgresType::Circle.size())?
+                + item_count * 4,
+        )
+    }
+    fn add_column_sizes(&self, rows: Range<usize>, sizes: &mut [usize]) -> Result<bool, ErrorKind> {
+        let field_size = type_size_fixed(PostgresType::Circle.size())?;
+        add_fixed_sizes(self.arr, field_size, rows, sizes);
+        Ok(true)
+    }
+    fn scatter_column(
+        &self,
+        rows: Range<usize>,
+        out: &mut [MaybeUninit<u8>],
+        offsets: &mut [usize],
+    ) -> Result<(), ErrorKind> {
+        for (row, offset) in rows.zip(offsets.iter_mut()) {
+            write_at(out, offset, |dst| self.put(row, dst))?;
+        }
+        Ok(())
+    }
+}
+
+/// Encodes lists of points as `PATH` (if `closed` is set) or `POLYGON` values
+#[derive(Debug)]
+pub struct GenericPointListEncoder<'a, T: OffsetSizeTrait> {
+    arr: &'a arrow_array::GenericListArray<T>,
+    points: PointArray<'a>,
+    field: String,
+    closed: Option<bool>,
+}
+
+impl<T: OffsetSizeTrait> Encode for GenericPointListEncoder<'_, T> {
+    fn encode(&self, row: usize, buf: &mut BytesMut) -> Result<(), ErrorKind> {
+        if self.arr.is_null(row) {
+            buf.put_i32(-1);
+            return Ok(());
+        }
+        let offsets = self.arr.value_offsets();
+        let start = offsets[row].as_usize();
+        let end = offsets[row + 1].as_usize();
+        let n_points = end - start;
+        if n_points == 0 {
+            return Err(ErrorKind::Encode {
+                reason: format!("geometric field {} requires at least one point", self.field),
+            });
+        }
+        let header_size = if self.closed.is_some() { 5 } else { 4 };
+        let len = header_size + n_points * POINT_SIZE;
+        match i32::try_from(len) {
+            Ok(l) => buf.put_i32(l),
+            Err(_) => return Err(ErrorKind::field_too_large(&self.field, len)),
+        }
+        if let Some(closed) = self.closed {
+            buf.put_u8(closed as u8);
+        }
+        buf.put_i32(n_points as i32);
+        for point in start..end {
+            self.points.write(&self.field, point, buf)?;
+        }
+        Ok(())
+    }
+    fn byte_size_hint(&self) -> Result<usize, ErrorKind> {
+        let mut sizes = vec![0; self.arr.len()];
+        self.add_column_sizes(0..sizes.len(), &mut sizes)?;
+        Ok(sizes.iter().sum())
+    }
+    fn add_column_sizes(&self, rows: Range<usize>, sizes: &mut [usize]) -> Result<bool, ErrorKind> {
+        let header_size = if self.closed.is_some() { 5 } else { 4 };
+        for (row, size) in rows.zip(sizes.iter_mut()) {
+            *size += 4;
+            if !self.arr.is_null(row) {
+                *size += header_size + self.arr.value_length(row).as_usize() * POINT_SIZE;
+            }
+        }
+        Ok(true)
+    }
+}
+
+type PointListEncoder<'a> = GenericPointListEncoder<'a, i32>;
+type LargePointListEncoder<'a> = GenericPointListEncoder<'a, i64>;
+
 #[enum_dispatch]
 pub trait BuildEncoder: std::fmt::Debug + PartialEq {
     fn try_new<'a, 'b: 'a>(&'b self, arr: &'a dyn Array) -> Result<Encoder<'a>, ErrorKind>;
@@ -718,7 +1935,7 @@ macro_rules! impl_encoder_builder_stateless_with_variable_output {
                     return Err(ErrorKind::unsupported_encoding(
                         &field.name(),
                         &output,
-                        &[PostgresType::Char, PostgresType::Int2],
+                        &$allowed_pg_data_types,
                     ));
                 }
                 Ok(Self { field, output })
@@ -772,25 +1989,71 @@ impl_encoder_builder_stateless!(
 pub struct UInt16EncoderBuilder {
     field: Arc<Field>,
 }
-impl_encoder_builder_stateless!(
-    UInt16EncoderBuilder,
-    Encoder::UInt16,
-    UInt16Encoder,
-    PostgresType::Int4,
-    |dt: &DataType| matches!(dt, DataType::UInt16)
-);
+impl_encoder_builder_stateless!(
+    UInt16EncoderBuilder,
+    Encoder::UInt16,
+    UInt16Encoder,
+    PostgresType::Int4,
+    |dt: &DataType| matches!(dt, DataType::UInt16)
+);
+
+/// Encodes UInt32 as `INT8` by default; `OID` and `REGCLASS` write the value as is
+#[derive(Debug, Clone, PartialEq)]
+pub struct UInt32EncoderBuilder {
+    field: Arc<Field>,
+    output: PostgresType,
+}
+
+impl UInt32EncoderBuilder {
+    pub fn new(field: Arc<Field>) -> Result<Self, ErrorKind> {
+        if !matches!(field.data_type(), DataType::UInt32) {
+            return Err(ErrorKind::FieldTypeNotSupported {
+                encoder: "UInt32EncoderBuilder".to_string(),
+                tp: field.data_type().clone(),
+                field: field.name().clone(),
+            });
+        }
+        Ok(Self {
+            field,
+            output: PostgresType::Int8,
+        })
+    }
+    pub fn new_with_output(field: Arc<Field>, output: PostgresType) -> Result<Self, ErrorKind> {
+        let allowed = [
+            PostgresType::Int8,
+            PostgresType::Oid,
+            PostgresType::Regclass,
+        ];
+        if !allowed.contains(&output) {
+            return Err(ErrorKind::unsupported_encoding(
+                field.name(),
+                &output,
+                &allowed,
+            ));
+        }
+        Ok(Self { field, output })
+    }
+}
 
-#[derive(Debug, Clone, PartialEq)]
-pub struct UInt32EncoderBuilder {
-    field: Arc<Field>,
+impl BuildEncoder for UInt32EncoderBuilder {
+    fn try_new<'a, 'b: 'a>(&'b self, arr: &'a dyn Array) -> Result<Encoder<'a>, ErrorKind> {
+        let arr = downcast_checked(arr, self.field.name())?;
+        match self.output {
+            PostgresType::Int8 => Ok(Encoder::UInt32(UInt32Encoder { arr })),
+            _ => Ok(Encoder::Oid(OidEncoder { arr })),
+        }
+    }
+    fn schema(&self) -> Column {
+        Column {
+            name: self.field.name().clone(),
+            data_type: self.output.clone(),
+            nullable: self.field.is_nullable(),
+        }
+    }
+    fn field(&self) -> Arc<Field> {
+        self.field.clone()
+    }
 }
-impl_encoder_builder_stateless!(
-    UInt32EncoderBuilder,
-    Encoder::UInt32,
-    UInt32Encoder,
-    PostgresType::Int8,
-    |dt: &DataType| matches!(dt, DataType::UInt32)
-);
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Int8EncoderBuilder {
@@ -830,15 +2093,18 @@ impl_encoder_builder_stateless!(
     |dt: &DataType| matches!(dt, DataType::Int32)
 );
 
+/// Encodes Int64 as `INT8` by default or as `MONEY`, in which case values are cents
 #[derive(Debug, Clone, PartialEq)]
 pub struct Int64EncoderBuilder {
     field: Arc<Field>,
+    output: PostgresType,
 }
-impl_encoder_builder_stateless!(
+impl_encoder_builder_stateless_with_variable_output!(
     Int64EncoderBuilder,
     Encoder::Int64,
     Int64Encoder,
     PostgresType::Int8,
+    [PostgresType::Int8, PostgresType::Money],
     |dt: &DataType| matches!(dt, DataType::Int64)
 );
 
@@ -878,11 +2144,64 @@ impl_encoder_builder_stateless!(
     |dt: &DataType| matches!(dt, DataType::Float64)
 );
 
+/// Encodes Decimal128 as `MONEY`, assuming two fractional digits.
+/// Decimals are inferred as `NUMERIC`, see `NumericEncoderBuilder`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decimal128EncoderBuilder {
+    field: Arc<Field>,
+    output: PostgresType,
+}
+
+impl Decimal128EncoderBuilder {
+    pub fn new(field: Arc<Field>) -> Result<Self, ErrorKind> {
+        Self::new_with_output(field, PostgresType::Money)
+    }
+    pub fn new_with_output(field: Arc<Field>, output: PostgresType) -> Result<Self, ErrorKind> {
+        if !matches!(field.data_type(), DataType::Decimal128(_, _)) {
+            return Err(ErrorKind::FieldTypeNotSupported {
+                encoder: "Decimal128EncoderBuilder".to_string(),
+                tp: field.data_type().clone(),
+                field: field.name().clone(),
+            });
+        }
+        if output != PostgresType::Money {
+            return Err(ErrorKind::unsupported_encoding(
+                field.name(),
+                &output,
+                &[PostgresType::Money],
+            ));
+        }
+        Ok(Self { field, output })
+    }
+}
+
+impl BuildEncoder for Decimal128EncoderBuilder {
+    fn try_new<'a, 'b: 'a>(&'b self, arr: &'a dyn Array) -> Result<Encoder<'a>, ErrorKind> {
+        let field = self.field.name();
+        let arr: &arrow_array::Decimal128Array = downcast_checked(arr, field)?;
+        Ok(Encoder::Decimal128(Decimal128Encoder {
+            arr,
+            field: field.to_string(),
+            scale: arr.scale(),
+        }))
+    }
+    fn schema(&self) -> Column {
+        Column {
+            name: self.field.name().clone(),
+            data_type: self.output.clone(),
+            nullable: self.field.is_nullable(),
+        }
+    }
+    fn field(&self) -> Arc<Field> {
+        self.field.clone()
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct TimestampMicrosecondEncoderBuilder {
     field: Arc<Field>,
 }
-impl_encoder_builder_stateless_with_field!(
+impl_encoder_builder_stateless!(
     TimestampMicrosecondEncoderBuilder,
     Encoder::TimestampMicrosecond,
     TimestampMicrosecondEncoder,
@@ -894,7 +2213,7 @@ impl_encoder_builder_stateless_with_field!(
 pub struct TimestampMillisecondEncoderBuilder {
     field: Arc<Field>,
 }
-impl_encoder_builder_stateless_with_field!(
+impl_encoder_builder_stateless!(
     TimestampMillisecondEncoderBuilder,
     Encoder::TimestampMillisecond,
     TimestampMillisecondEncoder,
@@ -906,7 +2225,7 @@ impl_encoder_builder_stateless_with_field!(
 pub struct TimestampSecondEncoderBuilder {
     field: Arc<Field>,
 }
-impl_encoder_builder_stateless_with_field!(
+impl_encoder_builder_stateless!(
     TimestampSecondEncoderBuilder,
     Encoder::TimestampSecond,
     TimestampSecondEncoder,
@@ -918,7 +2237,7 @@ impl_encoder_builder_stateless_with_field!(
 pub struct Date32EncoderBuilder {
     field: Arc<Field>,
 }
-impl_encoder_builder_stateless_with_field!(
+impl_encoder_builder_stateless!(
     Date32EncoderBuilder,
     Encoder::Date32,
     Date32Encoder,
@@ -930,7 +2249,7 @@ impl_encoder_builder_stateless_with_field!(
 pub struct Time32MillisecondEncoderBuilder {
     field: Arc<Field>,
 }
-impl_encoder_builder_stateless_with_field!(
+impl_encoder_builder_stateless!(
     Time32MillisecondEncoderBuilder,
     Encoder::Time32Millisecond,
     Time32MillisecondEncoder,
@@ -942,7 +2261,7 @@ impl_encoder_builder_stateless_with_field!(
 pub struct Time32SecondEncoderBuilder {
     field: Arc<Field>,
 }
-impl_encoder_builder_stateless_with_field!(
+impl_encoder_builder_stateless!(
     Time32SecondEncoderBuilder,
     Encoder::Time32Second,
     Time32SecondEncoder,
@@ -978,7 +2297,7 @@ impl_encoder_builder_stateless!(
 pub struct DurationMillisecondEncoderBuilder {
     field: Arc<Field>,
 }
-impl_encoder_builder_stateless_with_field!(
+impl_encoder_builder_stateless!(
     DurationMillisecondEncoderBuilder,
     Encoder::DurationMillisecond,
     DurationMillisecondEncoder,
@@ -990,7 +2309,7 @@ impl_encoder_builder_stateless_with_field!(
 pub struct DurationSecondEncoderBuilder {
     field: Arc<Field>,
 }
-impl_encoder_builder_stateless_with_field!(
+impl_encoder_builder_stateless!(
     DurationSecondEncoderBuilder,
     Encoder::DurationSecond,
     DurationSecondEncoder,
@@ -1003,6 +2322,9 @@ enum StringOutputType {
     Text,
     Json,
     Jsonb,
+    Xml { check_well_formed: bool },
+    Tsvector,
+    Uuid,
 }
 
 impl StringOutputType {
@@ -1011,10 +2333,22 @@ impl StringOutputType {
             PostgresType::Text => Ok(StringOutputType::Text),
             PostgresType::Json => Ok(StringOutputType::Json),
             PostgresType::Jsonb => Ok(StringOutputType::Jsonb),
+            PostgresType::Xml => Ok(StringOutputType::Xml {
+                check_well_formed: false,
+            }),
+            PostgresType::Tsvector => Ok(StringOutputType::Tsvector),
+            PostgresType::Uuid => Ok(StringOutputType::Uuid),
             other => Err(ErrorKind::EncodingNotSupported {
                 field: field.name().clone(),
                 tp: other,
-                allowed: vec![PostgresType::Text, PostgresType::Json, PostgresType::Jsonb],
+                allowed: vec![
+                    PostgresType::Text,
+                    PostgresType::Json,
+                    PostgresType::Jsonb,
+                    PostgresType::Xml,
+                    PostgresType::Tsvector,
+                    PostgresType::Uuid,
+                ],
             }),
         }
     }
@@ -1023,6 +2357,9 @@ impl StringOutputType {
             StringOutputType::Text => PostgresType::Text,
             StringOutputType::Json => PostgresType::Json,
             StringOutputType::Jsonb => PostgresType::Jsonb,
+            StringOutputType::Xml { .. } => PostgresType::Xml,
+            StringOutputType::Tsvector => PostgresType::Tsvector,
+            StringOutputType::Uuid => PostgresType::Uuid,
         }
     }
 }
@@ -1050,6 +2387,13 @@ macro_rules! impl_encoder_builder_with_variable_output {
                 let output = StringOutputType::from_postgres_type(output, &field)?;
                 Ok(Self { field, output })
             }
+            /// Encodes values as `XML`, optionally rejecting values that are not well-formed
+            /// before they reach Postgres
+            pub fn new_xml(field: Arc<Field>, check_well_formed: bool) -> Result<Self, ErrorKind> {
+                let mut builder = Self::new_with_output(field, PostgresType::Xml)?;
+                builder.output = StringOutputType::Xml { check_well_formed };
+                Ok(builder)
+            }
         }
         impl BuildEncoder for $struct_name {
             fn try_new<'a, 'b: 'a>(&'b self, arr: &'a dyn Array) -> Result<Encoder<'a>, ErrorKind> {
@@ -1272,6 +2616,380 @@ impl StructEncoderBuilder {
     }
 }
 
+fn is_point_type(dt: &DataType) -> bool {
+    match dt {
+        DataType::Struct(fields) => {
+            fields.len() == 2
+                && fields
+                    .iter()
+                    .all(|f| matches!(f.data_type(), DataType::Float64))
+        }
+        _ => false,
+    }
+}
+
+fn is_point_pair_type(dt: &DataType) -> bool {
+    match dt {
+        DataType::Struct(fields) => {
+            fields.len() == 2 && fields.iter().all(|f| is_point_type(f.data_type()))
+        }
+        _ => false,
+    }
+}
+
+fn is_circle_type(dt: &DataType) -> bool {
+    match dt {
+        DataType::Struct(fields) => {
+            fields.len() == 2
+                && is_point_type(fields[0].data_type())
+                && matches!(fields[1].data_type(), DataType::Float64)
+        }
+        _ => false,
+    }
+}
+
+fn is_point_list_type(dt: &DataType) -> bool {
+    match dt {
+        DataType::List(inner) | DataType::LargeList(inner) => is_point_type(inner.data_type()),
+        _ => false,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum GeometricOutputType {
+    Point,
+    Lseg,
+    Box,
+    Circle,
+    Path { closed: bool },
+    Polygon,
+}
+
+impl GeometricOutputType {
+    pub fn from_postgres_type(tp: PostgresType, field: &Field) -> Result<Self, ErrorKind> {
+        let (output, valid_shape) = match tp {
+            PostgresType::Point => (
+                GeometricOutputType::Point,
+                is_point_type as fn(&DataType) -> bool,
+            ),
+            PostgresType::Lseg => (GeometricOutputType::Lseg, is_point_pair_type as _),
+            PostgresType::Box => (GeometricOutputType::Box, is_point_pair_type as _),
+            PostgresType::Circle => (GeometricOutputType::Circle, is_circle_type as _),
+            PostgresType::Path => (
+                GeometricOutputType::Path { closed: false },
+                is_point_list_type as _,
+            ),
+            PostgresType::Polygon => (GeometricOutputType::Polygon, is_point_list_type as _),
+            other => {
+                return Err(ErrorKind::EncodingNotSupported {
+                    field: field.name().clone(),
+                    tp: other,
+                    allowed: vec![
+                        PostgresType::Point,
+                        PostgresType::Lseg,
+                        PostgresType::Box,
+                        PostgresType::Circle,
+                        PostgresType::Path,
+                        PostgresType::Polygon,
+                    ],
+                })
+            }
+        };
+        if !valid_shape(field.data_type()) {
+            return Err(ErrorKind::FieldTypeNotSupported {
+                encoder: "GeometricEncoderBuilder".to_string(),
+                tp: field.data_type().clone(),
+                field: field.name().clone(),
+            });
+        }
+        Ok(output)
+    }
+    pub fn postgres_datatype(&self) -> PostgresType {
+        match self {
+            GeometricOutputType::Point => PostgresType::Point,
+            GeometricOutputType::Lseg => PostgresType::Lseg,
+            GeometricOutputType::Box => PostgresType::Box,
+            GeometricOutputType::Circle => PostgresType::Circle,
+            GeometricOutputType::Path { .. } => PostgresType::Path,
+            GeometricOutputType::Polygon => PostgresType::Polygon,
+        }
+    }
+}
+
+/// Encodes Arrow structs and lists of points into Postgres' built-in geometric types.
+///
+/// A point is a `struct<x: float64, y: float64>` (children are matched by position).
+/// `LSEG` and `BOX` take a struct of two points, `CIRCLE` a struct of a center point
+/// and a `float64` radius, and `PATH` and `POLYGON` a list of points.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeometricEncoderBuilder {
+    field: Arc<Field>,
+    output: GeometricOutputType,
+}
+
+impl GeometricEncoderBuilder {
+    /// Infers the geometric type from the shape of the field:
+    /// points, boxes, circles and polygons are inferred.
+    pub fn new(field: Arc<Field>) -> Result<Self, ErrorKind> {
+        let dt = field.data_type();
+        let output = if is_point_type(dt) {
+            GeometricOutputType::Point
+        } else if is_point_pair_type(dt) {
+            GeometricOutputType::Box
+        } else if is_circle_type(dt) {
+            GeometricOutputType::Circle
+        } else if is_point_list_type(dt) {
+            GeometricOutputType::Polygon
+        } else {
+            return Err(ErrorKind::FieldTypeNotSupported {
+                encoder: "GeometricEncoderBuilder".to_string(),
+                tp: dt.clone(),
+                field: field.name().clone(),
+            });
+        };
+        Ok(Self { field, output })
+    }
+    pub fn new_with_output(field: Arc<Field>, output: PostgresType) -> Result<Self, ErrorKind> {
+        let output = GeometricOutputType::from_postgres_type(output, &field)?;
+        Ok(Self { field, output })
+    }
+    /// Builds a `PATH` encoder, marking every path as open or closed
+    pub fn new_path(field: Arc<Field>, closed: bool) -> Result<Self, ErrorKind> {
+        GeometricOutputType::from_postgres_type(PostgresType::Path, &field)?;
+        Ok(Self {
+            field,
+            output: GeometricOutputType::Path { closed },
+        })
+    }
+}
+
+impl BuildEncoder for GeometricEncoderBuilder {
+    fn try_new<'a, 'b: 'a>(&'b self, arr: &'a dyn Array) -> Result<Encoder<'a>, ErrorKind> {
+        let field = self.field.name();
+        let closed = match self.output {
+            GeometricOutputType::Point => {
+                return Ok(Encoder::Point(PointEncoder {
+                    points: PointArray::try_new(arr, field)?,
+                    field: field.to_string(),
+                }))
+            }
+            GeometricOutputType::Lseg | GeometricOutputType::Box => {
+                let arr: &arrow_array::StructArray = downcast_checked(arr, field)?;
+                return Ok(Encoder::PointPair(PointPairEncoder {
+                    arr,
+                    start: PointArray::try_new(arr.column(0).as_ref(), field)?,
+                    end: PointArray::try_new(arr.column(1).as_ref(), field)?,
+                    field: field.to_string(),
+                }));
+            }
+            GeometricOutputType::Circle => {
+                let arr: &arrow_array::StructArray = downcast_checked(arr, field)?;
+                return Ok(Encoder::Circle(CircleEncoder {
+                    arr,
+                    center: PointArray::try_new(arr.column(0).as_ref(), field)?,
+                    radius: downcast_checked(arr.column(1).as_ref(), field)?,
+                    field: field.to_string(),
+                }));
+            }
+            GeometricOutputType::Path { closed } => Some(closed),
+            GeometricOutputType::Polygon => None,
+        };
+        match arr.data_type() {
+            DataType::LargeList(_) => {
+                let arr: &arrow_array::LargeListArray = downcast_checked(arr, field)?;
+                Ok(Encoder::LargePointList(LargePointListEncoder {
+                    arr,
+                    points: PointArray::try_new(arr.values().as_ref(), field)?,
+                    field: field.to_string(),
+                    closed,
+                }))
+            }
+            _ => {
+                let arr: &arrow_array::ListArray = downcast_checked(arr, field)?;
+                Ok(Encoder::PointList(PointListEncoder {
+                    arr,
+                    points: PointArray::try_new(arr.values().as_ref(), field)?,
+                    field: field.to_string(),
+                    closed,
+                }))
+            }
+        }
+    }
+
+    fn schema(&self) -> Column {
+        Column {
+            name: self.field.name().clone(),
+            data_type: self.output.postgres_datatype(),
+            nullable: self.field.is_nullable(),
+        }
+    }
+
+    fn field(&self) -> Arc<Field> {
+        self.field.clone()
+    }
+}
+
+/// Widens an Arrow integer column to an `INT8` column
+fn widen_to_int64(arr: &dyn Array) -> Option<arrow_array::Int64Array> {
+    let widened = match arr.data_type() {
+        DataType::Int8 => arr.as_primitive::<Int8Type>().unary(|v| v as i64),
+        DataType::Int16 => arr.as_primitive::<Int16Type>().unary(|v| v as i64),
+        DataType::Int32 => arr.as_primitive::<Int32Type>().unary(|v| v as i64),
+        DataType::Int64 => arr.as_primitive::<Int64Type>().clone(),
+        DataType::UInt8 => arr.as_primitive::<UInt8Type>().unary(|v| v as i64),
+        DataType::UInt16 => arr.as_primitive::<UInt16Type>().unary(|v| v as i64),
+        DataType::UInt32 => arr.as_primitive::<UInt32Type>().unary(|v| v as i64),
+        _ => return None,
+    };
+    Some(widened)
+}
+
+/// Encodes integers, floats and decimals as `NUMERIC`
+#[derive(Debug, Clone, PartialEq)]
+pub struct NumericEncoderBuilder {
+    field: Arc<Field>,
+    output: PostgresType,
+}
+
+impl NumericEncoderBuilder {
+    pub fn new(field: Arc<Field>) -> Result<Self, ErrorKind> {
+        Self::new_with_output(field, PostgresType::Numeric)
+    }
+    pub fn new_with_output(field: Arc<Field>, output: PostgresType) -> Result<Self, ErrorKind> {
+        if !matches!(
+            field.data_type(),
+            DataType::Int8
+                | DataType::Int16
+                | DataType::Int32
+                | DataType::Int64
+                | DataType::UInt8
+                | DataType::UInt16
+                | DataType::UInt32
+                | DataType::Float32
+                | DataType::Float64
+                | DataType::Decimal128(_, _)
+        ) {
+            return Err(ErrorKind::FieldTypeNotSupported {
+                encoder: "NumericEncoderBuilder".to_string(),
+                tp: field.data_type().clone(),
+                field: field.name().clone(),
+            });
+        }
+        if output != PostgresType::Numeric {
+            return Err(ErrorKind::unsupported_encoding(
+                field.name(),
+                &output,
+                &[PostgresType::Numeric],
+            ));
+        }
+        Ok(Self { field, output })
+    }
+}
+
+impl BuildEncoder for NumericEncoderBuilder {
+    fn try_new<'a, 'b: 'a>(&'b self, arr: &'a dyn Array) -> Result<Encoder<'a>, ErrorKind> {
+        let field = self.field.name();
+        let values = match arr.data_type() {
+            DataType::Float32 => NumericValues::Float32(downcast_checked(arr, field)?),
+            DataType::Float64 => NumericValues::Float64(downcast_checked(arr, field)?),
+            DataType::Decimal128(_, _) => NumericValues::Decimal128(downcast_checked(arr, field)?),
+            dt => NumericValues::Int(
+                widen_to_int64(arr)
+                    .ok_or_else(|| ErrorKind::mismatched_column_type(field, "Int64Array", dt))?,
+            ),
+        };
+        Ok(Encoder::Numeric(NumericEncoder { values }))
+    }
+    fn schema(&self) -> Column {
+        Column {
+            name: self.field.name().clone(),
+            data_type: self.output.clone(),
+            nullable: self.field.is_nullable(),
+        }
+    }
+    fn field(&self) -> Arc<Field> {
+        self.field.clone()
+    }
+}
+
+/// Widens a column to a larger Postgres type than the one inferred for it,
+/// e.g. Int16 to `INT8`, Float32 to `FLOAT8` or Date32 to `TIMESTAMP`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CastEncoderBuilder {
+    field: Arc<Field>,
+    output: PostgresType,
+}
+
+impl CastEncoderBuilder {
+    /// Casts to the widest type available for the field
+    pub fn new(field: Arc<Field>) -> Result<Self, ErrorKind> {
+        let output = match field.data_type() {
+            DataType::Float16 | DataType::Float32 => PostgresType::Float8,
+            DataType::Date32 => PostgresType::Timestamp,
+            _ => PostgresType::Int8,
+        };
+        Self::new_with_output(field, output)
+    }
+    pub fn new_with_output(field: Arc<Field>, output: PostgresType) -> Result<Self, ErrorKind> {
+        let allowed: &[PostgresType] = match field.data_type() {
+            DataType::Int8 | DataType::Int16 | DataType::UInt8 => {
+                &[PostgresType::Int2, PostgresType::Int4, PostgresType::Int8]
+            }
+            DataType::Int32 | DataType::UInt16 => &[PostgresType::Int4, PostgresType::Int8],
+            DataType::Int64 | DataType::UInt32 => &[PostgresType::Int8],
+            DataType::Float16 | DataType::Float32 => &[PostgresType::Float8],
+            DataType::Date32 => &[PostgresType::Timestamp],
+            dt => {
+                return Err(ErrorKind::FieldTypeNotSupported {
+                    encoder: "CastEncoderBuilder".to_string(),
+                    tp: dt.clone(),
+                    field: field.name().clone(),
+                })
+            }
+        };
+        if !allowed.contains(&output) {
+            return Err(ErrorKind::unsupported_encoding(
+                field.name(),
+                &output,
+                allowed,
+            ));
+        }
+        Ok(Self { field, output })
+    }
+}
+
+impl BuildEncoder for CastEncoderBuilder {
+    fn try_new<'a, 'b: 'a>(&'b self, arr: &'a dyn Array) -> Result<Encoder<'a>, ErrorKind> {
+        let field = self.field.name();
+        match (arr.data_type(), &self.output) {
+            (DataType::Float16, _) => Ok(Encoder::Float16AsFloat8(Float16AsFloat8Encoder {
+                arr: downcast_checked(arr, field)?,
+            })),
+            (DataType::Float32, _) => Ok(Encoder::Float32AsFloat8(Float32AsFloat8Encoder {
+                arr: downcast_checked(arr, field)?,
+            })),
+            (DataType::Date32, _) => Ok(Encoder::Date32AsTimestamp(Date32AsTimestampEncoder {
+                arr: downcast_checked(arr, field)?,
+            })),
+            (dt, output) => Ok(Encoder::IntCast(IntCastEncoder {
+                arr: widen_to_int64(arr)
+                    .ok_or_else(|| ErrorKind::mismatched_column_type(field, "Int64Array", dt))?,
+                output_size: type_size_fixed(output.size())?,
+            })),
+        }
+    }
+    fn schema(&self) -> Column {
+        Column {
+            name: self.field.name().clone(),
+            data_type: self.output.clone(),
+            nullable: self.field.is_nullable(),
+        }
+    }
+    fn field(&self) -> Arc<Field> {
+        self.field.clone()
+    }
+}
+
 #[enum_dispatch(BuildEncoder)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum EncoderBuilder {
@@ -1286,6 +3004,7 @@ pub enum EncoderBuilder {
     Float16(Float16EncoderBuilder),
     Float32(Float32EncoderBuilder),
     Float64(Float64EncoderBuilder),
+    Decimal128(Decimal128EncoderBuilder),
     TimestampMicrosecond(TimestampMicrosecondEncoderBuilder),
     TimestampMillisecond(TimestampMillisecondEncoderBuilder),
     TimestampSecond(TimestampSecondEncoderBuilder),
@@ -1303,6 +3022,9 @@ pub enum EncoderBuilder {
     List(ListEncoderBuilder),
     LargeList(LargeListEncoderBuilder),
     Struct(StructEncoderBuilder),
+    Geometric(GeometricEncoderBuilder),
+    Numeric(NumericEncoderBuilder),
+    Cast(CastEncoderBuilder),
 }
 
 impl EncoderBuilder {
@@ -1312,7 +3034,10 @@ impl EncoderBuilder {
             DataType::Boolean => Self::Boolean(BooleanEncoderBuilder { field }),
             DataType::UInt8 => Self::UInt8(UInt8EncoderBuilder { field }),
             DataType::UInt16 => Self::UInt16(UInt16EncoderBuilder { field }),
-            DataType::UInt32 => Self::UInt32(UInt32EncoderBuilder { field }),
+            DataType::UInt32 => Self::UInt32(UInt32EncoderBuilder {
+                field,
+                output: PostgresType::Int8,
+            }),
             // Note that rust-postgres encodes int8 to CHAR by default
             DataType::Int8 => Self::Int8(Int8EncoderBuilder {
                 field,
@@ -1320,10 +3045,17 @@ impl EncoderBuilder {
             }),
             DataType::Int16 => Self::Int16(Int16EncoderBuilder { field }),
             DataType::Int32 => Self::Int32(Int32EncoderBuilder { field }),
-            DataType::Int64 => Self::Int64(Int64EncoderBuilder { field }),
+            DataType::Int64 => Self::Int64(Int64EncoderBuilder {
+                field,
+                output: PostgresType::Int8,
+            }),
             DataType::Float16 => Self::Float16(Float16EncoderBuilder { field }),
             DataType::Float32 => Self::Float32(Float32EncoderBuilder { field }),
             DataType::Float64 => Self::Float64(Float64EncoderBuilder { field }),
+            DataType::Decimal128(_, _) => Self::Numeric(NumericEncoderBuilder {
+                field,
+                output: PostgresType::Numeric,
+            }),
             DataType::Timestamp(unit, _) => match unit {
                 TimeUnit::Nanosecond => {
                     return Err(ErrorKind::type_unsupported(
@@ -1346,7 +3078,13 @@ impl EncoderBuilder {
                     Self::Time32Millisecond(Time32MillisecondEncoderBuilder { field })
                 }
                 TimeUnit::Second => Self::Time32Second(Time32SecondEncoderBuilder { field }),
-                _ => unreachable!(),
+                _ => {
+                    return Err(ErrorKind::type_unsupported(
+                        field.name(),
+                        data_type,
+                        "Time32 only supports second and millisecond precision",
+                    ))
+                }
             },
             DataType::Time64(unit) => match unit {
                 TimeUnit::Nanosecond => {
@@ -1359,7 +3097,13 @@ impl EncoderBuilder {
                 TimeUnit::Microsecond => {
                     Self::Time64Microsecond(Time64MicrosecondEncoderBuilder { field })
                 }
-                _ => unreachable!(),
+                _ => {
+                    return Err(ErrorKind::type_unsupported(
+                        field.name(),
+                        data_type,
+                        "Time64 only supports microsecond and nanosecond precision",
+                    ))
+                }
             },
             DataType::Duration(unit) => match unit {
                 TimeUnit::Nanosecond => {
@@ -1443,4 +3187,86 @@ impl EncoderBuilder {
         };
         Ok(res)
     }
+
+    /// Picks an encoder that writes `field` as the given Postgres type,
+    /// widening or converting values where a lossless cast exists.
+    pub fn try_new_with_output(
+        field: Arc<Field>,
+        output: &PostgresType,
+    ) -> Result<Self, ErrorKind> {
+        let data_type = field.data_type().clone();
+        if let PostgresType::List(inner_column) = output {
+            let inner = match &data_type {
+                DataType::List(inner)
+                | DataType::LargeList(inner)
+                | DataType::FixedSizeList(inner, _) => inner.clone(),
+                _ => {
+                    return Err(ErrorKind::unsupported_output(
+                        field.name(),
+                        &data_type,
+                        output,
+                    ))
+                }
+            };
+            let inner = Self::try_new_with_output(inner, &inner_column.data_type)?;
+            return match data_type {
+                DataType::List(_) => Ok(Self::List(ListEncoderBuilder::new_with_inner(
+                    field, inner,
+                )?)),
+                _ => Ok(Self::LargeList(LargeListEncoderBuilder::new_with_inner(
+                    field, inner,
+                )?)),
+            };
+        }
+        if let Ok(builder) = Self::try_new(field.clone()) {
+            if builder.schema().data_type == *output {
+                return Ok(builder);
+            }
+        }
+        let res = match (&data_type, output) {
+            (DataType::Int8, PostgresType::Char) => {
+                Self::Int8(Int8EncoderBuilder::new_with_output(field, output.clone())?)
+            }
+            (DataType::Int64, PostgresType::Money) => {
+                Self::Int64(Int64EncoderBuilder::new_with_output(field, output.clone())?)
+            }
+            (DataType::UInt32, PostgresType::Oid | PostgresType::Regclass) => Self::UInt32(
+                UInt32EncoderBuilder::new_with_output(field, output.clone())?,
+            ),
+            (DataType::Decimal128(_, _), PostgresType::Money) => Self::Decimal128(
+                Decimal128EncoderBuilder::new_with_output(field, output.clone())?,
+            ),
+            (_, PostgresType::Numeric) => Self::Numeric(NumericEncoderBuilder::new_with_output(
+                field,
+                output.clone(),
+            )?),
+            (DataType::Utf8, _) => Self::String(StringEncoderBuilder::new_with_output(
+                field,
+                output.clone(),
+            )?),
+            (DataType::LargeUtf8, _) => Self::LargeString(
+                LargeStringEncoderBuilder::new_with_output(field, output.clone())?,
+            ),
+            (
+                _,
+                PostgresType::Point
+                | PostgresType::Lseg
+                | PostgresType::Box
+                | PostgresType::Circle
+                | PostgresType::Path
+                | PostgresType::Polygon,
+            ) => Self::Geometric(GeometricEncoderBuilder::new_with_output(
+                field,
+                output.clone(),
+            )?),
+            _ => {
+                let name = field.name().clone();
+                Self::Cast(
+                    CastEncoderBuilder::new_with_output(field, output.clone())
+                        .map_err(|_| ErrorKind::unsupported_output(&name, &data_type, output))?,
+                )
+            }
+        };
+        Ok(res)
+    }
 }
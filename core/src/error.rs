@@ -1,8 +1,60 @@
-use arrow_schema::DataType;
+use std::fmt;
+
+use arrow_schema::{ArrowError, DataType};
 use thiserror::Error;
 
 use crate::pg_schema::PostgresType;
 
+/// A single column that could not be matched against a schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnMismatch {
+    pub column: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ColumnMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.column, self.reason)
+    }
+}
+
+/// Where in the input an encoding error happened
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ErrorLocation {
+    /// The column followed by any struct fields and list indexes, e.g. `tags[2].name`
+    pub path: String,
+    /// The row within the batch
+    pub row: Option<usize>,
+    /// The ordinal of the batch, counting from 0 for the first batch given to the encoder
+    pub batch: Option<usize>,
+    /// The offending value rendered as text
+    pub value: Option<String>,
+}
+
+impl fmt::Display for ErrorLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}", self.path)?;
+        if let Some(row) = self.row {
+            write!(f, ", row {row}")?;
+        }
+        if let Some(batch) = self.batch {
+            write!(f, ", batch {batch}")?;
+        }
+        if let Some(value) = &self.value {
+            write!(f, ", value {value:?}")?;
+        }
+        Ok(())
+    }
+}
+
+fn format_mismatches(columns: &[ColumnMismatch]) -> String {
+    columns
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
 #[derive(Debug, Error)]
 pub enum ErrorKind {
     #[error("Type mismatch for column {field}: expected {expected} but got {actual:?}")]
@@ -38,11 +90,109 @@ pub enum ErrorKind {
     },
     #[error("Missing encoder for field {field}")]
     EncoderMissing { field: String },
+    #[error("Field {field} not found in the Arrow schema or record batch")]
+    FieldNotFound { field: String },
+    #[error("Cannot {operation} while the encoder is in the {state} state")]
+    InvalidState { operation: String, state: String },
+    #[error("Record batch does not match the encoder's schema: {}", format_mismatches(.columns))]
+    SchemaMismatch { columns: Vec<ColumnMismatch> },
+    #[error("No type OID is known for {tp:?} in field {field}")]
+    TypeOidUnknown { field: String, tp: PostgresType },
+    #[error("{location}: {source}")]
+    Located {
+        location: ErrorLocation,
+        source: Box<ErrorKind>,
+    },
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Error reading record batch: {0}")]
+    Read(#[from] ArrowError),
+    #[error("Postgres protocol error: {reason}")]
+    Protocol { reason: String },
+    #[error("Postgres client error: {0}")]
+    Client(#[source] Box<dyn std::error::Error + Send + Sync>),
+    #[error("Invalid value for virtual column {column}: {reason}")]
+    VirtualColumn { column: String, reason: String },
     #[error("No fields match supplied encoder fields: {fields:?}")]
     UnknownFields { fields: Vec<String> },
+    #[error("Arrow type {tp} for field {field} cannot be encoded as {output:?}")]
+    OutputNotSupported {
+        field: String,
+        tp: DataType,
+        output: PostgresType,
+    },
+    #[error("Incompatible with target schema: {}", format_mismatches(.columns))]
+    IncompatibleTargetSchema { columns: Vec<ColumnMismatch> },
+    #[error("Invalid COPY options: {reason}")]
+    InvalidCopyOptions { reason: String },
+    #[error("encoding needs {size} bytes at once, more than the memory limit of {limit} bytes")]
+    MemoryLimitExceeded { size: usize, limit: usize },
 }
 
 impl ErrorKind {
+    /// Where in the input the error happened, if known
+    pub fn location(&self) -> Option<&ErrorLocation> {
+        match self {
+            ErrorKind::Located { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+
+    /// The error without its location
+    pub fn root_cause(&self) -> &ErrorKind {
+        match self {
+            ErrorKind::Located { source, .. } => source.root_cause(),
+            other => other,
+        }
+    }
+
+    /// Prefixes the error's path with `segment`, recording `value` if no value is known yet
+    pub(crate) fn in_field(
+        self,
+        segment: &str,
+        value: impl FnOnce() -> Option<String>,
+    ) -> ErrorKind {
+        match self {
+            ErrorKind::Located {
+                mut location,
+                source,
+            } => {
+                location.path.insert_str(0, segment);
+                ErrorKind::Located { location, source }
+            }
+            other => ErrorKind::Located {
+                location: ErrorLocation {
+                    path: segment.to_string(),
+                    value: value(),
+                    ..Default::default()
+                },
+                source: Box::new(other),
+            },
+        }
+    }
+
+    /// Records the row and batch the error happened in
+    pub(crate) fn at_row(self, row: Option<usize>, batch: usize) -> ErrorKind {
+        match self {
+            ErrorKind::Located {
+                mut location,
+                source,
+            } => {
+                location.row = row;
+                location.batch = Some(batch);
+                ErrorKind::Located { location, source }
+            }
+            other => ErrorKind::Located {
+                location: ErrorLocation {
+                    row,
+                    batch: Some(batch),
+                    ..Default::default()
+                },
+                source: Box::new(other),
+            },
+        }
+    }
+
     pub(crate) fn field_too_large(field: &str, size: usize) -> ErrorKind {
         ErrorKind::FieldTooLarge {
             field: field.to_string(),
@@ -70,6 +220,18 @@ impl ErrorKind {
         }
     }
 
+    pub(crate) fn unsupported_output(
+        field: &str,
+        tp: &DataType,
+        output: &PostgresType,
+    ) -> ErrorKind {
+        ErrorKind::OutputNotSupported {
+            field: field.to_string(),
+            tp: tp.clone(),
+            output: output.clone(),
+        }
+    }
+
     pub(crate) fn mismatched_column_type(
         field: &str,
         expected: &str,
@@ -1,19 +1,41 @@
 use std::collections::HashMap;
+use std::io::Write;
+use std::mem::MaybeUninit;
+use std::ops::Range;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 
-use arrow_array::RecordBatch;
-use arrow_schema::Fields;
-use arrow_schema::Schema;
+use arrow_array::{
+    Array, ArrayRef, Int64Array, RecordBatch, RecordBatchReader, StringArray, UInt32Array,
+};
+use arrow_schema::{ArrowError, Schema};
+use arrow_schema::{DataType, Field, Fields};
 use bytes::{BufMut, BytesMut};
-use error::ErrorKind;
+use error::{ColumnMismatch, ErrorKind};
+use rayon::prelude::*;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 
+#[cfg(feature = "tokio")]
+pub mod async_writer;
+pub mod decode;
 pub mod encoders;
 pub mod error;
+pub mod load;
+pub mod mapping;
+pub mod params;
 pub mod pg_schema;
+pub mod protocol;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+pub mod text;
+pub mod writer;
 
-use crate::encoders::{BuildEncoder, Encode, EncoderBuilder};
-use crate::pg_schema::PostgresSchema;
+use crate::encoders::{render_value, write_at, BuildEncoder, Encode, EncoderBuilder};
+use crate::mapping::{ColumnMapping, ColumnSource};
+use crate::pg_schema::{Column, PostgresSchema};
+use crate::writer::EncodeReader;
 
-const HEADER_MAGIC_BYTES: &[u8] = b"PGCOPY\n\xff\r\n\0";
+pub(crate) const HEADER_MAGIC_BYTES: &[u8] = b"PGCOPY\n\xff\r\n\0";
 
 #[derive(Debug, PartialEq)]
 enum EncoderState {
@@ -22,11 +44,148 @@ enum EncoderState {
     Finished,
 }
 
+/// What `write_batch` does with rows that fail to encode.
+/// Errors affecting a whole batch, such as a schema mismatch, always fail the batch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorPolicy {
+    /// Return the first error
+    #[default]
+    Fail,
+    /// Leave failing rows out of the output
+    SkipRow,
+    /// Leave failing rows out of the output and collect them, see
+    /// [`ArrowToPostgresBinaryEncoder::take_quarantined`]
+    Quarantine,
+}
+
+/// The column holding the error message in quarantined batches
+pub const QUARANTINE_ERROR_COLUMN: &str = "_error";
+
+/// Batches are only split across threads in ranges of at least this many rows
+pub const MIN_ROWS_PER_THREAD: usize = 4096;
+
 #[derive(Debug)]
 pub struct ArrowToPostgresBinaryEncoder {
-    fields: Fields,
+    // where the values of each column come from; fields are looked up by name in each batch
+    sources: Vec<ColumnSource>,
+    // the Postgres column name of each column
+    names: Vec<String>,
     state: EncoderState,
     encoder_builders: Vec<EncoderBuilder>,
+    // used to number rows across batches
+    rows_written: i64,
+    // the number of batches given to the encoder so far, used to locate errors
+    batches_seen: usize,
+    error_policy: ErrorPolicy,
+    rows_skipped: usize,
+    // the rows quarantined since the last `take_quarantined`, by the number of the batch
+    // they are from, in parts when a batch is written in parts
+    quarantined: Vec<(usize, RecordBatch)>,
+    // the threads batches are encoded on, if more than one
+    pool: Option<ThreadPool>,
+}
+
+/// Where a batch, or the part of one being written, sits in the encoder's input
+struct BatchPosition {
+    // the row of the batch the part starts at
+    first_row: usize,
+    // the number of batches before this one, used to locate errors
+    batch_ordinal: usize,
+    // the rows written before the batch, used to number rows
+    rows_written: i64,
+}
+
+/// The rows of a batch that failed to encode, with the errors kept for quarantine
+type RowFailures = (Vec<u32>, Vec<String>);
+
+fn field_names(fields: &Fields) -> Vec<String> {
+    fields.iter().map(|f| f.name().clone()).collect()
+}
+
+fn field_sources(fields: &Fields) -> Vec<ColumnSource> {
+    fields
+        .iter()
+        .map(|f| ColumnSource::Field(f.name().clone()))
+        .collect()
+}
+
+/// Builds a batch holding the given rows of `batch` plus a column with the reason each row failed
+fn quarantine_batch(
+    batch: &RecordBatch,
+    rows: Vec<u32>,
+    errors: Vec<String>,
+) -> Result<RecordBatch, ErrorKind> {
+    let to_error = |e: ArrowError| ErrorKind::Encode {
+        reason: format!("failed to build quarantine batch: {e}"),
+    };
+    let indices = UInt32Array::from(rows);
+    let mut columns = batch
+        .columns()
+        .iter()
+        .map(|col| arrow_select::take::take(col.as_ref(), &indices, None))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(to_error)?;
+    columns.push(Arc::new(StringArray::from(errors)));
+    let mut fields = batch.schema().fields().iter().cloned().collect::<Vec<_>>();
+    fields.push(Arc::new(Field::new(
+        QUARANTINE_ERROR_COLUMN,
+        DataType::Utf8,
+        false,
+    )));
+    RecordBatch::try_new(Arc::new(Schema::new(fields)), columns).map_err(to_error)
+}
+
+fn check_virtual_value(
+    column: &str,
+    value: &dyn Array,
+    data_type: &DataType,
+) -> Result<(), ErrorKind> {
+    if value.len() != 1 {
+        return Err(ErrorKind::VirtualColumn {
+            column: column.to_string(),
+            reason: format!("expected a single value but got {}", value.len()),
+        });
+    }
+    if value.data_type() != data_type {
+        return Err(ErrorKind::VirtualColumn {
+            column: column.to_string(),
+            reason: format!("expected {data_type:?} but got {:?}", value.data_type()),
+        });
+    }
+    Ok(())
+}
+
+/// Appends rows of the given sizes to `buf`, written in place by `write` after `buf`'s
+/// contents. `write` gets the start of each row and moves it past what it writes with
+/// [`write_at`]. Returns false, leaving `buf` as it was, if `write` fails or a row is not
+/// written up to where the next one starts.
+fn append_rows(
+    buf: &mut BytesMut,
+    sizes: &[usize],
+    write: impl FnOnce(&mut [MaybeUninit<u8>], &mut [usize]) -> bool,
+) -> bool {
+    let mut offsets = Vec::with_capacity(sizes.len());
+    let mut total = 0;
+    for size in sizes {
+        offsets.push(total);
+        total += size;
+    }
+    buf.reserve(total);
+    if !write(&mut buf.spare_capacity_mut()[..total], &mut offsets) {
+        return false;
+    }
+    let mut end = 0;
+    for (offset, size) in offsets.iter().zip(sizes) {
+        end += size;
+        if *offset != end {
+            return false;
+        }
+    }
+    // SAFETY: `write_at` only moves an offset past bytes it wrote, and each row's offset
+    // went from where the row before ended to where the next one starts, so all `total`
+    // bytes have been written
+    unsafe { buf.set_len(buf.len() + total) };
+    true
 }
 
 pub fn build_encoders(
@@ -49,9 +208,16 @@ impl ArrowToPostgresBinaryEncoder {
             .collect();
 
         Ok(ArrowToPostgresBinaryEncoder {
-            fields: fields.clone(),
+            sources: field_sources(fields),
+            names: field_names(fields),
             state: EncoderState::Created,
             encoder_builders: maybe_encoder_builders?,
+            rows_written: 0,
+            batches_seen: 0,
+            error_policy: ErrorPolicy::default(),
+            pool: None,
+            rows_skipped: 0,
+            quarantined: vec![],
         })
     }
 
@@ -80,9 +246,116 @@ impl ArrowToPostgresBinaryEncoder {
             });
         }
         Ok(ArrowToPostgresBinaryEncoder {
-            fields: schema.fields.clone(),
+            sources: field_sources(schema.fields()),
+            names: field_names(schema.fields()),
             state: EncoderState::Created,
             encoder_builders: maybe_encoder_builders?,
+            rows_written: 0,
+            batches_seen: 0,
+            error_policy: ErrorPolicy::default(),
+            pool: None,
+            rows_skipped: 0,
+            quarantined: vec![],
+        })
+    }
+
+    /// Creates an encoder that writes into an existing table's schema.
+    /// Columns are matched by name and each one is encoded as the target's type,
+    /// casting where needed. Target columns missing from `schema` are left out of
+    /// the output so that Postgres can fill in their defaults.
+    /// Nullable fields can only be written to nullable columns.
+    pub fn try_new_for_target(schema: &Schema, target: &PostgresSchema) -> Result<Self, ErrorKind> {
+        let mut mismatches = vec![];
+        let mut encoder_builders = vec![];
+        for field in schema.fields() {
+            let column = target
+                .columns
+                .iter()
+                .find(|column| &column.name == field.name());
+            let Some(column) = column else {
+                mismatches.push(ColumnMismatch {
+                    column: field.name().clone(),
+                    reason: "not present in target schema".to_string(),
+                });
+                continue;
+            };
+            if field.is_nullable() && !column.nullable {
+                mismatches.push(ColumnMismatch {
+                    column: field.name().clone(),
+                    reason: "nullable field for a NOT NULL column".to_string(),
+                });
+                continue;
+            }
+            match EncoderBuilder::try_new_with_output(field.clone(), &column.data_type) {
+                Ok(builder) => encoder_builders.push(builder),
+                Err(e) => mismatches.push(ColumnMismatch {
+                    column: field.name().clone(),
+                    reason: e.to_string(),
+                }),
+            }
+        }
+        if !mismatches.is_empty() {
+            return Err(ErrorKind::IncompatibleTargetSchema {
+                columns: mismatches,
+            });
+        }
+        Ok(ArrowToPostgresBinaryEncoder {
+            sources: field_sources(schema.fields()),
+            names: field_names(schema.fields()),
+            state: EncoderState::Created,
+            encoder_builders,
+            rows_written: 0,
+            batches_seen: 0,
+            error_policy: ErrorPolicy::default(),
+            pool: None,
+            rows_skipped: 0,
+            quarantined: vec![],
+        })
+    }
+
+    /// Creates an encoder that writes the columns listed in `mapping`, in that order
+    /// and under their mapped names. Encoders are inferred unless the mapping supplies one.
+    pub fn try_new_with_mapping(
+        schema: &Schema,
+        mapping: &ColumnMapping,
+    ) -> Result<Self, ErrorKind> {
+        let mut encoder_builders = vec![];
+        for column in &mapping.columns {
+            let field = match &column.source {
+                ColumnSource::Field(source) => match schema.field_with_name(source) {
+                    Ok(field) => field.clone(),
+                    Err(_) => {
+                        return Err(ErrorKind::FieldNotFound {
+                            field: source.clone(),
+                        })
+                    }
+                },
+                ColumnSource::Constant(value) => {
+                    check_virtual_value(&column.name, value, value.data_type())?;
+                    Field::new(&column.name, value.data_type().clone(), value.is_null(0))
+                }
+                ColumnSource::PerBatch { data_type, .. } => {
+                    Field::new(&column.name, data_type.clone(), true)
+                }
+                ColumnSource::RowNumber { .. } => Field::new(&column.name, DataType::Int64, false),
+            };
+            let builder = match &column.encoder {
+                Some(builder) => builder.clone(),
+                None => EncoderBuilder::try_new(Arc::new(field))?,
+            };
+            encoder_builders.push(builder);
+        }
+        Ok(ArrowToPostgresBinaryEncoder {
+            sources: mapping.columns.iter().map(|c| c.source.clone()).collect(),
+            names: mapping.columns.iter().map(|c| c.name.clone()).collect(),
+            state: EncoderState::Created,
+            encoder_builders,
+            rows_written: 0,
+            batches_seen: 0,
+            error_policy: ErrorPolicy::default(),
+            pool: None,
+            rows_skipped: 0,
+            quarantined: vec![],
         })
     }
 
@@ -91,72 +364,664 @@ impl ArrowToPostgresBinaryEncoder {
             columns: self
                 .encoder_builders
                 .iter()
-                .map(|builder| (builder.schema()))
+                .zip(&self.names)
+                .map(|(builder, name)| Column {
+                    name: name.clone(),
+                    ..builder.schema()
+                })
                 .collect(),
         }
     }
 
-    pub fn write_header(&mut self, out: &mut BytesMut) {
-        assert_eq!(self.state, EncoderState::Created);
+    fn check_state(&self, operation: &str, expected: EncoderState) -> Result<(), ErrorKind> {
+        if self.state != expected {
+            return Err(ErrorKind::InvalidState {
+                operation: operation.to_string(),
+                state: format!("{:?}", self.state),
+            });
+        }
+        Ok(())
+    }
+
+    /// Looks up the columns to write in `batch` by name and computes virtual columns.
+    /// Columns holding a single value to be repeated for every row are flagged.
+    fn batch_columns(&self, batch: &RecordBatch) -> Result<Vec<(ArrayRef, bool)>, ErrorKind> {
+        self.batch_columns_at(batch, self.rows_written)
+    }
+
+    /// `batch_columns` for a batch written after `rows_written` rows
+    fn batch_columns_at(
+        &self,
+        batch: &RecordBatch,
+        rows_written: i64,
+    ) -> Result<Vec<(ArrayRef, bool)>, ErrorKind> {
+        let schema = batch.schema();
+        let mut columns = vec![];
+        let mut mismatches = vec![];
+        for (idx, (source, builder)) in self.sources.iter().zip(&self.encoder_builders).enumerate()
+        {
+            let column = match source {
+                ColumnSource::Field(field) => {
+                    // batches usually have the same layout as the encoder's schema
+                    let position =
+                        if idx < schema.fields().len() && schema.field(idx).name() == field {
+                            Some(idx)
+                        } else {
+                            schema.index_of(field).ok()
+                        };
+                    let Some(position) = position else {
+                        mismatches.push(ColumnMismatch {
+                            column: field.clone(),
+                            reason: "missing from record batch".to_string(),
+                        });
+                        continue;
+                    };
+                    let expected = builder.field();
+                    let actual = schema.field(position).data_type();
+                    if !actual.equals_datatype(expected.data_type()) {
+                        mismatches.push(ColumnMismatch {
+                            column: field.clone(),
+                            reason: format!(
+                                "expected {:?} but got {actual:?}",
+                                expected.data_type()
+                            ),
+                        });
+                        continue;
+                    }
+                    (batch.column(position).clone(), false)
+                }
+                ColumnSource::Constant(value) => (value.clone(), true),
+                ColumnSource::PerBatch { data_type, value } => {
+                    let value = value(batch)?;
+                    check_virtual_value(&self.names[idx], &value, data_type)?;
+                    (value, true)
+                }
+                ColumnSource::RowNumber { start } => {
+                    let first = start + rows_written;
+                    let values = first..first + batch.num_rows() as i64;
+                    (
+                        Arc::new(Int64Array::from_iter_values(values)) as ArrayRef,
+                        false,
+                    )
+                }
+            };
+            columns.push(column);
+        }
+        if !mismatches.is_empty() {
+            return Err(ErrorKind::SchemaMismatch {
+                columns: mismatches,
+            });
+        }
+        Ok(columns)
+    }
+
+    pub fn set_error_policy(&mut self, policy: ErrorPolicy) {
+        self.error_policy = policy;
+    }
+
+    /// Encodes on a pool of `threads` threads that is created here and reused for every batch.
+    /// Each batch is split into row ranges encoded in parallel, and `write_batches`
+    /// also encodes several batches at once.
+    /// The output, errors and skipped rows are the same as when encoding on one thread.
+    pub fn set_parallelism(&mut self, threads: usize) -> Result<(), ErrorKind> {
+        self.pool = if threads > 1 {
+            let pool = ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .thread_name(|i| format!("pgpq-encoder-{i}"))
+                .build()
+                .map_err(std::io::Error::other)?;
+            Some(pool)
+        } else {
+            None
+        };
+        Ok(())
+    }
+
+    /// The exact number of bytes `write_batch` writes for `batch` if every row encodes,
+    /// except that `TSVECTOR` values and floats written as `NUMERIC` are counted at their
+    /// largest size
+    pub fn encoded_size(&self, batch: &RecordBatch) -> Result<usize, ErrorKind> {
+        let n_rows = batch.num_rows();
+        let mut size = 2 * n_rows; // the field count of each tuple
+        let columns = self.batch_columns(batch)?;
+        for (((col, repeated), builder), name) in
+            columns.iter().zip(&self.encoder_builders).zip(&self.names)
+        {
+            let col_size = builder
+                .try_new(col)
+                .and_then(|encoder| encoder.byte_size_hint())
+                .map_err(|e| e.in_field(name, || None))?;
+            size += if *repeated {
+                col_size * n_rows
+            } else {
+                col_size
+            };
+        }
+        Ok(size)
+    }
+
+    /// An upper bound on the end offset of each row of `batch` in what `write_batch` writes
+    /// for it, starting with 0, so the rows `a..b` take up at most `offsets[b] - offsets[a]`
+    /// bytes. The bounds are computed without encoding any value and are exact except for
+    /// `TSVECTOR` values and floats written as `NUMERIC`. Rows that fail to encode may be
+    /// counted.
+    pub(crate) fn row_offsets(&self, batch: &RecordBatch) -> Result<Vec<usize>, ErrorKind> {
+        let n_rows = batch.num_rows();
+        let mut sizes = vec![2; n_rows]; // the field count of each tuple
+        let columns = self.batch_columns(batch)?;
+        for (((col, repeated), builder), name) in
+            columns.iter().zip(&self.encoder_builders).zip(&self.names)
+        {
+            let in_field = |e: ErrorKind| e.in_field(name, || None);
+            let encoder = builder.try_new(col).map_err(in_field)?;
+            if *repeated {
+                let mut value_size = [0];
+                encoder
+                    .add_column_size_bounds(0..1, &mut value_size)
+                    .map_err(in_field)?;
+                sizes.iter_mut().for_each(|s| *s += value_size[0]);
+            } else {
+                encoder
+                    .add_column_size_bounds(0..n_rows, &mut sizes)
+                    .map_err(in_field)?;
+            }
+        }
+        let mut offsets = Vec::with_capacity(n_rows + 1);
+        offsets.push(0);
+        let mut end = 0;
+        for size in sizes {
+            end += size;
+            offsets.push(end);
+        }
+        Ok(offsets)
+    }
+
+    /// The number of rows left out of the output because they failed to encode
+    pub fn rows_skipped(&self) -> usize {
+        self.rows_skipped
+    }
+
+    /// Returns the rows quarantined since the last call, one batch per input batch
+    /// that had failing rows. Each batch has the input's columns plus
+    /// [`QUARANTINE_ERROR_COLUMN`].
+    pub fn take_quarantined(&mut self) -> Vec<RecordBatch> {
+        let mut batches = vec![];
+        let mut parts = std::mem::take(&mut self.quarantined).into_iter().peekable();
+        while let Some((batch_ordinal, part)) = parts.next() {
+            let mut batch_parts = vec![part];
+            while let Some((_, part)) = parts.next_if(|(next, _)| *next == batch_ordinal) {
+                batch_parts.push(part);
+            }
+            if batch_parts.len() == 1 {
+                batches.extend(batch_parts);
+                continue;
+            }
+            let schema = batch_parts[0].schema();
+            batches.push(
+                arrow_select::concat::concat_batches(&schema, &batch_parts)
+                    .expect("the parts of a batch are quarantined with the same schema"),
+            );
+        }
+        batches
+    }
+
+    /// Quarantines the given rows of `batch`, a part of the batch being written
+    fn quarantine(
+        &mut self,
+        batch: &RecordBatch,
+        rows: Vec<u32>,
+        errors: Vec<String>,
+    ) -> Result<(), ErrorKind> {
+        let batch_ordinal = self.batches_seen.saturating_sub(1);
+        self.quarantined
+            .push((batch_ordinal, quarantine_batch(batch, rows, errors)?));
+        Ok(())
+    }
+
+    pub fn write_header(&mut self, out: &mut BytesMut) -> Result<(), ErrorKind> {
+        self.check_state("write the header", EncoderState::Created)?;
         out.put(HEADER_MAGIC_BYTES);
         out.put_i32(0); // flags
         out.put_i32(0); // header extension
         self.state = EncoderState::Encoding;
+        Ok(())
     }
 
+    /// Encodes every row of `batch` into `buf`.
+    /// If an error is returned nothing from the batch is left in `buf`
+    /// and the encoder can keep on being used with the next batch.
     pub fn write_batch(
         &mut self,
         batch: &RecordBatch,
         buf: &mut BytesMut,
     ) -> Result<(), ErrorKind> {
-        assert_eq!(self.state, EncoderState::Encoding);
-        assert!(
-            batch.num_columns() == self.fields.len(),
-            "expected {} values but got {}",
-            self.fields.len(),
-            batch.num_columns(),
-        );
+        self.write_batch_part(batch, 0, buf)
+    }
+
+    /// Encodes `batches` into `buf` like calling `write_batch` for each of them in order,
+    /// but encodes several batches at once on the pool set up by `set_parallelism`.
+    /// If a batch fails, the batches before it are left in `buf`, nothing from it or the
+    /// batches after it is written, and its error is returned.
+    pub fn write_batches(
+        &mut self,
+        batches: &[RecordBatch],
+        buf: &mut BytesMut,
+    ) -> Result<(), ErrorKind> {
+        self.check_state("write a batch", EncoderState::Encoding)?;
+        let Some(pool) = &self.pool else {
+            for batch in batches {
+                self.write_batch(batch, buf)?;
+            }
+            return Ok(());
+        };
+        // the position each batch has if every batch before it is written
+        let mut rows_written = self.rows_written;
+        let positions = batches
+            .iter()
+            .enumerate()
+            .map(|(idx, batch)| {
+                let position = BatchPosition {
+                    first_row: 0,
+                    batch_ordinal: self.batches_seen + idx,
+                    rows_written,
+                };
+                rows_written += batch.num_rows() as i64;
+                position
+            })
+            .collect::<Vec<_>>();
+        // batches after a failed one are not written, so they stop early
+        let first_failed = AtomicUsize::new(usize::MAX);
+        let parts = pool.install(|| {
+            batches
+                .par_iter()
+                .zip(positions)
+                .enumerate()
+                .map(|(idx, (batch, position))| {
+                    let mut part = BytesMut::new();
+                    let cancelled = || first_failed.load(Ordering::Relaxed) < idx;
+                    let outcome =
+                        self.encode_rows(batch, &position, Some(pool), &cancelled, &mut part);
+                    if outcome.is_err() {
+                        first_failed.fetch_min(idx, Ordering::Relaxed);
+                    }
+                    outcome.map(|failures| failures.map(|failures| (part, failures)))
+                })
+                .collect::<Vec<_>>()
+        });
+        for (batch, part) in batches.iter().zip(parts) {
+            self.batches_seen += 1;
+            // only batches after the first failing one are cancelled
+            let Some((part, failures)) = part? else {
+                break;
+            };
+            self.record_failures(batch, failures)?;
+            buf.extend_from_slice(&part);
+        }
+        Ok(())
+    }
+
+    /// Writes `part`, the rows of a batch starting at `first_row`.
+    /// A part with a `first_row` other than 0 continues the batch of the previous call,
+    /// so errors are located by the batch's number and row rather than the part's.
+    pub(crate) fn write_batch_part(
+        &mut self,
+        part: &RecordBatch,
+        first_row: usize,
+        buf: &mut BytesMut,
+    ) -> Result<(), ErrorKind> {
+        let batch_start = buf.len();
+        let res = self.encode_batch(part, first_row, buf);
+        if res.is_err() {
+            buf.truncate(batch_start);
+        }
+        res
+    }
+
+    fn encode_batch(
+        &mut self,
+        batch: &RecordBatch,
+        first_row: usize,
+        buf: &mut BytesMut,
+    ) -> Result<(), ErrorKind> {
+        self.check_state("write a batch", EncoderState::Encoding)?;
+        if first_row == 0 {
+            self.batches_seen += 1;
+        }
+        let position = BatchPosition {
+            first_row,
+            batch_ordinal: self.batches_seen.saturating_sub(1),
+            rows_written: self.rows_written,
+        };
+        let failures = self
+            .encode_rows(batch, &position, self.pool.as_ref(), &|| false, buf)?
+            .unwrap_or_default();
+        self.record_failures(batch, failures)
+    }
+
+    /// Encodes the rows of `batch` into `buf`, splitting them into ranges encoded on `pool`,
+    /// and returns the rows that failed under the error policy.
+    /// Stops and returns None once `cancelled` returns true.
+    fn encode_rows(
+        &self,
+        batch: &RecordBatch,
+        position: &BatchPosition,
+        pool: Option<&ThreadPool>,
+        cancelled: &(dyn Fn() -> bool + Sync),
+        buf: &mut BytesMut,
+    ) -> Result<Option<RowFailures>, ErrorKind> {
         let n_rows = batch.num_rows();
-        let n_cols = batch.num_columns();
+        let n_cols = self.sources.len();
 
-        let encoders = batch
-            .columns()
+        let columns = self.batch_columns_at(batch, position.rows_written)?;
+        // repeated columns render their single value but report the row being written
+        let locate = |e: ErrorKind, col: usize, row: Option<usize>| {
+            let value_row = if columns[col].1 { row.map(|_| 0) } else { row };
+            let value = || value_row.and_then(|row| render_value(columns[col].0.as_ref(), row));
+            e.in_field(&self.names[col], value).at_row(
+                row.map(|row| row + position.first_row),
+                position.batch_ordinal,
+            )
+        };
+        let encoders = columns
             .iter()
             .zip(&self.encoder_builders)
-            .map(|(col, builder)| builder.try_new(col))
-            .collect::<Result<Vec<_>, _>>()?;
+            .enumerate()
+            .map(|(idx, ((col, repeated), builder))| {
+                let encoder = builder.try_new(col).map_err(|e| locate(e, idx, None))?;
+                Ok((encoder, *repeated))
+            })
+            .collect::<Result<Vec<_>, ErrorKind>>()?;
 
-        let mut required_size: usize = 0;
-        for encoder in &encoders {
-            required_size += encoder.byte_size_hint()?
+        // the field count of each tuple plus the fields
+        let mut required_size: usize = 2 * n_rows;
+        for (idx, (encoder, repeated)) in encoders.iter().enumerate() {
+            let size = encoder.byte_size_hint().map_err(|e| locate(e, idx, None))?;
+            required_size += if *repeated { size * n_rows } else { size };
         }
         buf.reserve(required_size);
 
-        for row in 0..n_rows {
+        let encode_row = |row: usize, buf: &mut BytesMut| -> Result<(), ErrorKind> {
             buf.put_i16(n_cols as i16);
-            for encoder in &encoders {
-                encoder.encode(row, buf)?
+            for (idx, (encoder, repeated)) in encoders.iter().enumerate() {
+                encoder
+                    .encode(if *repeated { 0 } else { row }, buf)
+                    .map_err(|e| locate(e, idx, Some(row)))?
+            }
+            Ok(())
+        };
+
+        // column by column: size every row, then write each column's values into place,
+        // copying the single encoded value of repeated columns into every row.
+        // Returns false, leaving `buf` untouched, if an encoder cannot size its column up
+        // front or a value fails to encode; the rows are then encoded one by one,
+        // which also reports the error.
+        let encode_columns = |rows: Range<usize>, buf: &mut BytesMut| {
+            let mut sizes = vec![2; rows.len()]; // the field count
+            let mut repeated_values = Vec::with_capacity(n_cols);
+            for (encoder, repeated) in encoders.iter() {
+                if *repeated {
+                    let mut value = BytesMut::new();
+                    if encoder.encode(0, &mut value).is_err() {
+                        return false;
+                    }
+                    sizes.iter_mut().for_each(|size| *size += value.len());
+                    repeated_values.push(Some(value));
+                } else if matches!(encoder.add_column_sizes(rows.clone(), &mut sizes), Ok(true)) {
+                    repeated_values.push(None);
+                } else {
+                    return false;
+                }
+            }
+            append_rows(buf, &sizes, |out, offsets| {
+                for offset in offsets.iter_mut() {
+                    write_at(out, offset, |dst| dst.put_i16(n_cols as i16));
+                }
+                for ((encoder, _), value) in encoders.iter().zip(&repeated_values) {
+                    let written = match value {
+                        Some(value) => {
+                            for offset in offsets.iter_mut() {
+                                write_at(out, offset, |dst| dst.put_slice(value));
+                            }
+                            Ok(())
+                        }
+                        None => encoder.scatter_column(rows.clone(), out, offsets),
+                    };
+                    if written.is_err() {
+                        return false;
+                    }
+                }
+                true
+            })
+        };
+
+        let error_policy = self.error_policy;
+        // returns None if `cancelled` turned true before the rows were encoded
+        let encode_rows = |rows: Range<usize>,
+                           buf: &mut BytesMut,
+                           cancelled: &dyn Fn() -> bool|
+         -> Result<Option<RowFailures>, ErrorKind> {
+            let mut failed_rows = vec![];
+            let mut errors = vec![];
+            if cancelled() {
+                return Ok(None);
+            }
+            if encode_columns(rows.clone(), buf) {
+                return Ok(Some((failed_rows, errors)));
+            }
+            for row in rows {
+                if cancelled() {
+                    return Ok(None);
+                }
+                let row_start = buf.len();
+                if let Err(e) = encode_row(row, buf) {
+                    // drop the partially written tuple; with ErrorPolicy::Fail
+                    // write_batch also drops the tuples written before it
+                    buf.truncate(row_start);
+                    match error_policy {
+                        ErrorPolicy::Fail => return Err(e),
+                        ErrorPolicy::SkipRow => {}
+                        ErrorPolicy::Quarantine => errors.push(e.to_string()),
+                    }
+                    failed_rows.push(row as u32);
+                }
+            }
+            Ok(Some((failed_rows, errors)))
+        };
+
+        let threads = pool
+            .map_or(1, |pool| pool.current_num_threads())
+            .min(n_rows / MIN_ROWS_PER_THREAD)
+            .max(1);
+        let (Some(pool), true) = (pool, threads > 1) else {
+            return encode_rows(0..n_rows, buf, cancelled);
+        };
+        // each thread encodes a contiguous range into its own buffer; appending the
+        // buffers in order gives the same bytes as encoding every row here
+        let rows_per_thread = n_rows.div_ceil(threads);
+        let ranges = (0..n_rows)
+            .step_by(rows_per_thread)
+            .map(|start| start..(start + rows_per_thread).min(n_rows))
+            .collect::<Vec<_>>();
+        // with ErrorPolicy::Fail only the first failing row is reported,
+        // so a range stops once a range before it has failed
+        let first_failed = AtomicUsize::new(usize::MAX);
+        let parts = pool.install(|| {
+            ranges
+                .into_par_iter()
+                .enumerate()
+                .map(|(idx, rows)| {
+                    let mut part = BytesMut::with_capacity(required_size / threads);
+                    let range_cancelled =
+                        || cancelled() || first_failed.load(Ordering::Relaxed) < idx;
+                    let outcome = encode_rows(rows, &mut part, &range_cancelled);
+                    if outcome.is_err() {
+                        first_failed.fetch_min(idx, Ordering::Relaxed);
+                    }
+                    outcome.map(|failures| failures.map(|failures| (part, failures)))
+                })
+                .collect::<Vec<_>>()
+        });
+        let mut failed_rows = vec![];
+        let mut errors = vec![];
+        // the first failing range holds the first failing row, and ranges are
+        // only cancelled after it
+        for part in parts {
+            let Some((part, (part_failed_rows, part_errors))) = part? else {
+                return Ok(None);
+            };
+            buf.extend_from_slice(&part);
+            failed_rows.extend(part_failed_rows);
+            errors.extend(part_errors);
+        }
+        Ok(Some((failed_rows, errors)))
+    }
+
+    /// Counts a batch as written, quarantining the rows that failed to encode
+    fn record_failures(
+        &mut self,
+        batch: &RecordBatch,
+        (failed_rows, errors): RowFailures,
+    ) -> Result<(), ErrorKind> {
+        let n_failed = failed_rows.len();
+        if !errors.is_empty() {
+            self.quarantine(batch, failed_rows, errors)?;
+        }
+        self.rows_written += batch.num_rows() as i64;
+        self.rows_skipped += n_failed;
+        Ok(())
+    }
+
+    /// Writes `row`, a batch of a single row that is row `first_row` of a larger batch
+    /// (see `write_batch_part`), to `sink` holding at most `memory_limit` bytes of it in memory.
+    /// Values that can be borrowed from Arrow buffers are written to `sink` as they are,
+    /// so only the other fields count towards the limit.
+    /// Nothing is written if the row fails to encode. A row over the limit is not invalid,
+    /// so it fails with `ErrorKind::MemoryLimitExceeded` whatever the error policy.
+    pub(crate) fn stream_row<W: Write>(
+        &mut self,
+        row: &RecordBatch,
+        first_row: usize,
+        sink: &mut W,
+        memory_limit: usize,
+    ) -> Result<(), ErrorKind> {
+        self.check_state("write a batch", EncoderState::Encoding)?;
+        debug_assert_eq!(row.num_rows(), 1);
+        if first_row == 0 {
+            self.batches_seen += 1;
+        }
+        let batch_ordinal = self.batches_seen.saturating_sub(1);
+        let columns = self.batch_columns(row)?;
+        let locate = |e: ErrorKind, col: usize| {
+            let value = || render_value(columns[col].0.as_ref(), 0);
+            e.in_field(&self.names[col], value)
+                .at_row(Some(first_row), batch_ordinal)
+        };
+        let encoders = columns
+            .iter()
+            .zip(&self.encoder_builders)
+            .enumerate()
+            .map(|(idx, ((col, _), builder))| builder.try_new(col).map_err(|e| locate(e, idx)))
+            .collect::<Result<Vec<_>, ErrorKind>>()?;
+
+        // the encoded fields in `scratch`, except for the borrowed values, which follow
+        // the bytes of `scratch` up to the offset they are paired with
+        let mut scratch = BytesMut::new();
+        let mut borrowed = vec![];
+        let encoded = (|| {
+            scratch.put_i16(encoders.len() as i16);
+            for (idx, encoder) in encoders.iter().enumerate() {
+                if let Some(value) = encoder.borrowed_value(0) {
+                    let len = i32::try_from(value.len()).map_err(|_| {
+                        locate(
+                            ErrorKind::field_too_large(&self.names[idx], value.len()),
+                            idx,
+                        )
+                    })?;
+                    scratch.put_i32(len);
+                    borrowed.push((scratch.len(), value));
+                    continue;
+                }
+                // an upper bound computed without encoding the value, so the limit is
+                // checked before anything is allocated
+                let size = encoder.byte_size_hint().map_err(|e| locate(e, idx))?;
+                if scratch.len() + size > memory_limit {
+                    let size = scratch.len() + size;
+                    // the value is too large to render
+                    return Err(ErrorKind::MemoryLimitExceeded {
+                        size,
+                        limit: memory_limit,
+                    }
+                    .in_field(&self.names[idx], || None)
+                    .at_row(Some(first_row), batch_ordinal));
+                }
+                encoder
+                    .encode(0, &mut scratch)
+                    .map_err(|e| locate(e, idx))?;
+            }
+            Ok(())
+        })();
+        if let Err(e) = encoded {
+            if matches!(e.root_cause(), ErrorKind::MemoryLimitExceeded { .. }) {
+                return Err(e);
+            }
+            match self.error_policy {
+                ErrorPolicy::Fail => return Err(e),
+                ErrorPolicy::SkipRow => {}
+                ErrorPolicy::Quarantine => self.quarantine(row, vec![0], vec![e.to_string()])?,
             }
+            self.rows_written += 1;
+            self.rows_skipped += 1;
+            return Ok(());
         }
+        let mut written = 0;
+        for (offset, value) in borrowed {
+            sink.write_all(&scratch[written..offset])?;
+            sink.write_all(value.prefix)?;
+            sink.write_all(value.bytes)?;
+            written = offset;
+        }
+        sink.write_all(&scratch[written..])?;
+        self.rows_written += 1;
         Ok(())
     }
 
     pub fn write_footer(&mut self, out: &mut BytesMut) -> Result<(), ErrorKind> {
-        assert_eq!(self.state, EncoderState::Encoding);
+        self.check_state("write the footer", EncoderState::Encoding)?;
         out.put_i16(-1);
         self.state = EncoderState::Finished;
         Ok(())
     }
+
+    /// Encodes every batch of `reader` into COPY chunks of roughly `chunk_size` bytes,
+    /// using an encoder inferred from the reader's schema
+    pub fn encode_reader<R: RecordBatchReader>(
+        reader: R,
+        chunk_size: usize,
+    ) -> Result<EncodeReader<R>, ErrorKind> {
+        let encoder = Self::try_new(&reader.schema())?;
+        Ok(EncodeReader::new(encoder, reader, chunk_size))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use std::{collections::HashMap, sync::Arc};
 
-    use crate::{encoders::StringEncoderBuilder, pg_schema::Column};
+    use crate::{
+        encoders::{
+            Decimal128EncoderBuilder, GeometricEncoderBuilder, NumericEncoderBuilder,
+            StringEncoderBuilder, UInt32EncoderBuilder,
+        },
+        pg_schema::Column,
+    };
 
     use super::*;
-    use arrow_array::{Int32Array, Int8Array, StringArray};
+    use arrow::buffer::OffsetBuffer;
+    use arrow_array::{
+        builder::{ListBuilder, StringBuilder},
+        Array, ArrayRef, Decimal128Array, Float32Array, Float64Array, Int32Array, Int8Array,
+        ListArray, StringArray, StructArray, UInt32Array,
+    };
     use arrow_schema::{DataType, Field};
 
     fn make_test_data() -> RecordBatch {
@@ -236,4 +1101,1218 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn test_geometric_encoders() {
+        let point_fields = Fields::from(vec![
+            Field::new("x", DataType::Float64, false),
+            Field::new("y", DataType::Float64, false),
+        ]);
+        let points = StructArray::new(
+            point_fields.clone(),
+            vec![
+                Arc::new(Float64Array::from(vec![1.0, 2.0, 3.0])),
+                Arc::new(Float64Array::from(vec![-1.0, -2.0, -3.0])),
+            ],
+            None,
+        );
+        let polygons = ListArray::new(
+            Arc::new(Field::new(
+                "item",
+                DataType::Struct(point_fields.clone()),
+                false,
+            )),
+            OffsetBuffer::new(vec![0, 3].into()),
+            Arc::new(points.clone()),
+            None,
+        );
+        let points = StructArray::new(
+            point_fields,
+            points.columns().to_vec(),
+            Some(vec![true, false, true].into()),
+        );
+        let schema = Schema::new(vec![
+            Field::new("point", points.data_type().clone(), true),
+            Field::new("polygon", polygons.data_type().clone(), false),
+        ]);
+        let encoders: HashMap<String, EncoderBuilder> = schema
+            .fields()
+            .iter()
+            .map(|f| {
+                let builder = GeometricEncoderBuilder::new(f.clone()).unwrap();
+                (f.name().clone(), EncoderBuilder::Geometric(builder))
+            })
+            .collect();
+        let mut encoder =
+            ArrowToPostgresBinaryEncoder::try_new_with_encoders(&schema, &encoders).unwrap();
+        assert_eq!(
+            encoder
+                .schema()
+                .columns
+                .iter()
+                .map(|c| c.data_type.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                pg_schema::PostgresType::Point,
+                pg_schema::PostgresType::Polygon
+            ]
+        );
+
+        // only encode the first row, a point and a triangle
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(points.slice(0, 1)), Arc::new(polygons)],
+        )
+        .unwrap();
+        let mut buf = BytesMut::new();
+        encoder.write_header(&mut buf).unwrap();
+        let header_len = buf.len();
+        encoder.write_batch(&batch, &mut buf).unwrap();
+
+        let mut expected = BytesMut::new();
+        expected.put_i16(2);
+        expected.put_i32(16);
+        expected.put_f64(1.0);
+        expected.put_f64(-1.0);
+        expected.put_i32(4 + 3 * 16);
+        expected.put_i32(3);
+        for (x, y) in [(1.0, -1.0), (2.0, -2.0), (3.0, -3.0)] {
+            expected.put_f64(x);
+            expected.put_f64(y);
+        }
+        assert_eq!(&buf[header_len..], &expected[..]);
+    }
+
+    #[test]
+    fn test_geometric_encoder_rejects_wrong_shape() {
+        let field = Arc::new(Field::new("p", DataType::Float64, false));
+        assert!(GeometricEncoderBuilder::new(field.clone()).is_err());
+        let field = Arc::new(Field::new(
+            "p",
+            DataType::Struct(Fields::from(vec![
+                Field::new("x", DataType::Float64, false),
+                Field::new("y", DataType::Float64, false),
+            ])),
+            false,
+        ));
+        assert!(
+            GeometricEncoderBuilder::new_with_output(field, pg_schema::PostgresType::Circle)
+                .is_err()
+        );
+    }
+
+    fn encode_column(builder: EncoderBuilder, col: ArrayRef) -> Result<BytesMut, ErrorKind> {
+        let schema = Schema::new(vec![builder.field().as_ref().clone()]);
+        let encoders = HashMap::from([(builder.field().name().clone(), builder)]);
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new_with_encoders(&schema, &encoders)?;
+        let batch = RecordBatch::try_new(Arc::new(schema), vec![col]).unwrap();
+        let mut buf = BytesMut::new();
+        encoder.write_header(&mut buf).unwrap();
+        let header_len = buf.len();
+        encoder.write_batch(&batch, &mut buf)?;
+        Ok(buf.split_off(header_len))
+    }
+
+    #[test]
+    fn test_byte_size_hint_is_exact() {
+        let field = |name: &str, tp: DataType| Arc::new(Field::new(name, tp, true));
+        let strings =
+            |values: Vec<Option<&str>>| -> ArrayRef { Arc::new(StringArray::from(values)) };
+        let list = ListArray::new(
+            Arc::new(Field::new("item", DataType::Int32, true)),
+            OffsetBuffer::new(vec![0, 2, 2, 5, 6].into()),
+            Arc::new(Int32Array::from(vec![
+                Some(1),
+                None,
+                Some(3),
+                Some(4),
+                Some(5),
+                None,
+            ])),
+            Some(vec![true, false, true, true].into()),
+        );
+        let cases: Vec<(EncoderBuilder, ArrayRef)> = vec![
+            (
+                EncoderBuilder::try_new(field("n", DataType::Decimal128(20, 4))).unwrap(),
+                Arc::new(
+                    Decimal128Array::from(vec![Some(1), None, Some(-123_456_789), Some(10_000)])
+                        .with_precision_and_scale(20, 4)
+                        .unwrap(),
+                ),
+            ),
+            (
+                EncoderBuilder::String(
+                    StringEncoderBuilder::new_with_output(
+                        field("j", DataType::Utf8),
+                        pg_schema::PostgresType::Jsonb,
+                    )
+                    .unwrap(),
+                ),
+                strings(vec![Some("{}"), None, Some("[1, 2]")]),
+            ),
+            (
+                EncoderBuilder::try_new(field("l", list.data_type().clone())).unwrap(),
+                Arc::new(list.slice(1, 3)),
+            ),
+            (
+                EncoderBuilder::try_new(field("s", DataType::Utf8)).unwrap(),
+                strings(vec![Some("abc"), None, Some("de"), Some("")]).slice(1, 3),
+            ),
+        ];
+        for (builder, col) in cases {
+            let hint = builder.try_new(&col).unwrap().byte_size_hint().unwrap();
+            let encoded = encode_column(builder, col.clone()).unwrap();
+            // each tuple also holds a field count
+            assert_eq!(hint + 2 * col.len(), encoded.len(), "{:?}", col.data_type());
+        }
+    }
+
+    #[test]
+    fn test_row_offsets() {
+        let batch = RecordBatch::try_from_iter(vec![
+            (
+                "doc",
+                Arc::new(StringArray::from(vec![Some("a b"), None, Some("c:1,2")])) as ArrayRef,
+            ),
+            (
+                "name",
+                Arc::new(StringArray::from(vec![Some("x"), Some("yz"), None])) as ArrayRef,
+            ),
+        ])
+        .unwrap();
+        let schema = batch.schema();
+        // text columns are sized from their offsets, and tsvector columns bounded from them
+        let tsvector = StringEncoderBuilder::new_with_output(
+            Arc::new(schema.field(0).clone()),
+            pg_schema::PostgresType::Tsvector,
+        )
+        .unwrap();
+        let text = EncoderBuilder::try_new(Arc::new(schema.field(1).clone())).unwrap();
+        let encoders = HashMap::from([
+            ("doc".to_string(), EncoderBuilder::String(tsvector)),
+            ("name".to_string(), text),
+        ]);
+        let mut encoder =
+            ArrowToPostgresBinaryEncoder::try_new_with_encoders(&schema, &encoders).unwrap();
+        encoder.write_header(&mut BytesMut::new()).unwrap();
+        let offsets = encoder.row_offsets(&batch).unwrap();
+        // the field count, then `a b` is bounded by 4 + 4 + 2 * 3 + 2 bytes, which is
+        // exactly its size, and the text by its length
+        assert_eq!(
+            offsets,
+            vec![0, 2 + 16 + 5, 23 + 2 + 4 + 6, 35 + 2 + 20 + 4]
+        );
+        // `c:1,2` takes 4 bytes less than its bound
+        for (row, slack) in [0, 0, 4].into_iter().enumerate() {
+            let mut buf = BytesMut::new();
+            encoder.write_batch(&batch.slice(row, 1), &mut buf).unwrap();
+            assert_eq!(
+                offsets[row + 1] - offsets[row],
+                buf.len() + slack,
+                "row {row}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_numeric_size_hint() {
+        let values = [
+            0,
+            1,
+            -1,
+            10,
+            1000,
+            9999,
+            10_000,
+            -12_345,
+            100_000_000,
+            -123_456_789,
+            10_i128.pow(37),
+            10_i128.pow(38) - 1,
+        ];
+        let mut cases: Vec<ArrayRef> = vec![Arc::new(Int64Array::from(vec![
+            0,
+            i64::MIN,
+            i64::MAX,
+            100,
+            -7,
+        ]))];
+        for scale in [-5, -3, 0, 1, 3, 4, 5, 8, 12] {
+            cases.push(Arc::new(
+                Decimal128Array::from_iter_values(values)
+                    .with_precision_and_scale(38, scale)
+                    .unwrap(),
+            ));
+        }
+        for col in cases {
+            let field = Arc::new(Field::new("n", col.data_type().clone(), false));
+            let builder = EncoderBuilder::Numeric(NumericEncoderBuilder::new(field).unwrap());
+            let hint = builder.try_new(&col).unwrap().byte_size_hint().unwrap();
+            let encoded = encode_column(builder, col.clone()).unwrap();
+            // each tuple also holds a field count
+            assert_eq!(hint + 2 * col.len(), encoded.len(), "{:?}", col.data_type());
+        }
+
+        // floats are counted at their largest size, which values with the most digits reach
+        let floats: Vec<(ArrayRef, usize)> = vec![
+            (
+                Arc::new(Float32Array::from(vec![
+                    1.234_567_9e-7,
+                    0.5,
+                    f32::NAN,
+                    f32::MIN_POSITIVE,
+                ])),
+                4 + 8 + 2 * 3,
+            ),
+            (
+                Arc::new(Float64Array::from(vec![
+                    1.234_567_890_123_456_7e-7,
+                    0.5,
+                    f64::INFINITY,
+                    f64::MAX,
+                ])),
+                4 + 8 + 2 * 5,
+            ),
+        ];
+        for (col, max_size) in floats {
+            let field = Arc::new(Field::new("n", col.data_type().clone(), false));
+            let builder = EncoderBuilder::Numeric(NumericEncoderBuilder::new(field).unwrap());
+            let encoder = builder.try_new(&col).unwrap();
+            let hint = encoder.byte_size_hint().unwrap();
+            assert_eq!(hint, col.len() * max_size);
+            let mut value = BytesMut::new();
+            encoder.encode(0, &mut value).unwrap();
+            assert_eq!(value.len(), max_size, "{:?}", col.data_type());
+            let encoded = encode_column(builder, col.clone()).unwrap();
+            assert!(
+                hint + 2 * col.len() >= encoded.len(),
+                "{:?}",
+                col.data_type()
+            );
+        }
+    }
+
+    #[test]
+    fn test_money_and_oid_encoders() {
+        let field = Arc::new(Field::new("price", DataType::Decimal128(10, 3), true));
+        let builder = EncoderBuilder::Decimal128(
+            Decimal128EncoderBuilder::new_with_output(
+                field.clone(),
+                pg_schema::PostgresType::Money,
+            )
+            .unwrap(),
+        );
+        let col: ArrayRef = Arc::new(
+            Decimal128Array::from(vec![Some(12_340), None, Some(-5_000)])
+                .with_precision_and_scale(10, 3)
+                .unwrap(),
+        );
+        let buf = encode_column(builder.clone(), col.clone()).unwrap();
+        let mut expected = BytesMut::new();
+        for cents in [Some(1_234_i64), None, Some(-500)] {
+            expected.put_i16(1);
+            match cents {
+                Some(cents) => {
+                    expected.put_i32(8);
+                    expected.put_i64(cents);
+                }
+                None => expected.put_i32(-1),
+            }
+        }
+        assert_eq!(buf, expected);
+        // every value has a length prefix, nulls included
+        let hint = builder.try_new(&col).unwrap().byte_size_hint().unwrap();
+        assert_eq!(hint, 2 * (4 + 8) + 4);
+        // 12.345 can't be represented in cents
+        let col = Decimal128Array::from(vec![12_345])
+            .with_precision_and_scale(10, 3)
+            .unwrap();
+        assert!(encode_column(builder, Arc::new(col)).is_err());
+
+        let field = Arc::new(Field::new("relid", DataType::UInt32, false));
+        let builder =
+            UInt32EncoderBuilder::new_with_output(field.clone(), pg_schema::PostgresType::Regclass)
+                .unwrap();
+        assert_eq!(
+            builder.schema().data_type,
+            pg_schema::PostgresType::Regclass
+        );
+        let buf = encode_column(
+            EncoderBuilder::UInt32(builder),
+            Arc::new(UInt32Array::from(vec![u32::MAX])),
+        )
+        .unwrap();
+        assert_eq!(&buf[..], &[0, 1, 0, 0, 0, 4, 255, 255, 255, 255]);
+        assert!(
+            UInt32EncoderBuilder::new_with_output(field, pg_schema::PostgresType::Money).is_err()
+        );
+    }
+
+    #[test]
+    fn test_xml_and_tsvector_encoders() {
+        let field = Arc::new(Field::new("doc", DataType::Utf8, false));
+        let xml = |check_well_formed: bool| {
+            EncoderBuilder::String(
+                StringEncoderBuilder::new_xml(field.clone(), check_well_formed).unwrap(),
+            )
+        };
+        let valid = StringArray::from(vec![
+            "<?xml version=\"1.0\"?><a><b x=\"1\"/>text</a>",
+            "some text <a></a><b/>",
+        ]);
+        assert!(encode_column(xml(true), Arc::new(valid)).is_ok());
+        for invalid in ["<a><b></a></b>", "<a>", "<a x=1/>"] {
+            let col = Arc::new(StringArray::from(vec![invalid]));
+            assert!(encode_column(xml(true), col.clone()).is_err(), "{invalid}");
+            assert!(encode_column(xml(false), col).is_ok(), "{invalid}");
+        }
+
+        let builder = EncoderBuilder::String(
+            StringEncoderBuilder::new_with_output(field, pg_schema::PostgresType::Tsvector)
+                .unwrap(),
+        );
+        let buf = encode_column(
+            builder.clone(),
+            Arc::new(StringArray::from(vec!["rat fat:4,2A  rat:1"])),
+        )
+        .unwrap();
+        let mut expected = BytesMut::new();
+        expected.put_i16(1);
+        expected.put_i32(4 + 4 + 2 + 2 * 2 + 4 + 2 + 2);
+        expected.put_i32(2);
+        expected.put_slice(b"fat\0");
+        expected.put_u16(2);
+        expected.put_u16(3 << 14 | 2);
+        expected.put_u16(4);
+        expected.put_slice(b"rat\0");
+        expected.put_u16(1);
+        expected.put_u16(1);
+        assert_eq!(buf, expected);
+        let col = Arc::new(StringArray::from(vec!["fat:0"]));
+        assert!(encode_column(builder.clone(), col).is_err());
+
+        // the size hint bounds values without parsing them, and single byte lexemes
+        // separated by single spaces reach the bound
+        let values = vec!["", "a", "a b c", "a:1,2,3A", "ab ab:1 ab:1", "\"a\\ b\""];
+        for value in values {
+            let col: ArrayRef = Arc::new(StringArray::from(vec![value]));
+            let hint = builder.try_new(&col).unwrap().byte_size_hint().unwrap();
+            let encoded = encode_column(builder.clone(), col).unwrap();
+            assert!(hint + 2 >= encoded.len(), "{value:?}");
+            if matches!(value, "a" | "a b c") {
+                assert_eq!(hint + 2, encoded.len(), "{value:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_encode_for_target_schema() {
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int16, false),
+            Field::new("score", DataType::Float64, true),
+            Field::new("day", DataType::Date32, false),
+            Field::new("uid", DataType::Utf8, false),
+            Field::new("ratio", DataType::Float32, false),
+        ]);
+        let target = PostgresSchema {
+            columns: vec![
+                Column {
+                    name: "uid".to_string(),
+                    data_type: pg_schema::PostgresType::Uuid,
+                    nullable: false,
+                },
+                Column {
+                    name: "id".to_string(),
+                    data_type: pg_schema::PostgresType::Int8,
+                    nullable: false,
+                },
+                Column {
+                    name: "score".to_string(),
+                    data_type: pg_schema::PostgresType::Numeric,
+                    nullable: true,
+                },
+                Column {
+                    name: "day".to_string(),
+                    data_type: pg_schema::PostgresType::Timestamp,
+                    nullable: false,
+                },
+                Column {
+                    name: "ratio".to_string(),
+                    data_type: pg_schema::PostgresType::Float8,
+                    nullable: false,
+                },
+                Column {
+                    name: "created_at".to_string(),
+                    data_type: pg_schema::PostgresType::Timestamp,
+                    nullable: false,
+                },
+            ],
+        };
+        let mut encoder =
+            ArrowToPostgresBinaryEncoder::try_new_for_target(&schema, &target).unwrap();
+        let types: Vec<_> = encoder
+            .schema()
+            .columns
+            .into_iter()
+            .map(|c| (c.name, c.data_type))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                ("id".to_string(), pg_schema::PostgresType::Int8),
+                ("score".to_string(), pg_schema::PostgresType::Numeric),
+                ("day".to_string(), pg_schema::PostgresType::Timestamp),
+                ("uid".to_string(), pg_schema::PostgresType::Uuid),
+                ("ratio".to_string(), pg_schema::PostgresType::Float8),
+            ]
+        );
+
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(arrow_array::Int16Array::from(vec![7])),
+                Arc::new(Float64Array::from(vec![12.5])),
+                Arc::new(arrow_array::Date32Array::from(vec![10_958])),
+                Arc::new(StringArray::from(vec![
+                    "{A0EEBC99-9C0B-4EF8-BB6D-6BB9BD380A11}",
+                ])),
+                Arc::new(arrow_array::Float32Array::from(vec![0.5])),
+            ],
+        )
+        .unwrap();
+        let mut buf = BytesMut::new();
+        encoder.write_header(&mut buf).unwrap();
+        let header_len = buf.len();
+        encoder.write_batch(&batch, &mut buf).unwrap();
+
+        let mut expected = BytesMut::new();
+        expected.put_i16(5);
+        expected.put_i32(8);
+        expected.put_i64(7);
+        // 12.5 is the base 10000 digits 12 and 5000 with weight 0 and dscale 1
+        expected.put_i32(12);
+        for v in [2, 0, 0, 1, 12, 5000] {
+            expected.put_i16(v);
+        }
+        // 2000-01-02 00:00:00
+        expected.put_i32(8);
+        expected.put_i64(86_400_000_000);
+        expected.put_i32(16);
+        expected.put_slice(&[
+            0xa0, 0xee, 0xbc, 0x99, 0x9c, 0x0b, 0x4e, 0xf8, 0xbb, 0x6d, 0x6b, 0xb9, 0xbd, 0x38,
+            0x0a, 0x11,
+        ]);
+        expected.put_i32(8);
+        expected.put_f64(0.5);
+        assert_eq!(buf.split_off(header_len), expected);
+
+        // the INT2 id is cast to INT8: a length prefix and 8 bytes
+        let hint = encoder.encoder_builders[0]
+            .try_new(batch.column(0))
+            .unwrap()
+            .byte_size_hint()
+            .unwrap();
+        assert_eq!(hint, 4 + 8);
+    }
+
+    #[test]
+    fn test_decimal_mapping() {
+        let field = Arc::new(Field::new("amount", DataType::Decimal128(10, 2), true));
+        assert_eq!(
+            EncoderBuilder::try_new(field.clone())
+                .unwrap()
+                .schema()
+                .data_type,
+            pg_schema::PostgresType::Numeric
+        );
+        let builder = Decimal128EncoderBuilder::new(field.clone()).unwrap();
+        assert_eq!(builder.schema().data_type, pg_schema::PostgresType::Money);
+        assert!(
+            Decimal128EncoderBuilder::new_with_output(field, pg_schema::PostgresType::Numeric)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_encode_for_target_schema_lists_all_mismatches() {
+        let schema = Schema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("big", DataType::Int64, false),
+            Field::new("extra", DataType::Int32, false),
+            Field::new("ok", DataType::Int8, false),
+            Field::new("maybe", DataType::Int32, true),
+        ]);
+        let column = |name: &str, data_type| Column {
+            name: name.to_string(),
+            data_type,
+            nullable: false,
+        };
+        let target = PostgresSchema {
+            columns: vec![
+                column("name", pg_schema::PostgresType::Int4),
+                column("big", pg_schema::PostgresType::Int2),
+                column("ok", pg_schema::PostgresType::Int4),
+                column("maybe", pg_schema::PostgresType::Int4),
+            ],
+        };
+        match ArrowToPostgresBinaryEncoder::try_new_for_target(&schema, &target) {
+            Err(ErrorKind::IncompatibleTargetSchema { columns }) => {
+                let names: Vec<_> = columns.iter().map(|c| c.column.as_str()).collect();
+                assert_eq!(names, vec!["name", "big", "extra", "maybe"]);
+            }
+            other => panic!("expected IncompatibleTargetSchema, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_numeric_encoder() {
+        let field = Arc::new(Field::new("amount", DataType::Decimal128(10, 2), true));
+        let builder = EncoderBuilder::try_new(field).unwrap();
+        assert_eq!(builder.schema().data_type, pg_schema::PostgresType::Numeric);
+        let col = Decimal128Array::from(vec![Some(-1_234_567), Some(0), None])
+            .with_precision_and_scale(10, 2)
+            .unwrap();
+        let buf = encode_column(builder, Arc::new(col)).unwrap();
+        let mut expected = BytesMut::new();
+        // -12345.67
+        expected.put_i16(1);
+        expected.put_i32(14);
+        for v in [3, 1, 0x4000, 2, 1, 2345, 6700] {
+            expected.put_i16(v as i16);
+        }
+        // zero has no digits
+        expected.put_i16(1);
+        expected.put_i32(8);
+        for v in [0, 0, 0, 2] {
+            expected.put_i16(v);
+        }
+        expected.put_i16(1);
+        expected.put_i32(-1);
+        assert_eq!(buf, expected);
+
+        let field = Arc::new(Field::new("x", DataType::Float64, false));
+        let builder =
+            EncoderBuilder::try_new_with_output(field, &pg_schema::PostgresType::Numeric).unwrap();
+        let col = Float64Array::from(vec![1e-7, f64::NAN]);
+        let buf = encode_column(builder, Arc::new(col)).unwrap();
+        let mut expected = BytesMut::new();
+        expected.put_i16(1);
+        expected.put_i32(10);
+        for v in [1, -2, 0, 7, 10] {
+            expected.put_i16(v);
+        }
+        expected.put_i16(1);
+        expected.put_i32(8);
+        for v in [0, 0, 0xC000_u16 as i16, 0] {
+            expected.put_i16(v);
+        }
+        assert_eq!(buf, expected);
+    }
+
+    #[test]
+    fn test_column_mapping() {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, false),
+            Field::new("c", DataType::Int8, true),
+        ]);
+        let mapping = ColumnMapping::default().rename("c", "code").column("a");
+        let mut encoder =
+            ArrowToPostgresBinaryEncoder::try_new_with_mapping(&schema, &mapping).unwrap();
+        assert_eq!(
+            encoder.schema().ddl("t"),
+            "CREATE TEMP TABLE \"t\" (\"code\" INT2, \"a\" INT4 NOT NULL);"
+        );
+
+        // the batch's columns are matched by name, not position
+        let batch_schema = Schema::new(vec![
+            Field::new("b", DataType::Utf8, false),
+            Field::new("c", DataType::Int8, true),
+            Field::new("a", DataType::Int32, false),
+        ]);
+        let batch = RecordBatch::try_new(
+            Arc::new(batch_schema),
+            vec![
+                Arc::new(StringArray::from(vec!["x"])),
+                Arc::new(Int8Array::from(vec![None])),
+                Arc::new(Int32Array::from(vec![42])),
+            ],
+        )
+        .unwrap();
+        let mut buf = BytesMut::new();
+        encoder.write_header(&mut buf).unwrap();
+        let header_len = buf.len();
+        encoder.write_batch(&batch, &mut buf).unwrap();
+        let mut expected = BytesMut::new();
+        expected.put_i16(2);
+        expected.put_i32(-1);
+        expected.put_i32(4);
+        expected.put_i32(42);
+        assert_eq!(buf.split_off(header_len), expected);
+
+        let batch = batch.project(&[0, 1]).unwrap();
+        assert!(matches!(
+            encoder.write_batch(&batch, &mut buf),
+            Err(ErrorKind::SchemaMismatch { columns }) if columns[0].column == "a"
+        ));
+
+        let mapping = ColumnMapping::select(["a", "missing"]);
+        assert!(matches!(
+            ArrowToPostgresBinaryEncoder::try_new_with_mapping(&schema, &mapping),
+            Err(ErrorKind::FieldNotFound { field }) if field == "missing"
+        ));
+    }
+
+    #[test]
+    fn test_virtual_columns() {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
+        let mapping = ColumnMapping::from_schema(&schema)
+            .constant("load_id", Arc::new(StringArray::from(vec!["L1"])))
+            .per_batch(
+                "batch_rows",
+                DataType::Int32,
+                Arc::new(|batch: &RecordBatch| {
+                    Ok(Arc::new(Int32Array::from(vec![batch.num_rows() as i32])) as ArrayRef)
+                }),
+            )
+            .row_number("row_id", 1);
+        let mut encoder =
+            ArrowToPostgresBinaryEncoder::try_new_with_mapping(&schema, &mapping).unwrap();
+        assert_eq!(
+            encoder.schema().ddl("t"),
+            "CREATE TEMP TABLE \"t\" (\"a\" INT4 NOT NULL, \"load_id\" TEXT NOT NULL, \"batch_rows\" INT4, \"row_id\" INT8 NOT NULL);"
+        );
+
+        let mut buf = BytesMut::new();
+        encoder.write_header(&mut buf).unwrap();
+        let header_len = buf.len();
+        let mut expected = BytesMut::new();
+        let mut row_id = 1;
+        for values in [vec![10, 20], vec![30]] {
+            let n_rows = values.len() as i32;
+            let batch = RecordBatch::try_new(
+                Arc::new(schema.clone()),
+                vec![Arc::new(Int32Array::from(values.clone()))],
+            )
+            .unwrap();
+            encoder.write_batch(&batch, &mut buf).unwrap();
+            for v in values {
+                expected.put_i16(4);
+                expected.put_i32(4);
+                expected.put_i32(v);
+                expected.put_i32(2);
+                expected.put_slice(b"L1");
+                expected.put_i32(4);
+                expected.put_i32(n_rows);
+                expected.put_i32(8);
+                expected.put_i64(row_id);
+                row_id += 1;
+            }
+        }
+        assert_eq!(buf.split_off(header_len), expected);
+
+        let mapping =
+            ColumnMapping::default().constant("c", Arc::new(Int32Array::from(vec![1, 2])));
+        assert!(matches!(
+            ArrowToPostgresBinaryEncoder::try_new_with_mapping(&schema, &mapping),
+            Err(ErrorKind::VirtualColumn { column, .. }) if column == "c"
+        ));
+    }
+
+    #[test]
+    fn test_lifecycle_errors() {
+        let schema = Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, false),
+        ]);
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&schema).unwrap();
+        let mut buf = BytesMut::new();
+        assert!(matches!(
+            encoder.write_footer(&mut buf),
+            Err(ErrorKind::InvalidState { .. })
+        ));
+        encoder.write_header(&mut buf).unwrap();
+        assert!(matches!(
+            encoder.write_header(&mut buf),
+            Err(ErrorKind::InvalidState { .. })
+        ));
+
+        let batch_schema = Schema::new(vec![Field::new("a", DataType::Int64, false)]);
+        let batch = RecordBatch::try_new(
+            Arc::new(batch_schema),
+            vec![Arc::new(arrow_array::Int64Array::from(vec![1]))],
+        )
+        .unwrap();
+        match encoder.write_batch(&batch, &mut buf) {
+            Err(ErrorKind::SchemaMismatch { columns }) => {
+                let names: Vec<_> = columns.iter().map(|c| c.column.as_str()).collect();
+                assert_eq!(names, vec!["a", "b"]);
+            }
+            other => panic!("expected SchemaMismatch, got {other:?}"),
+        }
+        // the encoder can still be used after a bad batch
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![
+                Arc::new(Int32Array::from(vec![1])),
+                Arc::new(StringArray::from(vec!["x"])),
+            ],
+        )
+        .unwrap();
+        encoder.write_batch(&batch, &mut buf).unwrap();
+        encoder.write_footer(&mut buf).unwrap();
+        assert!(matches!(
+            encoder.write_batch(&batch, &mut buf),
+            Err(ErrorKind::InvalidState { .. })
+        ));
+    }
+
+    #[test]
+    fn test_error_location() {
+        let date_field = Arc::new(Field::new("d", DataType::Date32, true));
+        let schema = Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("days", DataType::List(date_field.clone()), false),
+            Field::new(
+                "s",
+                DataType::Struct(vec![date_field.clone()].into()),
+                false,
+            ),
+        ]);
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&schema).unwrap();
+        let mut buf = BytesMut::new();
+        encoder.write_header(&mut buf).unwrap();
+
+        let make_batch = |days: Vec<i32>, s: Vec<i32>| {
+            let offsets = OffsetBuffer::from_lengths([days.len()]);
+            let days = ListArray::new(
+                date_field.clone(),
+                offsets,
+                Arc::new(arrow_array::Date32Array::from(days)),
+                None,
+            );
+            let s = StructArray::new(
+                vec![date_field.clone()].into(),
+                vec![Arc::new(arrow_array::Date32Array::from(s)) as ArrayRef],
+                None,
+            );
+            RecordBatch::try_new(
+                Arc::new(schema.clone()),
+                vec![
+                    Arc::new(Int32Array::from(vec![1])),
+                    Arc::new(days),
+                    Arc::new(s),
+                ],
+            )
+            .unwrap()
+        };
+        encoder
+            .write_batch(&make_batch(vec![0], vec![0]), &mut buf)
+            .unwrap();
+
+        let err = encoder
+            .write_batch(&make_batch(vec![0, i32::MIN], vec![0]), &mut buf)
+            .unwrap_err();
+        let location = err.location().unwrap();
+        assert_eq!(location.path, "days[1]");
+        assert_eq!(location.row, Some(0));
+        assert_eq!(location.batch, Some(1));
+        // arrow can't display this date so it renders the raw value in an error
+        assert!(location.value.as_ref().unwrap().contains("-2147483648"));
+        assert!(matches!(err.root_cause(), ErrorKind::Encode { .. }));
+        assert!(err
+            .to_string()
+            .starts_with("column days[1], row 0, batch 1"));
+
+        let err = encoder
+            .write_batch(&make_batch(vec![], vec![i32::MIN]), &mut buf)
+            .unwrap_err();
+        let location = err.location().unwrap();
+        assert_eq!(location.path, "s.d");
+        assert_eq!(location.batch, Some(2));
+    }
+
+    #[test]
+    fn test_error_policy() {
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("day", DataType::Date32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![1, 2, 3])),
+                Arc::new(arrow_array::Date32Array::from(vec![
+                    10_957,
+                    i32::MIN,
+                    10_958,
+                ])),
+            ],
+        )
+        .unwrap();
+        let encode = |policy: ErrorPolicy| {
+            let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&schema).unwrap();
+            encoder.set_error_policy(policy);
+            let mut buf = BytesMut::new();
+            encoder.write_header(&mut buf).unwrap();
+            let header_len = buf.len();
+            let res = encoder.write_batch(&batch, &mut buf);
+            (encoder, res, buf.split_off(header_len))
+        };
+        let row = |id: i32, day: i32| {
+            let mut buf = BytesMut::new();
+            buf.put_i16(2);
+            buf.put_i32(4);
+            buf.put_i32(id);
+            buf.put_i32(4);
+            buf.put_i32(day);
+            buf
+        };
+
+        // nothing from the failed batch is left in the buffer
+        let (mut encoder, res, buf) = encode(ErrorPolicy::Fail);
+        assert!(res.is_err());
+        assert!(buf.is_empty());
+        // and the encoder can still be used
+        let mut buf = BytesMut::new();
+        encoder.write_batch(&batch.slice(0, 1), &mut buf).unwrap();
+        assert_eq!(buf, row(1, 0));
+
+        let mut expected = row(1, 0);
+        expected.extend_from_slice(&row(3, 1));
+        let (mut encoder, res, buf) = encode(ErrorPolicy::SkipRow);
+        res.unwrap();
+        assert_eq!(buf, expected);
+        assert_eq!(encoder.rows_skipped(), 1);
+        assert!(encoder.take_quarantined().is_empty());
+
+        let (mut encoder, res, buf) = encode(ErrorPolicy::Quarantine);
+        res.unwrap();
+        assert_eq!(buf, expected);
+        let quarantined = encoder.take_quarantined();
+        assert_eq!(quarantined.len(), 1);
+        let quarantined = &quarantined[0];
+        assert_eq!(quarantined.num_rows(), 1);
+        assert_eq!(
+            quarantined.schema().field(2).name(),
+            QUARANTINE_ERROR_COLUMN
+        );
+        let ids = quarantined
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int32Array>()
+            .unwrap();
+        assert_eq!(ids.value(0), 2);
+        let errors = quarantined
+            .column(2)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert!(errors.value(0).starts_with("column day, row 1, batch 0"));
+        assert!(encoder.take_quarantined().is_empty());
+
+        // the parts of a batch written separately are quarantined as one batch
+        let (mut encoder, ..) = encode(ErrorPolicy::Quarantine);
+        let mut buf = BytesMut::new();
+        for (first_row, len) in [(0, 2), (2, 1)] {
+            encoder
+                .write_batch_part(&batch.slice(first_row, len), first_row, &mut buf)
+                .unwrap();
+        }
+        let quarantined = encoder.take_quarantined();
+        assert_eq!(
+            quarantined.iter().map(|b| b.num_rows()).collect::<Vec<_>>(),
+            vec![1, 1]
+        );
+        let failing = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from(vec![4, 5, 6])),
+                Arc::new(arrow_array::Date32Array::from(vec![i32::MIN, 0, i32::MIN])),
+            ],
+        )
+        .unwrap();
+        for (first_row, len) in [(0, 2), (2, 1)] {
+            encoder
+                .write_batch_part(&failing.slice(first_row, len), first_row, &mut buf)
+                .unwrap();
+        }
+        encoder.write_batch(&failing, &mut buf).unwrap();
+        let quarantined = encoder.take_quarantined();
+        assert_eq!(
+            quarantined.iter().map(|b| b.num_rows()).collect::<Vec<_>>(),
+            vec![2, 2]
+        );
+        let errors = quarantined[0]
+            .column(2)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert!(errors.value(1).starts_with("column day, row 2, batch 2"));
+    }
+
+    #[test]
+    fn test_column_scatter_matches_row_encoding() {
+        let columns: Vec<(&str, ArrayRef)> = vec![
+            (
+                "i",
+                Arc::new(Int32Array::from(vec![Some(1), None, Some(3), Some(-4)])),
+            ),
+            (
+                "f",
+                Arc::new(Float64Array::from(vec![0.5, 1.5, -2.0, 8.25])),
+            ),
+            (
+                "d",
+                Arc::new(arrow_array::Date32Array::from(vec![
+                    Some(0),
+                    Some(19_000),
+                    None,
+                    Some(-1),
+                ])),
+            ),
+            (
+                "s",
+                Arc::new(StringArray::from(vec![
+                    Some("a"),
+                    Some(""),
+                    None,
+                    Some("long value"),
+                ])),
+            ),
+            (
+                "b",
+                Arc::new(arrow_array::BinaryArray::from(vec![
+                    Some(&b"\x00\x01"[..]),
+                    None,
+                    Some(&b""[..]),
+                    Some(&b"xyz"[..]),
+                ])),
+            ),
+        ];
+        let batch = RecordBatch::try_from_iter(columns).unwrap();
+        let mut builders = batch
+            .schema()
+            .fields()
+            .iter()
+            .map(|field| EncoderBuilder::try_new(field.clone()).unwrap())
+            .collect::<Vec<_>>();
+        let mut columns = batch.columns().to_vec();
+
+        // the fixed-width encoders picked for a target type, lists and composites
+        let point = |x: f64, y: f64| -> ArrayRef {
+            Arc::new(StructArray::from(vec![
+                (
+                    Arc::new(Field::new("x", DataType::Float64, false)),
+                    Arc::new(Float64Array::from(vec![x, x + 1.0, x + 2.0, x + 3.0])) as ArrayRef,
+                ),
+                (
+                    Arc::new(Field::new("y", DataType::Float64, false)),
+                    Arc::new(Float64Array::from(vec![y; 4])) as ArrayRef,
+                ),
+            ]))
+        };
+        let struct_of = |fields: Vec<(&str, ArrayRef)>| -> ArrayRef {
+            Arc::new(StructArray::from(
+                fields
+                    .into_iter()
+                    .map(|(name, col)| {
+                        let field = Field::new(name, col.data_type().clone(), true);
+                        (Arc::new(field), col)
+                    })
+                    .collect::<Vec<_>>(),
+            ))
+        };
+        let mut list = ListBuilder::new(StringBuilder::new());
+        list.append_value([Some("a"), None]);
+        list.append_null();
+        list.append_value([Some("long value")]);
+        list.append_value(Vec::<Option<&str>>::new());
+        let with_output: Vec<(ArrayRef, Option<pg_schema::PostgresType>)> = vec![
+            (
+                Arc::new(
+                    Decimal128Array::from(vec![Some(1_234), None, Some(-5), Some(0)])
+                        .with_precision_and_scale(10, 2)
+                        .unwrap(),
+                ),
+                Some(pg_schema::PostgresType::Money),
+            ),
+            (
+                Arc::new(arrow_array::Int16Array::from(vec![
+                    Some(1),
+                    None,
+                    Some(-3),
+                    Some(4),
+                ])),
+                Some(pg_schema::PostgresType::Int8),
+            ),
+            (point(0.0, 1.0), Some(pg_schema::PostgresType::Point)),
+            (
+                struct_of(vec![("start", point(0.0, 1.0)), ("end", point(2.0, 3.0))]),
+                Some(pg_schema::PostgresType::Lseg),
+            ),
+            (
+                struct_of(vec![
+                    ("center", point(0.0, 1.0)),
+                    ("radius", Arc::new(Float64Array::from(vec![1.0; 4]))),
+                ]),
+                Some(pg_schema::PostgresType::Circle),
+            ),
+            (Arc::new(list.finish()), None),
+            (
+                struct_of(vec![
+                    (
+                        "a",
+                        Arc::new(Int32Array::from(vec![Some(1), None, Some(3), Some(4)])),
+                    ),
+                    (
+                        "b",
+                        Arc::new(StringArray::from(vec![Some("x"), Some("yz"), None, None])),
+                    ),
+                ]),
+                None,
+            ),
+        ];
+        for (col, output) in with_output {
+            let field = Arc::new(Field::new("c", col.data_type().clone(), true));
+            builders.push(match output {
+                Some(output) => EncoderBuilder::try_new_with_output(field, &output).unwrap(),
+                None => EncoderBuilder::try_new(field).unwrap(),
+            });
+            columns.push(col);
+        }
+
+        for (builder, col) in builders.iter().zip(&columns) {
+            let field = builder.field();
+            let encoder = builder.try_new(col).unwrap();
+            // a range not starting at 0, as encoded by the parallel path
+            let rows = 1..col.len();
+            let mut expected = BytesMut::new();
+            for row in rows.clone() {
+                encoder.encode(row, &mut expected).unwrap();
+            }
+            let mut sizes = vec![0; rows.len()];
+            assert!(encoder.add_column_sizes(rows.clone(), &mut sizes).unwrap());
+            assert_eq!(sizes.iter().sum::<usize>(), expected.len(), "{field:?}");
+            let mut bounds = vec![0; rows.len()];
+            encoder
+                .add_column_size_bounds(rows.clone(), &mut bounds)
+                .unwrap();
+            assert_eq!(bounds, sizes, "{field:?}");
+            let mut out = BytesMut::from(&b"before"[..]);
+            assert!(append_rows(&mut out, &sizes, |dst, offsets| {
+                encoder.scatter_column(rows.clone(), dst, offsets).is_ok()
+            }));
+            assert_eq!(&out[6..], &expected[..], "{field:?}");
+        }
+
+        // a failing value makes the scatter fail so the batch is encoded row by row
+        let dates = arrow_array::Date32Array::from(vec![0, i32::MIN]);
+        let field = Arc::new(Field::new("d", DataType::Date32, false));
+        let builder = EncoderBuilder::try_new(field).unwrap();
+        let encoder = builder.try_new(&dates).unwrap();
+        let mut out = vec![MaybeUninit::new(0); 16];
+        assert!(encoder.scatter_column(0..2, &mut out, &mut [0, 8]).is_err());
+    }
+
+    #[test]
+    fn test_parallel_encoding_matches_serial() {
+        let n_rows = MIN_ROWS_PER_THREAD * 3 + 17;
+        let schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, true),
+            Field::new("day", DataType::Date32, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(Int32Array::from_iter_values(0..n_rows as i32)),
+                Arc::new(StringArray::from_iter(
+                    (0..n_rows).map(|i| (i % 7 != 0).then(|| "x".repeat(i % 13))),
+                )),
+                // every 5000th date is out of range for Postgres
+                Arc::new(arrow_array::Date32Array::from_iter_values(
+                    (0..n_rows as i32).map(|i| if i % 5000 == 4999 { i32::MIN } else { i }),
+                )),
+            ],
+        )
+        .unwrap();
+        let encode = |policy: ErrorPolicy, threads: usize| {
+            let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&schema).unwrap();
+            encoder.set_error_policy(policy);
+            encoder.set_parallelism(threads).unwrap();
+            let mut buf = BytesMut::new();
+            encoder.write_header(&mut buf).unwrap();
+            let res = encoder
+                .write_batch(&batch, &mut buf)
+                .map_err(|e| e.to_string());
+            (res, buf, encoder.rows_skipped(), encoder.take_quarantined())
+        };
+
+        for policy in [
+            ErrorPolicy::Fail,
+            ErrorPolicy::SkipRow,
+            ErrorPolicy::Quarantine,
+        ] {
+            let serial = encode(policy, 1);
+            for threads in [2, 3, 8] {
+                assert_eq!(
+                    encode(policy, threads),
+                    serial,
+                    "{policy:?} on {threads} threads"
+                );
+            }
+        }
+        assert!(encode(ErrorPolicy::Fail, 4).0.is_err());
+        assert_eq!(encode(ErrorPolicy::SkipRow, 4).2, 2);
+    }
+
+    #[test]
+    fn test_write_batches_matches_write_batch() {
+        let schema = Arc::new(Schema::new(vec![Field::new(
+            "day",
+            DataType::Date32,
+            false,
+        )]));
+        let batch = |days: Vec<i32>| {
+            RecordBatch::try_new(
+                schema.clone(),
+                vec![Arc::new(arrow_array::Date32Array::from(days))],
+            )
+            .unwrap()
+        };
+        // the third batch holds a date that is out of range for Postgres
+        let batches = vec![
+            batch(vec![1, 2]),
+            batch(vec![]),
+            batch(vec![3, i32::MIN, 4]),
+            batch(vec![5]),
+        ];
+        let make_encoder = |policy: ErrorPolicy| {
+            let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&schema).unwrap();
+            encoder.set_error_policy(policy);
+            encoder.write_header(&mut BytesMut::new()).unwrap();
+            encoder
+        };
+
+        for policy in [ErrorPolicy::Fail, ErrorPolicy::Quarantine] {
+            let mut encoder = make_encoder(policy);
+            let mut serial = BytesMut::new();
+            let serial_res = batches
+                .iter()
+                .try_for_each(|b| encoder.write_batch(b, &mut serial))
+                .map_err(|e| e.to_string());
+            let serial_quarantined = encoder.take_quarantined();
+
+            let mut encoder = make_encoder(policy);
+            encoder.set_parallelism(3).unwrap();
+            let mut buf = BytesMut::new();
+            let res = encoder
+                .write_batches(&batches, &mut buf)
+                .map_err(|e| e.to_string());
+            assert_eq!(res, serial_res, "{policy:?}");
+            assert_eq!(buf, serial, "{policy:?}");
+            assert_eq!(encoder.take_quarantined(), serial_quarantined, "{policy:?}");
+        }
+    }
 }
@@ -0,0 +1,878 @@
+use std::io::Write;
+
+use arrow_array::RecordBatch;
+use arrow_schema::Schema;
+
+use crate::decode::decode_copy_binary;
+use crate::error::{ColumnMismatch, ErrorKind};
+use crate::pg_schema::{quote_ident, CopyStatementOptions, PostgresSchema, PostgresType};
+use crate::writer::CopyWriter;
+use crate::ArrowToPostgresBinaryEncoder;
+
+/// The name of the temporary table batches are copied into before being inserted
+pub const DEFAULT_STAGING_TABLE: &str = "_pgpq_staging";
+
+/// What to do with rows that conflict with rows already in the target table
+#[derive(Debug, Clone, PartialEq)]
+pub enum OnConflict {
+    /// Skip conflicting rows. With no columns any unique constraint counts.
+    DoNothing { columns: Vec<String> },
+    /// Overwrite every other column of the existing row
+    DoUpdate { columns: Vec<String> },
+}
+
+/// How batches are loaded into a table through a staging table
+#[derive(Debug, Clone)]
+pub struct CopyOptions {
+    pub staging_table: String,
+    /// The schema of the target table; when set, every column is cast to the target type
+    pub target: Option<PostgresSchema>,
+    pub on_conflict: Option<OnConflict>,
+    /// The minimum size of the chunks sent to the server
+    pub chunk_size: usize,
+}
+
+impl Default for CopyOptions {
+    fn default() -> Self {
+        Self {
+            staging_table: DEFAULT_STAGING_TABLE.to_string(),
+            target: None,
+            on_conflict: None,
+            chunk_size: crate::writer::DEFAULT_FLUSH_THRESHOLD,
+        }
+    }
+}
+
+impl CopyOptions {
+    pub fn with_staging_table(mut self, staging_table: &str) -> Self {
+        self.staging_table = staging_table.to_string();
+        self
+    }
+    pub fn with_target(mut self, target: PostgresSchema) -> Self {
+        self.target = Some(target);
+        self
+    }
+    pub fn with_on_conflict(mut self, on_conflict: OnConflict) -> Self {
+        self.on_conflict = Some(on_conflict);
+        self
+    }
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+}
+
+fn column_list(names: &[&str]) -> String {
+    names
+        .iter()
+        .map(|n| quote_ident(n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Drops the staging table if it exists
+pub fn drop_staging_statement(options: &CopyOptions) -> String {
+    format!(
+        "DROP TABLE IF EXISTS pg_temp.{}",
+        quote_ident(&options.staging_table)
+    )
+}
+
+/// The statements run before the COPY: drop any leftover staging table and create a new one
+pub fn staging_statements(schema: &PostgresSchema, options: &CopyOptions) -> String {
+    format!(
+        "{};\n{}",
+        drop_staging_statement(options),
+        schema.ddl(&options.staging_table)
+    )
+}
+
+/// The encoder for batches with `schema`, matched against the target table when one is set
+fn load_encoder(
+    schema: &Schema,
+    options: &CopyOptions,
+) -> Result<ArrowToPostgresBinaryEncoder, ErrorKind> {
+    match &options.target {
+        Some(target) => ArrowToPostgresBinaryEncoder::try_new_for_target(schema, target),
+        None => ArrowToPostgresBinaryEncoder::try_new(schema),
+    }
+}
+
+/// The COPY statement that loads the staging table.
+/// Temporary tables come first in the search path, so the name needs no `pg_temp` prefix.
+pub fn copy_statement(schema: &PostgresSchema, options: &CopyOptions) -> Result<String, ErrorKind> {
+    schema.copy_statement(&options.staging_table, &CopyStatementOptions::default())
+}
+
+/// The ` ON CONFLICT ...` clause of an insert of the columns `names`, or nothing
+fn conflict_clause(on_conflict: Option<&OnConflict>, names: &[&str]) -> String {
+    match on_conflict {
+        None => String::new(),
+        Some(OnConflict::DoNothing { columns }) if columns.is_empty() => {
+            " ON CONFLICT DO NOTHING".to_string()
+        }
+        Some(OnConflict::DoNothing { columns }) => {
+            let keys = columns.iter().map(String::as_str).collect::<Vec<_>>();
+            format!(" ON CONFLICT ({}) DO NOTHING", column_list(&keys))
+        }
+        Some(OnConflict::DoUpdate { columns }) => {
+            let keys = columns.iter().map(String::as_str).collect::<Vec<_>>();
+            let updates = names
+                .iter()
+                .filter(|n| !columns.iter().any(|k| k == *n))
+                .map(|n| format!("{0} = EXCLUDED.{0}", quote_ident(n)))
+                .collect::<Vec<_>>();
+            if updates.is_empty() {
+                format!(" ON CONFLICT ({}) DO NOTHING", column_list(&keys))
+            } else {
+                format!(
+                    " ON CONFLICT ({}) DO UPDATE SET {}",
+                    column_list(&keys),
+                    updates.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// Whether values of `tp` are or contain composite values
+fn is_composite(tp: &PostgresType) -> bool {
+    match tp {
+        PostgresType::UserDefined { .. } => true,
+        PostgresType::List(inner) => is_composite(&inner.data_type),
+        _ => false,
+    }
+}
+
+/// The statements run after the COPY: move the rows into `table` and drop the staging table.
+/// `table` is used verbatim so it may be schema-qualified; quote it if needed.
+/// Composite columns are rejected: the staging table would need types of its own
+/// that could not be inserted into the target's composite types.
+pub fn insert_statements(
+    table: &str,
+    schema: &PostgresSchema,
+    options: &CopyOptions,
+) -> Result<String, ErrorKind> {
+    let composites = schema
+        .columns
+        .iter()
+        .filter(|c| is_composite(&c.data_type))
+        .map(|c| ColumnMismatch {
+            column: c.name.clone(),
+            reason: "composite columns cannot be loaded through a staging table".to_string(),
+        })
+        .collect::<Vec<_>>();
+    if !composites.is_empty() {
+        return Err(ErrorKind::IncompatibleTargetSchema {
+            columns: composites,
+        });
+    }
+    let names = schema
+        .columns
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect::<Vec<_>>();
+    let values = match &options.target {
+        None => column_list(&names),
+        Some(target) => {
+            let mut values = Vec::with_capacity(names.len());
+            let mut mismatches = Vec::new();
+            for name in &names {
+                match target.columns.iter().find(|c| c.name == *name) {
+                    Some(column) => match (&column.data_type, column.data_type.name()) {
+                        (PostgresType::UserDefined { .. }, _) | (_, None) => {
+                            values.push(quote_ident(name))
+                        }
+                        (_, Some(tp)) => values.push(format!("{}::{tp}", quote_ident(name))),
+                    },
+                    None => mismatches.push(ColumnMismatch {
+                        column: name.to_string(),
+                        reason: "not found in the target schema".to_string(),
+                    }),
+                }
+            }
+            if !mismatches.is_empty() {
+                return Err(ErrorKind::IncompatibleTargetSchema {
+                    columns: mismatches,
+                });
+            }
+            values.join(", ")
+        }
+    };
+    let conflict = conflict_clause(options.on_conflict.as_ref(), &names);
+    let staging = quote_ident(&options.staging_table);
+    Ok(format!(
+        "INSERT INTO {table} ({}) SELECT {values} FROM pg_temp.{staging}{conflict};\nDROP TABLE pg_temp.{staging};",
+        column_list(&names)
+    ))
+}
+
+/// The statement inserting one row per element of the array parameters written by
+/// `ArrowToPostgresParamsEncoder`, one parameter per column of `schema`.
+/// `table` is used verbatim, like in `insert_statements`.
+pub fn unnest_insert_statement(
+    table: &str,
+    schema: &PostgresSchema,
+    on_conflict: Option<&OnConflict>,
+) -> Result<String, ErrorKind> {
+    let names = schema
+        .columns
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect::<Vec<_>>();
+    let params = schema
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, column)| match column.data_type.array_oid() {
+            Some(_) => Ok(format!(
+                "${}::{}[]",
+                i + 1,
+                column.data_type.name().unwrap()
+            )),
+            None => Err(ErrorKind::TypeOidUnknown {
+                field: column.name.clone(),
+                tp: column.data_type.clone(),
+            }),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(format!(
+        "INSERT INTO {table} ({}) SELECT * FROM UNNEST({}){}",
+        column_list(&names),
+        params.join(", "),
+        conflict_clause(on_conflict, &names)
+    ))
+}
+
+/// The statement that exports the columns of `schema` from `table` in the binary COPY format.
+/// `table` is used verbatim, like in `insert_statements`.
+pub fn copy_out_statement(table: &str, schema: &PostgresSchema) -> String {
+    let names = schema
+        .columns
+        .iter()
+        .map(|c| c.name.as_str())
+        .collect::<Vec<_>>();
+    format!(
+        "COPY {table} ({}) TO STDOUT WITH (FORMAT BINARY)",
+        column_list(&names)
+    )
+}
+
+/// The operations `copy_batches_blocking` and `copy_out_blocking` need from a blocking Postgres connection
+pub trait BlockingCopyClient {
+    /// Runs one or more statements that return no rows
+    fn batch_execute(&mut self, sql: &str) -> Result<(), ErrorKind>;
+    /// Runs a `COPY ... FROM STDIN` statement whose data is written by `write`
+    /// and returns the number of rows copied.
+    /// The COPY must be aborted if `write` fails.
+    fn copy_in<F>(&mut self, sql: &str, write: F) -> Result<u64, ErrorKind>
+    where
+        F: FnOnce(&mut dyn Write) -> Result<(), ErrorKind>;
+    /// Runs a `COPY ... TO STDOUT` statement, writes its data to `out`
+    /// and returns the number of bytes written
+    fn copy_out(&mut self, sql: &str, out: &mut dyn Write) -> Result<u64, ErrorKind>;
+}
+
+/// The blocking counterpart of `copy_batches`
+pub fn copy_batches_blocking<C, I>(
+    client: &mut C,
+    table: &str,
+    batches: I,
+    options: &CopyOptions,
+) -> Result<u64, ErrorKind>
+where
+    C: BlockingCopyClient,
+    I: IntoIterator<Item = RecordBatch>,
+{
+    load_blocking(client, table, batches, options, None)
+}
+
+/// Like `copy_batches_blocking`, but holds at most `memory_limit` encoded bytes in memory
+/// at once, see `CopyWriter::with_memory_limit`
+pub fn copy_batches_blocking_with_memory_limit<C, I>(
+    client: &mut C,
+    table: &str,
+    batches: I,
+    options: &CopyOptions,
+    memory_limit: usize,
+) -> Result<u64, ErrorKind>
+where
+    C: BlockingCopyClient,
+    I: IntoIterator<Item = RecordBatch>,
+{
+    load_blocking(client, table, batches, options, Some(memory_limit))
+}
+
+fn load_blocking<C, I>(
+    client: &mut C,
+    table: &str,
+    batches: I,
+    options: &CopyOptions,
+    memory_limit: Option<usize>,
+) -> Result<u64, ErrorKind>
+where
+    C: BlockingCopyClient,
+    I: IntoIterator<Item = RecordBatch>,
+{
+    let mut batches = batches.into_iter();
+    let first = match batches.next() {
+        Some(batch) => batch,
+        None => return Ok(0),
+    };
+    let encoder = load_encoder(&first.schema(), options)?;
+    let schema = encoder.schema();
+    // fail before touching the database if the target schema does not fit
+    let copy = copy_statement(&schema, options)?;
+    let insert = insert_statements(table, &schema, options)?;
+
+    client.batch_execute(&staging_statements(&schema, options))?;
+    let loaded = client.copy_in(&copy, |out| {
+        let mut writer =
+            CopyWriter::try_new(encoder, out)?.with_flush_threshold(options.chunk_size);
+        if let Some(memory_limit) = memory_limit {
+            writer = writer.with_memory_limit(memory_limit);
+        }
+        for batch in std::iter::once(first).chain(batches) {
+            writer.write_batch(&batch)?;
+        }
+        writer.finish()?;
+        Ok(())
+    });
+    let loaded = loaded.and_then(|rows| client.batch_execute(&insert).map(|()| rows));
+    if loaded.is_err() {
+        // the error that stopped the load matters more than one from the cleanup
+        let _ = client.batch_execute(&drop_staging_statement(options));
+    }
+    loaded
+}
+
+/// Exports the columns of `schema` from `table` and decodes them into a single batch.
+/// Values are checked against `schema`, so a NULL in a NOT NULL column is an error.
+/// Types without a dedicated decoder, such as `NUMERIC` and arrays,
+/// are returned as `Binary` columns holding the raw values, see [`decode_copy_binary`].
+pub fn copy_out_blocking<C: BlockingCopyClient>(
+    client: &mut C,
+    table: &str,
+    schema: &PostgresSchema,
+) -> Result<RecordBatch, ErrorKind> {
+    let mut data = vec![];
+    client.copy_out(&copy_out_statement(table, schema), &mut data)?;
+    decode_copy_binary(&data, schema)
+}
+
+#[cfg(feature = "postgres")]
+mod postgres_client {
+    use std::io::{self, Write};
+
+    use super::BlockingCopyClient;
+    use crate::error::ErrorKind;
+
+    fn client_error(e: postgres::Error) -> ErrorKind {
+        ErrorKind::Client(Box::new(e))
+    }
+
+    macro_rules! impl_blocking_copy_client {
+        ($client:ty) => {
+            impl BlockingCopyClient for $client {
+                fn batch_execute(&mut self, sql: &str) -> Result<(), ErrorKind> {
+                    <$client>::batch_execute(self, sql).map_err(client_error)
+                }
+
+                fn copy_in<F>(&mut self, sql: &str, write: F) -> Result<u64, ErrorKind>
+                where
+                    F: FnOnce(&mut dyn Write) -> Result<(), ErrorKind>,
+                {
+                    let mut writer = <$client>::copy_in(self, sql).map_err(client_error)?;
+                    // dropping the writer before `finish` aborts the COPY
+                    write(&mut writer)?;
+                    writer.finish().map_err(client_error)
+                }
+
+                fn copy_out(&mut self, sql: &str, out: &mut dyn Write) -> Result<u64, ErrorKind> {
+                    let mut reader = <$client>::copy_out(self, sql).map_err(client_error)?;
+                    Ok(io::copy(&mut reader, out)?)
+                }
+            }
+        };
+    }
+
+    impl_blocking_copy_client!(postgres::Client);
+    impl_blocking_copy_client!(postgres::Transaction<'_>);
+}
+
+#[cfg(feature = "tokio")]
+mod client {
+    use std::future::Future;
+
+    use arrow_array::RecordBatch;
+    use bytes::Bytes;
+    use futures_util::stream::{self, Stream, StreamExt};
+
+    use super::{
+        copy_statement, drop_staging_statement, insert_statements, load_encoder,
+        staging_statements, CopyOptions,
+    };
+    use crate::async_writer::encode_stream;
+    use crate::error::ErrorKind;
+    use crate::pg_schema::PostgresSchema;
+
+    /// The operations `copy_batches` needs from a Postgres connection
+    pub trait CopyClient {
+        /// Runs one or more statements that return no rows
+        fn batch_execute(
+            &mut self,
+            sql: &str,
+        ) -> impl Future<Output = Result<(), ErrorKind>> + Send;
+        /// Runs a `COPY ... FROM STDIN` statement fed by `chunks` and returns the number of rows copied.
+        /// The COPY must be aborted if `chunks` yields an error.
+        fn copy_in<S>(
+            &mut self,
+            sql: &str,
+            chunks: S,
+        ) -> impl Future<Output = Result<u64, ErrorKind>> + Send
+        where
+            S: Stream<Item = Result<Bytes, ErrorKind>> + Send + Unpin;
+    }
+
+    /// Loads `batches` into `table` by copying them into a temporary staging table
+    /// and inserting from there, casting and resolving conflicts as set in `options`.
+    /// The encoder is inferred from the first batch, or matched against `options.target` when set.
+    /// The staging table is dropped if the load fails. Returns the number of rows copied.
+    /// Struct columns are rejected before anything is sent, see `insert_statements`.
+    pub async fn copy_batches<C, S>(
+        client: &mut C,
+        table: &str,
+        batches: S,
+        options: &CopyOptions,
+    ) -> Result<u64, ErrorKind>
+    where
+        C: CopyClient,
+        S: Stream<Item = RecordBatch> + Send + Unpin,
+    {
+        let mut batches = batches;
+        let first = match batches.next().await {
+            Some(batch) => batch,
+            None => return Ok(0),
+        };
+        let encoder = load_encoder(&first.schema(), options)?;
+        let schema: PostgresSchema = encoder.schema();
+        // fail before touching the database if the target schema does not fit
+        let copy = copy_statement(&schema, options)?;
+        let insert = insert_statements(table, &schema, options)?;
+
+        client
+            .batch_execute(&staging_statements(&schema, options))
+            .await?;
+        let batches = stream::iter(Some(first)).chain(batches);
+        let chunks = encode_stream(encoder, batches, options.chunk_size);
+        let loaded = match client.copy_in(&copy, Box::pin(chunks)).await {
+            Ok(rows) => client.batch_execute(&insert).await.map(|()| rows),
+            Err(e) => Err(e),
+        };
+        if loaded.is_err() {
+            // the error that stopped the load matters more than one from the cleanup
+            let _ = client.batch_execute(&drop_staging_statement(options)).await;
+        }
+        loaded
+    }
+
+    #[cfg(feature = "tokio-postgres")]
+    mod tokio_postgres_client {
+        use bytes::Bytes;
+        use futures_util::{SinkExt, Stream, StreamExt};
+
+        use super::CopyClient;
+        use crate::error::ErrorKind;
+
+        fn client_error(e: tokio_postgres::Error) -> ErrorKind {
+            ErrorKind::Client(Box::new(e))
+        }
+
+        macro_rules! impl_copy_client {
+            ($client:ty) => {
+                impl CopyClient for $client {
+                    async fn batch_execute(&mut self, sql: &str) -> Result<(), ErrorKind> {
+                        <$client>::batch_execute(self, sql)
+                            .await
+                            .map_err(client_error)
+                    }
+
+                    async fn copy_in<S>(
+                        &mut self,
+                        sql: &str,
+                        mut chunks: S,
+                    ) -> Result<u64, ErrorKind>
+                    where
+                        S: Stream<Item = Result<Bytes, ErrorKind>> + Send + Unpin,
+                    {
+                        let sink = <$client>::copy_in::<_, Bytes>(self, sql)
+                            .await
+                            .map_err(client_error)?;
+                        futures_util::pin_mut!(sink);
+                        // dropping the sink before `finish` aborts the COPY
+                        while let Some(chunk) = chunks.next().await {
+                            sink.feed(chunk?).await.map_err(client_error)?;
+                        }
+                        sink.finish().await.map_err(client_error)
+                    }
+                }
+            };
+        }
+
+        impl_copy_client!(tokio_postgres::Client);
+        impl_copy_client!(tokio_postgres::Transaction<'_>);
+    }
+
+    #[cfg(feature = "sqlx")]
+    mod sqlx_client {
+        use bytes::Bytes;
+        use futures_util::{Stream, StreamExt};
+        use sqlx::PgConnection;
+
+        use super::CopyClient;
+        use crate::error::ErrorKind;
+
+        fn client_error(e: sqlx::Error) -> ErrorKind {
+            ErrorKind::Client(Box::new(e))
+        }
+
+        /// Pool connections and transactions deref to `PgConnection`, so pass `&mut *conn`
+        impl CopyClient for PgConnection {
+            async fn batch_execute(&mut self, sql: &str) -> Result<(), ErrorKind> {
+                // a plain `&str` runs through the simple query protocol, which allows several
+                // statements. Calling `Executor::execute` directly rather than `sqlx::raw_sql`
+                // keeps the future `Send`.
+                sqlx::Executor::execute(self, sql)
+                    .await
+                    .map_err(client_error)?;
+                Ok(())
+            }
+
+            async fn copy_in<S>(&mut self, sql: &str, mut chunks: S) -> Result<u64, ErrorKind>
+            where
+                S: Stream<Item = Result<Bytes, ErrorKind>> + Send + Unpin,
+            {
+                let mut copy = self.copy_in_raw(sql).await.map_err(client_error)?;
+                while let Some(chunk) = chunks.next().await {
+                    match chunk {
+                        Ok(chunk) => {
+                            copy.send(chunk).await.map_err(client_error)?;
+                        }
+                        Err(e) => {
+                            copy.abort(e.to_string()).await.map_err(client_error)?;
+                            return Err(e);
+                        }
+                    }
+                }
+                copy.finish().await.map_err(client_error)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+pub use client::{copy_batches, CopyClient};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pg_schema::Column;
+
+    fn schema() -> PostgresSchema {
+        PostgresSchema {
+            columns: vec![
+                Column {
+                    name: "id".to_string(),
+                    data_type: PostgresType::Int4,
+                    nullable: false,
+                },
+                Column {
+                    name: "name".to_string(),
+                    data_type: PostgresType::Text,
+                    nullable: true,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_load_statements() {
+        let options = CopyOptions::default().with_staging_table("stage");
+        assert_eq!(
+            staging_statements(&schema(), &options),
+            "DROP TABLE IF EXISTS pg_temp.\"stage\";\nCREATE TEMP TABLE \"stage\" (\"id\" INT4 NOT NULL, \"name\" TEXT);"
+        );
+        assert_eq!(
+            copy_statement(&schema(), &options).unwrap(),
+            "COPY \"stage\" (\"id\", \"name\") FROM STDIN WITH (FORMAT BINARY)"
+        );
+        assert_eq!(
+            insert_statements("public.t", &schema(), &options).unwrap(),
+            "INSERT INTO public.t (\"id\", \"name\") SELECT \"id\", \"name\" FROM pg_temp.\"stage\";\nDROP TABLE pg_temp.\"stage\";"
+        );
+
+        let mut target = schema();
+        target.columns[0].data_type = PostgresType::Int8;
+        let options = options
+            .with_target(target)
+            .with_on_conflict(OnConflict::DoUpdate {
+                columns: vec!["id".to_string()],
+            });
+        assert_eq!(
+            insert_statements("t", &schema(), &options).unwrap(),
+            "INSERT INTO t (\"id\", \"name\") SELECT \"id\"::INT8, \"name\"::TEXT FROM pg_temp.\"stage\" ON CONFLICT (\"id\") DO UPDATE SET \"name\" = EXCLUDED.\"name\";\nDROP TABLE pg_temp.\"stage\";"
+        );
+
+        let options = options.with_target(PostgresSchema {
+            columns: schema().columns[..1].to_vec(),
+        });
+        assert!(matches!(
+            insert_statements("t", &schema(), &options),
+            Err(ErrorKind::IncompatibleTargetSchema { columns }) if columns[0].column == "name"
+        ));
+
+        let point = Column {
+            name: "x".to_string(),
+            data_type: PostgresType::Float8,
+            nullable: true,
+        };
+        let mut composite = schema();
+        composite.columns[1].data_type = PostgresType::List(Box::new(Column {
+            name: "point".to_string(),
+            data_type: PostgresType::UserDefined {
+                fields: vec![Box::new(point)],
+            },
+            nullable: true,
+        }));
+        assert!(matches!(
+            insert_statements("t", &composite, &CopyOptions::default()),
+            Err(ErrorKind::IncompatibleTargetSchema { columns }) if columns.len() == 1 && columns[0].column == "name"
+        ));
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn test_copy_batches() {
+        use std::sync::Arc;
+
+        use arrow_array::{Int32Array, RecordBatch, StringArray};
+        use arrow_schema::{DataType, Field, Schema};
+        use bytes::{Bytes, BytesMut};
+        use futures_util::stream::{self, Stream, StreamExt};
+
+        use crate::ArrowToPostgresBinaryEncoder;
+
+        /// Records statements and the bytes of every COPY instead of talking to a server
+        #[derive(Default)]
+        struct MockClient {
+            statements: Vec<String>,
+            copied: Vec<u8>,
+            fail_insert: bool,
+        }
+
+        impl CopyClient for MockClient {
+            async fn batch_execute(&mut self, sql: &str) -> Result<(), ErrorKind> {
+                self.statements.push(sql.to_string());
+                if self.fail_insert && sql.starts_with("INSERT") {
+                    return Err(std::io::Error::other("unique violation").into());
+                }
+                Ok(())
+            }
+
+            async fn copy_in<S>(&mut self, sql: &str, mut chunks: S) -> Result<u64, ErrorKind>
+            where
+                S: Stream<Item = Result<Bytes, ErrorKind>> + Send + Unpin,
+            {
+                self.statements.push(sql.to_string());
+                while let Some(chunk) = chunks.next().await {
+                    self.copied.extend_from_slice(&chunk?);
+                }
+                Ok(3)
+            }
+        }
+
+        let arrow_schema = Arc::new(Schema::new(vec![
+            Field::new("id", DataType::Int32, false),
+            Field::new("name", DataType::Utf8, true),
+        ]));
+        let batch = |ids: Vec<i32>, names: Vec<Option<&str>>| {
+            RecordBatch::try_new(
+                arrow_schema.clone(),
+                vec![
+                    Arc::new(Int32Array::from(ids)),
+                    Arc::new(StringArray::from(names)),
+                ],
+            )
+            .unwrap()
+        };
+        let batches = vec![
+            batch(vec![1, 2], vec![Some("a"), None]),
+            batch(vec![3], vec![Some("c")]),
+        ];
+
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&arrow_schema).unwrap();
+        let mut expected = BytesMut::new();
+        encoder.write_header(&mut expected).unwrap();
+        for b in &batches {
+            encoder.write_batch(b, &mut expected).unwrap();
+        }
+        encoder.write_footer(&mut expected).unwrap();
+
+        let mut client = MockClient::default();
+        let options = CopyOptions::default()
+            .with_target(schema())
+            .with_on_conflict(OnConflict::DoNothing { columns: vec![] });
+        let rows = copy_batches(&mut client, "t", stream::iter(batches.clone()), &options)
+            .await
+            .unwrap();
+        assert_eq!(rows, 3);
+        assert_eq!(client.copied, expected.to_vec());
+        let statements = &client.statements;
+        assert_eq!(statements.len(), 3);
+        assert!(statements[1].starts_with("COPY \"_pgpq_staging\""));
+        assert!(statements[2].contains(" ON CONFLICT DO NOTHING;"));
+
+        // a failed INSERT drops the staging table
+        let mut client = MockClient {
+            fail_insert: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            copy_batches(&mut client, "t", stream::iter(batches), &options).await,
+            Err(ErrorKind::Io(_))
+        ));
+        assert_eq!(client.statements.len(), 4);
+        assert_eq!(
+            client.statements[3],
+            "DROP TABLE IF EXISTS pg_temp.\"_pgpq_staging\""
+        );
+    }
+
+    #[test]
+    fn test_copy_batches_blocking() {
+        use std::sync::Arc;
+
+        use arrow_array::Int32Array;
+        use arrow_schema::{DataType, Field, Schema};
+
+        /// Records statements and COPY data instead of talking to a server
+        #[derive(Default)]
+        struct MockClient {
+            statements: Vec<String>,
+            copied: Vec<u8>,
+            fail_copy: bool,
+        }
+
+        impl BlockingCopyClient for MockClient {
+            fn batch_execute(&mut self, sql: &str) -> Result<(), ErrorKind> {
+                self.statements.push(sql.to_string());
+                Ok(())
+            }
+
+            fn copy_in<F>(&mut self, sql: &str, write: F) -> Result<u64, ErrorKind>
+            where
+                F: FnOnce(&mut dyn Write) -> Result<(), ErrorKind>,
+            {
+                self.statements.push(sql.to_string());
+                if self.fail_copy {
+                    return Err(std::io::Error::other("connection reset").into());
+                }
+                write(&mut self.copied)?;
+                Ok(2)
+            }
+
+            fn copy_out(&mut self, sql: &str, out: &mut dyn Write) -> Result<u64, ErrorKind> {
+                self.statements.push(sql.to_string());
+                out.write_all(&self.copied)?;
+                Ok(self.copied.len() as u64)
+            }
+        }
+
+        let arrow_schema = Arc::new(Schema::new(vec![Field::new("id", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(
+            arrow_schema.clone(),
+            vec![Arc::new(Int32Array::from(vec![1, 2]))],
+        )
+        .unwrap();
+
+        let mut client = MockClient::default();
+        let rows = copy_batches_blocking(
+            &mut client,
+            "t",
+            vec![batch.clone()],
+            &CopyOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(rows, 2);
+        assert_eq!(client.statements.len(), 3);
+        assert!(client.statements[2].starts_with("INSERT INTO t (\"id\")"));
+
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&arrow_schema).unwrap();
+        let mut expected = bytes::BytesMut::new();
+        encoder.write_header(&mut expected).unwrap();
+        encoder.write_batch(&batch, &mut expected).unwrap();
+        encoder.write_footer(&mut expected).unwrap();
+        assert_eq!(client.copied, expected.to_vec());
+
+        let exported = copy_out_blocking(&mut client, "t", &encoder.schema()).unwrap();
+        assert_eq!(exported, batch);
+        assert_eq!(
+            client.statements[3],
+            "COPY t (\"id\") TO STDOUT WITH (FORMAT BINARY)"
+        );
+
+        // the staging table is created with the target types
+        let target = PostgresSchema {
+            columns: vec![Column {
+                name: "id".to_string(),
+                data_type: PostgresType::Int8,
+                nullable: false,
+            }],
+        };
+        let options = CopyOptions::default().with_target(target);
+        let mut client = MockClient::default();
+        copy_batches_blocking(&mut client, "t", vec![batch.clone()], &options).unwrap();
+        assert!(client.statements[0].ends_with("(\"id\" INT8 NOT NULL);"));
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new_for_target(
+            &arrow_schema,
+            options.target.as_ref().unwrap(),
+        )
+        .unwrap();
+        let mut expected = bytes::BytesMut::new();
+        encoder.write_header(&mut expected).unwrap();
+        encoder.write_batch(&batch, &mut expected).unwrap();
+        encoder.write_footer(&mut expected).unwrap();
+        assert_eq!(client.copied, expected.to_vec());
+
+        // a failed COPY drops the staging table
+        let mut client = MockClient {
+            fail_copy: true,
+            ..Default::default()
+        };
+        assert!(matches!(
+            copy_batches_blocking(&mut client, "t", vec![batch.clone()], &options),
+            Err(ErrorKind::Io(_))
+        ));
+        assert_eq!(client.statements.len(), 3);
+        assert_eq!(
+            client.statements[2],
+            "DROP TABLE IF EXISTS pg_temp.\"_pgpq_staging\""
+        );
+
+        // a row larger than the memory limit fails the load
+        let mut client = MockClient::default();
+        let err = copy_batches_blocking_with_memory_limit(
+            &mut client,
+            "t",
+            vec![batch],
+            &CopyOptions::default(),
+            5,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            err.root_cause(),
+            ErrorKind::MemoryLimitExceeded { limit: 5, .. }
+        ));
+        assert_eq!(client.statements.len(), 3);
+    }
+}
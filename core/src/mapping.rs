@@ -0,0 +1,139 @@
+use std::fmt;
+use std::sync::Arc;
+
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Schema};
+
+use crate::encoders::EncoderBuilder;
+use crate::error::ErrorKind;
+
+/// Computes the value of a per-batch column as an array holding a single value
+pub type BatchValueFn = Arc<dyn Fn(&RecordBatch) -> Result<ArrayRef, ErrorKind> + Send + Sync>;
+
+/// Where the values of a column come from
+#[derive(Clone)]
+pub enum ColumnSource {
+    /// The Arrow field with this name
+    Field(String),
+    /// The single value in this array, repeated for every row
+    Constant(ArrayRef),
+    /// A single value computed for each batch, e.g. the time the batch was loaded
+    PerBatch {
+        data_type: DataType,
+        value: BatchValueFn,
+    },
+    /// A sequence numbering rows across batches, starting at `start`
+    RowNumber { start: i64 },
+}
+
+impl fmt::Debug for ColumnSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ColumnSource::Field(name) => f.debug_tuple("Field").field(name).finish(),
+            ColumnSource::Constant(value) => f.debug_tuple("Constant").field(value).finish(),
+            ColumnSource::PerBatch { data_type, .. } => f
+                .debug_struct("PerBatch")
+                .field("data_type", data_type)
+                .finish_non_exhaustive(),
+            ColumnSource::RowNumber { start } => {
+                f.debug_struct("RowNumber").field("start", start).finish()
+            }
+        }
+    }
+}
+
+/// A column written by the encoder
+#[derive(Debug, Clone)]
+pub struct MappedColumn {
+    pub source: ColumnSource,
+    pub name: String,
+    pub encoder: Option<EncoderBuilder>,
+}
+
+impl MappedColumn {
+    /// Writes the Arrow field named `source`
+    pub fn new(source: &str) -> Self {
+        Self {
+            source: ColumnSource::Field(source.to_string()),
+            name: source.to_string(),
+            encoder: None,
+        }
+    }
+    /// Writes the only value in `value` for every row
+    pub fn constant(name: &str, value: ArrayRef) -> Self {
+        Self {
+            source: ColumnSource::Constant(value),
+            name: name.to_string(),
+            encoder: None,
+        }
+    }
+    /// Writes the value returned by `value` for every row of a batch
+    pub fn per_batch(name: &str, data_type: DataType, value: BatchValueFn) -> Self {
+        Self {
+            source: ColumnSource::PerBatch { data_type, value },
+            name: name.to_string(),
+            encoder: None,
+        }
+    }
+    /// Writes a row number as `INT8`, counting from `start` across batches
+    pub fn row_number(name: &str, start: i64) -> Self {
+        Self {
+            source: ColumnSource::RowNumber { start },
+            name: name.to_string(),
+            encoder: None,
+        }
+    }
+    /// Writes the column under a different name
+    pub fn renamed(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+    /// Uses `encoder` instead of inferring one from the Arrow field
+    pub fn with_encoder(mut self, encoder: EncoderBuilder) -> Self {
+        self.encoder = Some(encoder);
+        self
+    }
+}
+
+/// Selects, orders and renames the Arrow fields written to Postgres and
+/// adds virtual columns that are not part of the batches.
+/// Fields that are not part of the mapping are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct ColumnMapping {
+    pub columns: Vec<MappedColumn>,
+}
+
+impl ColumnMapping {
+    pub fn new(columns: Vec<MappedColumn>) -> Self {
+        Self { columns }
+    }
+    /// Writes every field of `schema` in order
+    pub fn from_schema(schema: &Schema) -> Self {
+        Self::select(schema.fields().iter().map(|f| f.name().as_str()))
+    }
+    /// Writes the given fields in the given order, keeping their names
+    pub fn select<'a>(sources: impl IntoIterator<Item = &'a str>) -> Self {
+        Self::new(sources.into_iter().map(MappedColumn::new).collect())
+    }
+    pub fn column(mut self, source: &str) -> Self {
+        self.columns.push(MappedColumn::new(source));
+        self
+    }
+    pub fn rename(mut self, source: &str, name: &str) -> Self {
+        self.columns.push(MappedColumn::new(source).renamed(name));
+        self
+    }
+    pub fn constant(mut self, name: &str, value: ArrayRef) -> Self {
+        self.columns.push(MappedColumn::constant(name, value));
+        self
+    }
+    pub fn per_batch(mut self, name: &str, data_type: DataType, value: BatchValueFn) -> Self {
+        self.columns
+            .push(MappedColumn::per_batch(name, data_type, value));
+        self
+    }
+    pub fn row_number(mut self, name: &str, start: i64) -> Self {
+        self.columns.push(MappedColumn::row_number(name, start));
+        self
+    }
+}
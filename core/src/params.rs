@@ -0,0 +1,253 @@
+//! Binary array bind parameters for loads without COPY. Like the text and CSV encoders,
+//! they wrap an `ArrowToPostgresBinaryEncoder` and use its columns, encoders and error policy.
+
+use arrow_array::RecordBatch;
+use arrow_schema::Schema;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::error::ErrorKind;
+use crate::load::{unnest_insert_statement, OnConflict};
+use crate::pg_schema::PostgresSchema;
+use crate::ArrowToPostgresBinaryEncoder;
+
+/// One column of a batch as a binary Postgres array, ready to be bound to a prepared statement
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrayParam {
+    /// The OID of the array type, to declare as the parameter's type
+    pub type_oid: u32,
+    /// The array in the binary format, to send with format code 1
+    pub value: Bytes,
+}
+
+#[derive(Debug)]
+struct ArrayColumn {
+    element_oid: u32,
+    type_oid: u32,
+    elements: BytesMut,
+    has_null: bool,
+}
+
+/// Encodes each column of a batch as one binary array parameter, for
+/// `INSERT INTO t SELECT * FROM UNNEST($1::INT4[], $2::TEXT[], ...)` loads.
+/// This avoids a COPY for small batches and allows `ON CONFLICT`.
+#[derive(Debug)]
+pub struct ArrowToPostgresParamsEncoder {
+    encoder: ArrowToPostgresBinaryEncoder,
+    columns: Vec<ArrayColumn>,
+    binary: BytesMut,
+}
+
+impl ArrowToPostgresParamsEncoder {
+    pub fn try_new(schema: &Schema) -> Result<Self, ErrorKind> {
+        Self::try_from_binary(ArrowToPostgresBinaryEncoder::try_new(schema)?)
+    }
+
+    /// Fails with `ErrorKind::TypeOidUnknown` for lists and structs,
+    /// since Postgres has no arrays of arrays
+    pub fn try_from_binary(mut encoder: ArrowToPostgresBinaryEncoder) -> Result<Self, ErrorKind> {
+        let columns = encoder
+            .schema()
+            .columns
+            .into_iter()
+            .map(|column| {
+                let oids = column.data_type.oid().zip(column.data_type.array_oid());
+                match oids {
+                    Some((element_oid, type_oid)) => Ok(ArrayColumn {
+                        element_oid,
+                        type_oid,
+                        elements: BytesMut::new(),
+                        has_null: false,
+                    }),
+                    None => Err(ErrorKind::TypeOidUnknown {
+                        field: column.name,
+                        tp: column.data_type,
+                    }),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        // parameters have no header, but the encoder only accepts batches after one
+        let mut binary = BytesMut::new();
+        encoder.write_header(&mut binary)?;
+        Ok(Self {
+            encoder,
+            columns,
+            binary,
+        })
+    }
+
+    pub fn encoder(&self) -> &ArrowToPostgresBinaryEncoder {
+        &self.encoder
+    }
+
+    pub fn encoder_mut(&mut self) -> &mut ArrowToPostgresBinaryEncoder {
+        &mut self.encoder
+    }
+
+    pub fn schema(&self) -> PostgresSchema {
+        self.encoder.schema()
+    }
+
+    /// The statement the parameters are bound to; see `unnest_insert_statement`
+    pub fn insert_statement(
+        &self,
+        table: &str,
+        on_conflict: Option<&OnConflict>,
+    ) -> Result<String, ErrorKind> {
+        unnest_insert_statement(table, &self.schema(), on_conflict)
+    }
+
+    /// Encodes `batch` as one array per column, in the order of `schema()`
+    pub fn encode_batch(&mut self, batch: &RecordBatch) -> Result<Vec<ArrayParam>, ErrorKind> {
+        self.binary.clear();
+        self.encoder.write_batch(batch, &mut self.binary)?;
+        for column in self.columns.iter_mut() {
+            column.elements.clear();
+            column.has_null = false;
+        }
+        let mut buf = &self.binary[..];
+        let mut n_rows = 0;
+        while buf.has_remaining() {
+            let n_fields = buf.get_i16() as usize;
+            debug_assert_eq!(n_fields, self.columns.len());
+            for column in self.columns.iter_mut() {
+                // each field is already an array element: a length prefix and the value
+                let len = buf.get_i32();
+                column.elements.put_i32(len);
+                if len < 0 {
+                    column.has_null = true;
+                    continue;
+                }
+                column.elements.put_slice(&buf[..len as usize]);
+                buf.advance(len as usize);
+            }
+            n_rows += 1;
+        }
+        let params = self
+            .columns
+            .iter()
+            .map(|column| {
+                let mut value = BytesMut::with_capacity(20 + column.elements.len());
+                if n_rows == 0 {
+                    // an empty array has no dimensions
+                    value.put_i32(0);
+                    value.put_i32(0);
+                    value.put_u32(column.element_oid);
+                } else {
+                    value.put_i32(1);
+                    value.put_i32(column.has_null as i32);
+                    value.put_u32(column.element_oid);
+                    value.put_i32(n_rows);
+                    value.put_i32(1);
+                    value.put_slice(&column.elements);
+                }
+                ArrayParam {
+                    type_oid: column.type_oid,
+                    value: value.freeze(),
+                }
+            })
+            .collect();
+        Ok(params)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    use arrow_array::builder::{ListBuilder, StringBuilder};
+    use arrow_array::{ArrayRef, Int32Array, StringArray};
+    use arrow_schema::{DataType, Field};
+
+    use super::*;
+    use crate::encoders::{EncoderBuilder, StringEncoderBuilder};
+    use crate::load::OnConflict;
+    use crate::pg_schema::PostgresType;
+
+    #[test]
+    fn test_encode_batch() {
+        let columns: Vec<(&str, ArrayRef)> = vec![
+            ("id", Arc::new(Int32Array::from(vec![1, 2]))),
+            ("name", Arc::new(StringArray::from(vec![Some("a"), None]))),
+        ];
+        let batch = RecordBatch::try_from_iter(columns).unwrap();
+        let mut encoder = ArrowToPostgresParamsEncoder::try_new(&batch.schema()).unwrap();
+        let params = encoder.encode_batch(&batch).unwrap();
+
+        let mut ids = BytesMut::new();
+        for v in [1, 0, 23, 2, 1, 4, 1, 4, 2] {
+            ids.put_i32(v);
+        }
+        let mut names = BytesMut::new();
+        for v in [1, 1, 25, 2, 1, 1] {
+            names.put_i32(v);
+        }
+        names.put_u8(b'a');
+        names.put_i32(-1);
+        assert_eq!(
+            params,
+            vec![
+                ArrayParam {
+                    type_oid: 1007,
+                    value: ids.freeze(),
+                },
+                ArrayParam {
+                    type_oid: 1009,
+                    value: names.freeze(),
+                },
+            ]
+        );
+
+        let params = encoder.encode_batch(&batch.slice(0, 0)).unwrap();
+        assert_eq!(
+            params[0].value.to_vec(),
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 23]
+        );
+
+        assert_eq!(
+            encoder
+                .insert_statement(
+                    "public.t",
+                    Some(&OnConflict::DoUpdate {
+                        columns: vec!["id".to_string()]
+                    })
+                )
+                .unwrap(),
+            r#"INSERT INTO public.t ("id", "name") SELECT * FROM UNNEST($1::INT4[], $2::TEXT[]) ON CONFLICT ("id") DO UPDATE SET "name" = EXCLUDED."name""#
+        );
+    }
+
+    #[test]
+    fn test_json_array_oids() {
+        let field = Arc::new(Field::new("doc", DataType::Utf8, false));
+        let builder = EncoderBuilder::String(
+            StringEncoderBuilder::new_with_output(field.clone(), PostgresType::Json).unwrap(),
+        );
+        let schema = Schema::new(vec![field]);
+        let encoders = HashMap::from([("doc".to_string(), builder)]);
+        let binary =
+            ArrowToPostgresBinaryEncoder::try_new_with_encoders(&schema, &encoders).unwrap();
+        let mut encoder = ArrowToPostgresParamsEncoder::try_from_binary(binary).unwrap();
+        let batch = RecordBatch::try_new(
+            Arc::new(schema),
+            vec![Arc::new(StringArray::from(vec!["{}"]))],
+        )
+        .unwrap();
+        let params = encoder.encode_batch(&batch).unwrap();
+        // json[] holding json elements, not jsonb
+        assert_eq!(params[0].type_oid, 199);
+        assert_eq!(params[0].value[8..12], 114_u32.to_be_bytes());
+    }
+
+    #[test]
+    fn test_nested_columns_are_rejected() {
+        let mut tags = ListBuilder::new(StringBuilder::new());
+        tags.append(true);
+        let columns: Vec<(&str, ArrayRef)> = vec![("tags", Arc::new(tags.finish()))];
+        let batch = RecordBatch::try_from_iter(columns).unwrap();
+        assert!(matches!(
+            ArrowToPostgresParamsEncoder::try_new(&batch.schema()),
+            Err(ErrorKind::TypeOidUnknown { field, .. }) if field == "tags"
+        ));
+    }
+}
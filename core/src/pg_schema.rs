@@ -1,3 +1,5 @@
+use crate::error::ErrorKind;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum TypeSize {
     Fixed(usize),
@@ -17,10 +19,23 @@ pub enum PostgresType {
     Jsonb,
     Float4,
     Float8,
+    Numeric,
+    Uuid,
     Date,
     Time,
     Timestamp,
     Interval,
+    Point,
+    Lseg,
+    Box,
+    Circle,
+    Path,
+    Polygon,
+    Money,
+    Oid,
+    Regclass,
+    Xml,
+    Tsvector,
     List(Box<Column>),
     UserDefined { fields: Vec<Box<Column>> }, // User-defined type, e.g. a struct
 }
@@ -39,10 +54,23 @@ impl PostgresType {
             PostgresType::Jsonb => TypeSize::Variable,
             PostgresType::Float4 => TypeSize::Fixed(4),
             PostgresType::Float8 => TypeSize::Fixed(8),
+            PostgresType::Numeric => TypeSize::Variable,
+            PostgresType::Uuid => TypeSize::Fixed(16),
             PostgresType::Date => TypeSize::Fixed(4),
             PostgresType::Time => TypeSize::Fixed(8),
             PostgresType::Timestamp => TypeSize::Fixed(8),
             PostgresType::Interval => TypeSize::Fixed(16),
+            PostgresType::Point => TypeSize::Fixed(16),
+            PostgresType::Lseg => TypeSize::Fixed(32),
+            PostgresType::Box => TypeSize::Fixed(32),
+            PostgresType::Circle => TypeSize::Fixed(24),
+            PostgresType::Path => TypeSize::Variable,
+            PostgresType::Polygon => TypeSize::Variable,
+            PostgresType::Money => TypeSize::Fixed(8),
+            PostgresType::Oid => TypeSize::Fixed(4),
+            PostgresType::Regclass => TypeSize::Fixed(4),
+            PostgresType::Xml => TypeSize::Variable,
+            PostgresType::Tsvector => TypeSize::Variable,
             PostgresType::List(_) => TypeSize::Variable,
             PostgresType::UserDefined { .. } => TypeSize::Variable,
         }
@@ -56,18 +84,67 @@ impl PostgresType {
             PostgresType::Int4 => Some(23),
             PostgresType::Char => Some(18),
             PostgresType::Text => Some(25),
-            PostgresType::Json => Some(3802),
+            PostgresType::Json => Some(114),
             PostgresType::Jsonb => Some(3802),
             PostgresType::Float4 => Some(700),
             PostgresType::Float8 => Some(701),
+            PostgresType::Numeric => Some(1700),
+            PostgresType::Uuid => Some(2950),
             PostgresType::Date => Some(1082),
             PostgresType::Time => Some(1083),
             PostgresType::Timestamp => Some(1114),
             PostgresType::Interval => Some(1186),
+            PostgresType::Point => Some(600),
+            PostgresType::Lseg => Some(601),
+            PostgresType::Path => Some(602),
+            PostgresType::Box => Some(603),
+            PostgresType::Polygon => Some(604),
+            PostgresType::Circle => Some(718),
+            PostgresType::Money => Some(790),
+            PostgresType::Oid => Some(26),
+            PostgresType::Regclass => Some(2205),
+            PostgresType::Xml => Some(142),
+            PostgresType::Tsvector => Some(3614),
             PostgresType::List(_) => None,
             PostgresType::UserDefined { .. } => Some(16385), // arbitrary dummy oid
         }
     }
+    /// The OID of the one-dimensional array type whose elements have this type
+    pub fn array_oid(&self) -> Option<u32> {
+        match &self {
+            PostgresType::Bool => Some(1000),
+            PostgresType::Bytea => Some(1001),
+            PostgresType::Int8 => Some(1016),
+            PostgresType::Int2 => Some(1005),
+            PostgresType::Int4 => Some(1007),
+            PostgresType::Char => Some(1002),
+            PostgresType::Text => Some(1009),
+            PostgresType::Json => Some(199),
+            PostgresType::Jsonb => Some(3807),
+            PostgresType::Float4 => Some(1021),
+            PostgresType::Float8 => Some(1022),
+            PostgresType::Numeric => Some(1231),
+            PostgresType::Uuid => Some(2951),
+            PostgresType::Date => Some(1182),
+            PostgresType::Time => Some(1183),
+            PostgresType::Timestamp => Some(1115),
+            PostgresType::Interval => Some(1187),
+            PostgresType::Point => Some(1017),
+            PostgresType::Lseg => Some(1018),
+            PostgresType::Path => Some(1019),
+            PostgresType::Box => Some(1020),
+            PostgresType::Polygon => Some(1027),
+            PostgresType::Circle => Some(719),
+            PostgresType::Money => Some(791),
+            PostgresType::Oid => Some(1028),
+            PostgresType::Regclass => Some(2210),
+            PostgresType::Xml => Some(143),
+            PostgresType::Tsvector => Some(3643),
+            // multidimensional and composite arrays have no fixed OID
+            PostgresType::List(_) => None,
+            PostgresType::UserDefined { .. } => None,
+        }
+    }
     pub fn name(&self) -> Option<String> {
         let v = match &self {
             PostgresType::Bool => "BOOL".to_string(),
@@ -81,10 +158,23 @@ impl PostgresType {
             PostgresType::Jsonb => "JSONB".to_string(),
             PostgresType::Float4 => "FLOAT4".to_string(),
             PostgresType::Float8 => "FLOAT8".to_string(),
+            PostgresType::Numeric => "NUMERIC".to_string(),
+            PostgresType::Uuid => "UUID".to_string(),
             PostgresType::Date => "DATE".to_string(),
             PostgresType::Time => "TIME".to_string(),
             PostgresType::Timestamp => "TIMESTAMP".to_string(),
             PostgresType::Interval => "INTERVAL".to_string(),
+            PostgresType::Point => "POINT".to_string(),
+            PostgresType::Lseg => "LSEG".to_string(),
+            PostgresType::Box => "BOX".to_string(),
+            PostgresType::Circle => "CIRCLE".to_string(),
+            PostgresType::Path => "PATH".to_string(),
+            PostgresType::Polygon => "POLYGON".to_string(),
+            PostgresType::Money => "MONEY".to_string(),
+            PostgresType::Oid => "OID".to_string(),
+            PostgresType::Regclass => "REGCLASS".to_string(),
+            PostgresType::Xml => "XML".to_string(),
+            PostgresType::Tsvector => "TSVECTOR".to_string(),
             PostgresType::List(inner) => {
                 // arrays of structs and such are not supported
                 let inner_tp = inner.data_type.name().unwrap();
@@ -96,6 +186,69 @@ impl PostgresType {
     }
 }
 
+/// The data format of a COPY, which must match the encoder writing the data
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CopyFormat {
+    /// `ArrowToPostgresBinaryEncoder`
+    #[default]
+    Binary,
+    /// `ArrowToPostgresTextEncoder`
+    Text,
+    /// `ArrowToPostgresCsvEncoder`
+    Csv,
+}
+
+/// What the server does with rows it cannot parse (Postgres 17+)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnError {
+    Stop,
+    Ignore,
+}
+
+/// Options for `PostgresSchema::copy_statement`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CopyStatementOptions {
+    pub format: CopyFormat,
+    /// Whether the data starts with a header line; only valid for CSV
+    pub header: bool,
+    /// Freeze the rows as they are loaded; the table must be created or truncated
+    /// in the same transaction
+    pub freeze: bool,
+    pub on_error: Option<OnError>,
+    /// The encoding of text and CSV data. The encoders always write UTF-8, so only `UTF8`
+    /// (or an alias such as `utf-8`) is accepted; it makes the server ignore a different
+    /// `client_encoding`.
+    pub encoding: Option<String>,
+}
+
+impl CopyStatementOptions {
+    pub fn with_format(mut self, format: CopyFormat) -> Self {
+        self.format = format;
+        self
+    }
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+    pub fn with_freeze(mut self, freeze: bool) -> Self {
+        self.freeze = freeze;
+        self
+    }
+    pub fn with_on_error(mut self, on_error: OnError) -> Self {
+        self.on_error = Some(on_error);
+        self
+    }
+    pub fn with_encoding(mut self, encoding: &str) -> Self {
+        self.encoding = Some(encoding.to_string());
+        self
+    }
+}
+
+/// Quotes `name` as a Postgres identifier
+pub(crate) fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Column {
     pub name: String,
@@ -109,6 +262,9 @@ pub struct PostgresSchema {
 }
 
 impl PostgresSchema {
+    /// The statements creating a temporary table `table_name` with these columns,
+    /// and the types of its struct columns.
+    /// `table_name` is quoted as an identifier, like in `copy_statement`.
     pub fn ddl(&self, table_name: &str) -> String {
         // Collect user-defined type DDLs and a mapping from column path to type name
         fn collect_types(
@@ -198,9 +354,79 @@ impl PostgresSchema {
             .collect::<Vec<_>>()
             .join(", ");
 
-        ddl.push_str(&format!("CREATE TEMP TABLE \"{}\" ({});", table_name, cols));
+        ddl.push_str(&format!(
+            "CREATE TEMP TABLE {} ({});",
+            quote_ident(table_name),
+            cols
+        ));
         ddl
     }
+
+    /// The `COPY ... FROM STDIN` statement matching the encoder's column layout.
+    /// `table` is quoted as an identifier, like in `ddl`, so both name the same table.
+    pub fn copy_statement(
+        &self,
+        table: &str,
+        options: &CopyStatementOptions,
+    ) -> Result<String, ErrorKind> {
+        let invalid = |reason: &str| {
+            Err(ErrorKind::InvalidCopyOptions {
+                reason: reason.to_string(),
+            })
+        };
+        if options.header && options.format != CopyFormat::Csv {
+            return invalid("HEADER is only written by the CSV encoder");
+        }
+        if options.format == CopyFormat::Binary && options.on_error == Some(OnError::Ignore) {
+            return invalid("ON_ERROR IGNORE is not supported with FORMAT BINARY");
+        }
+        if let Some(encoding) = &options.encoding {
+            if options.format == CopyFormat::Binary {
+                return invalid("ENCODING is not supported with FORMAT BINARY");
+            }
+            // Postgres ignores case and punctuation in encoding names
+            let name = encoding
+                .chars()
+                .filter(char::is_ascii_alphanumeric)
+                .collect::<String>()
+                .to_ascii_lowercase();
+            if name != "utf8" && name != "unicode" {
+                return invalid(&format!(
+                    "ENCODING {encoding} is not supported, the text and CSV encoders always write UTF8"
+                ));
+            }
+        }
+        let columns = self
+            .columns
+            .iter()
+            .map(|c| quote_ident(&c.name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut with = vec![match options.format {
+            CopyFormat::Binary => "FORMAT BINARY".to_string(),
+            CopyFormat::Text => "FORMAT TEXT".to_string(),
+            CopyFormat::Csv => "FORMAT CSV".to_string(),
+        }];
+        if options.header {
+            with.push("HEADER".to_string());
+        }
+        if options.freeze {
+            with.push("FREEZE".to_string());
+        }
+        match options.on_error {
+            Some(OnError::Stop) => with.push("ON_ERROR STOP".to_string()),
+            Some(OnError::Ignore) => with.push("ON_ERROR IGNORE".to_string()),
+            None => {}
+        }
+        if options.encoding.is_some() {
+            with.push("ENCODING 'UTF8'".to_string());
+        }
+        Ok(format!(
+            "COPY {} ({columns}) FROM STDIN WITH ({})",
+            quote_ident(table),
+            with.join(", ")
+        ))
+    }
 }
 
 #[cfg(test)]
@@ -244,4 +470,66 @@ CREATE TEMP TABLE "test_table" ("id" INT4 NOT NULL, "data" data_t);"#;
 
         assert_eq!(ddl.trim(), expected_ddl.trim());
     }
+
+    #[test]
+    fn test_copy_statement() {
+        let schema = PostgresSchema {
+            columns: vec![
+                Column {
+                    name: "id".to_string(),
+                    data_type: PostgresType::Int4,
+                    nullable: false,
+                },
+                Column {
+                    name: "Odd \"name\"".to_string(),
+                    data_type: PostgresType::Text,
+                    nullable: true,
+                },
+            ],
+        };
+
+        // the table is quoted the same way as in the DDL
+        assert_eq!(
+            schema
+                .copy_statement("MyTable", &CopyStatementOptions::default())
+                .unwrap(),
+            r#"COPY "MyTable" ("id", "Odd ""name""") FROM STDIN WITH (FORMAT BINARY)"#
+        );
+        assert!(schema
+            .ddl("MyTable")
+            .starts_with(r#"CREATE TEMP TABLE "MyTable" ("#));
+
+        let options = CopyStatementOptions::default()
+            .with_format(CopyFormat::Csv)
+            .with_header(true)
+            .with_freeze(true)
+            .with_on_error(OnError::Ignore)
+            .with_encoding("utf-8");
+        assert_eq!(
+            schema.copy_statement("data", &options).unwrap(),
+            r#"COPY "data" ("id", "Odd ""name""") FROM STDIN WITH (FORMAT CSV, HEADER, FREEZE, ON_ERROR IGNORE, ENCODING 'UTF8')"#
+        );
+
+        // the encoders only write UTF-8, and binary data has no encoding
+        for options in [
+            options.clone().with_encoding("LATIN1"),
+            CopyStatementOptions::default().with_encoding("UTF8"),
+        ] {
+            assert!(matches!(
+                schema.copy_statement("data", &options),
+                Err(ErrorKind::InvalidCopyOptions { .. })
+            ));
+        }
+
+        let options = CopyStatementOptions::default().with_header(true);
+        assert!(matches!(
+            schema.copy_statement("data", &options),
+            Err(ErrorKind::InvalidCopyOptions { .. })
+        ));
+        let options = CopyStatementOptions::default().with_on_error(OnError::Ignore);
+        assert!(matches!(
+            schema.copy_statement("data", &options),
+            Err(ErrorKind::InvalidCopyOptions { .. })
+        ));
+    }
 }
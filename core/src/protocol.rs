@@ -0,0 +1,336 @@
+use std::io::{self, Write};
+
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::error::{ColumnMismatch, ErrorKind};
+use crate::pg_schema::PostgresSchema;
+use crate::writer::sink_failed;
+
+/// The largest payload put into a single `CopyData` message by default
+pub const DEFAULT_MAX_COPY_DATA_SIZE: usize = 64 * 1024;
+/// The largest payload whose message length still fits the protocol's i32
+pub const MAX_COPY_DATA_SIZE: usize = i32::MAX as usize - 4;
+
+const COPY_DATA: u8 = b'd';
+const COPY_DONE: u8 = b'c';
+const COPY_FAIL: u8 = b'f';
+const COPY_IN_RESPONSE: u8 = b'G';
+
+const BINARY_FORMAT: i16 = 1;
+
+/// Writes `data` as one or more `CopyData` messages holding at most `max_size` bytes each.
+/// `max_size` is capped at [`MAX_COPY_DATA_SIZE`].
+pub fn write_copy_data(data: &[u8], max_size: usize, out: &mut BytesMut) {
+    for chunk in data.chunks(max_size.clamp(1, MAX_COPY_DATA_SIZE)) {
+        out.reserve(5 + chunk.len());
+        out.put_u8(COPY_DATA);
+        // the length includes itself
+        out.put_i32((4 + chunk.len()) as i32);
+        out.put_slice(chunk);
+    }
+}
+
+/// Writes a `CopyDone` message, ending a successful COPY
+pub fn write_copy_done(out: &mut BytesMut) {
+    out.put_u8(COPY_DONE);
+    out.put_i32(4);
+}
+
+/// Writes a `CopyFail` message, aborting the COPY with `message` as the reason
+pub fn write_copy_fail(message: &str, out: &mut BytesMut) {
+    // the message is a C string so it cannot contain NUL bytes
+    let message = message.replace('\0', "");
+    out.put_u8(COPY_FAIL);
+    out.put_i32((4 + message.len() + 1) as i32);
+    out.put_slice(message.as_bytes());
+    out.put_u8(0);
+}
+
+/// Frames everything written to it into `CopyData` messages.
+/// Wrap the connection in it to feed a `CopyWriter` straight into the wire protocol.
+/// Once a write to the connection fails every later call fails too,
+/// since messages that were partly written cannot be resent.
+#[derive(Debug)]
+pub struct CopyDataWriter<W: Write> {
+    inner: W,
+    buf: BytesMut,
+    max_size: usize,
+    failed: bool,
+}
+
+impl<W: Write> CopyDataWriter<W> {
+    pub fn new(inner: W) -> Self {
+        Self {
+            inner,
+            buf: BytesMut::new(),
+            max_size: DEFAULT_MAX_COPY_DATA_SIZE,
+            failed: false,
+        }
+    }
+
+    /// Splits writes into messages holding at most `max_size` bytes,
+    /// capped at [`MAX_COPY_DATA_SIZE`]
+    pub fn with_max_size(mut self, max_size: usize) -> Self {
+        self.max_size = max_size.clamp(1, MAX_COPY_DATA_SIZE);
+        self
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Writes `CopyDone` and returns the connection
+    pub fn finish(mut self) -> Result<W, ErrorKind> {
+        write_copy_done(&mut self.buf);
+        self.write_buffered()?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Writes `CopyFail` with `message` as the reason and returns the connection
+    pub fn fail(mut self, message: &str) -> Result<W, ErrorKind> {
+        write_copy_fail(message, &mut self.buf);
+        self.write_buffered()?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_buffered(&mut self) -> io::Result<()> {
+        if self.failed {
+            self.buf.clear();
+            return Err(sink_failed());
+        }
+        let result = self.inner.write_all(&self.buf);
+        // messages that were partly written must not be sent again
+        self.buf.clear();
+        if result.is_err() {
+            self.failed = true;
+        }
+        result
+    }
+}
+
+impl<W: Write> Write for CopyDataWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        write_copy_data(data, self.max_size, &mut self.buf);
+        self.write_buffered()?;
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn protocol_error(reason: &str) -> ErrorKind {
+    ErrorKind::Protocol {
+        reason: reason.to_string(),
+    }
+}
+
+/// The server's answer to `COPY ... FROM STDIN`, listing the format it expects for each column
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopyInResponse {
+    /// 0 for text and 1 for binary
+    pub format: i8,
+    /// The format of each column, 0 for text and 1 for binary
+    pub column_formats: Vec<i16>,
+}
+
+impl CopyInResponse {
+    /// Parses a complete message, including the `G` tag and the length
+    pub fn parse(message: &[u8]) -> Result<Self, ErrorKind> {
+        let mut buf = message;
+        if buf.remaining() < 5 {
+            return Err(protocol_error("CopyInResponse is truncated"));
+        }
+        let tag = buf.get_u8();
+        if tag != COPY_IN_RESPONSE {
+            return Err(protocol_error(&format!(
+                "expected CopyInResponse ('G') but got message {:?}",
+                tag as char
+            )));
+        }
+        let len = buf.get_i32();
+        if len < 0 || len as usize != buf.remaining() + 4 {
+            return Err(protocol_error(&format!(
+                "CopyInResponse length {len} does not match the {} bytes received",
+                buf.remaining() + 4
+            )));
+        }
+        if buf.remaining() < 3 {
+            return Err(protocol_error("CopyInResponse is truncated"));
+        }
+        let format = buf.get_i8();
+        let n_columns = buf.get_i16();
+        if n_columns < 0 || buf.remaining() != n_columns as usize * 2 {
+            return Err(protocol_error(&format!(
+                "CopyInResponse declares {n_columns} columns but holds {} bytes of formats",
+                buf.remaining()
+            )));
+        }
+        let column_formats = (0..n_columns).map(|_| buf.get_i16()).collect();
+        Ok(Self {
+            format,
+            column_formats,
+        })
+    }
+
+    /// Checks that the server expects binary data with one column for each column of `schema`
+    pub fn check(&self, schema: &PostgresSchema) -> Result<(), ErrorKind> {
+        if self.format != BINARY_FORMAT as i8 {
+            return Err(protocol_error(
+                "the server expects text data; use COPY ... WITH (FORMAT BINARY)",
+            ));
+        }
+        if self.column_formats.len() != schema.columns.len() {
+            return Err(protocol_error(&format!(
+                "the server expects {} columns but the encoder writes {}",
+                self.column_formats.len(),
+                schema.columns.len()
+            )));
+        }
+        let mismatches = schema
+            .columns
+            .iter()
+            .zip(&self.column_formats)
+            .filter(|(_, format)| **format != BINARY_FORMAT)
+            .map(|(column, format)| ColumnMismatch {
+                column: column.name.clone(),
+                reason: format!("the server expects format {format} instead of binary"),
+            })
+            .collect::<Vec<_>>();
+        if !mismatches.is_empty() {
+            return Err(ErrorKind::IncompatibleTargetSchema {
+                columns: mismatches,
+            });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::{Int32Array, RecordBatch};
+    use arrow_schema::{DataType, Field, Schema};
+
+    use super::*;
+    use crate::writer::tests::FailingSink;
+    use crate::writer::CopyWriter;
+    use crate::ArrowToPostgresBinaryEncoder;
+
+    fn copy_in_response(format: i8, column_formats: &[i16]) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        buf.put_u8(b'G');
+        buf.put_i32(4 + 1 + 2 + 2 * column_formats.len() as i32);
+        buf.put_i8(format);
+        buf.put_i16(column_formats.len() as i16);
+        for f in column_formats {
+            buf.put_i16(*f);
+        }
+        buf.to_vec()
+    }
+
+    #[test]
+    fn test_copy_data_framing() {
+        let mut out = BytesMut::new();
+        write_copy_data(b"abcde", 2, &mut out);
+        write_copy_done(&mut out);
+        assert_eq!(
+            out.to_vec(),
+            b"d\0\0\0\x06abd\0\0\0\x06cdd\0\0\0\x05ec\0\0\0\x04".to_vec()
+        );
+
+        let mut out = BytesMut::new();
+        write_copy_fail("bad\0row", &mut out);
+        assert_eq!(out.to_vec(), b"f\0\0\0\x0bbadrow\0".to_vec());
+    }
+
+    #[test]
+    fn test_copy_data_writer() {
+        let schema = Arc::new(Schema::new(vec![Field::new("a", DataType::Int32, false)]));
+        let batch = RecordBatch::try_new(schema.clone(), vec![Arc::new(Int32Array::from(vec![1]))])
+            .unwrap();
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&schema).unwrap();
+        let mut expected = BytesMut::new();
+        encoder.write_header(&mut expected).unwrap();
+        encoder.write_batch(&batch, &mut expected).unwrap();
+        encoder.write_footer(&mut expected).unwrap();
+
+        let encoder = ArrowToPostgresBinaryEncoder::try_new(&schema).unwrap();
+        let framed = CopyDataWriter::new(vec![]).with_max_size(8);
+        let mut writer = CopyWriter::try_new(encoder, framed).unwrap();
+        writer.write_batch(&batch).unwrap();
+        let out = writer.finish().unwrap().finish().unwrap();
+
+        // strip the framing and compare the payloads
+        let mut payload = vec![];
+        let mut rest = &out[..];
+        while rest[0] == b'd' {
+            let len = i32::from_be_bytes(rest[1..5].try_into().unwrap()) as usize;
+            assert!(len - 4 <= 8);
+            payload.extend_from_slice(&rest[5..1 + len]);
+            rest = &rest[1 + len..];
+        }
+        assert_eq!(rest, b"c\0\0\0\x04");
+        assert_eq!(payload, expected.to_vec());
+
+        let writer = CopyDataWriter::new(vec![]).with_max_size(usize::MAX);
+        assert_eq!(writer.max_size, MAX_COPY_DATA_SIZE);
+    }
+
+    #[test]
+    fn test_copy_data_writer_sink_failure() {
+        let mut writer = CopyDataWriter::new(FailingSink::new(3));
+        assert!(writer.write(b"abc").is_err());
+        // the partly written message is not sent again
+        assert!(writer.write(b"de").is_err());
+        assert_eq!(writer.get_ref().data, b"d\0\0");
+        assert!(writer.finish().is_err());
+    }
+
+    #[test]
+    fn test_copy_in_response() {
+        let schema = ArrowToPostgresBinaryEncoder::try_new(&Schema::new(vec![
+            Field::new("a", DataType::Int32, false),
+            Field::new("b", DataType::Utf8, true),
+        ]))
+        .unwrap()
+        .schema();
+
+        let response = CopyInResponse::parse(&copy_in_response(1, &[1, 1])).unwrap();
+        assert_eq!(response.column_formats, vec![1, 1]);
+        response.check(&schema).unwrap();
+
+        let response = CopyInResponse::parse(&copy_in_response(1, &[1])).unwrap();
+        assert!(matches!(
+            response.check(&schema),
+            Err(ErrorKind::Protocol { .. })
+        ));
+
+        let response = CopyInResponse::parse(&copy_in_response(0, &[0, 0])).unwrap();
+        assert!(matches!(
+            response.check(&schema),
+            Err(ErrorKind::Protocol { .. })
+        ));
+
+        let response = CopyInResponse::parse(&copy_in_response(1, &[1, 0])).unwrap();
+        assert!(matches!(
+            response.check(&schema),
+            Err(ErrorKind::IncompatibleTargetSchema { columns }) if columns[0].column == "b"
+        ));
+
+        let mut truncated = copy_in_response(1, &[1, 1]);
+        truncated.pop();
+        assert!(matches!(
+            CopyInResponse::parse(&truncated),
+            Err(ErrorKind::Protocol { .. })
+        ));
+        assert!(matches!(
+            CopyInResponse::parse(b"Z\0\0\0\x05I"),
+            Err(ErrorKind::Protocol { .. })
+        ));
+    }
+}
@@ -0,0 +1,691 @@
+//! A minimal in-process Postgres server for testing clients against pgpq output
+//! without a real database.
+//! It speaks just enough of the simple and extended query protocols for `tokio-postgres`,
+//! `postgres` and `sqlx` to connect, run statements (which are recorded but not executed) and
+//! `COPY ... FROM STDIN WITH (FORMAT BINARY)`, whose data is validated against a
+//! declared `PostgresSchema` and decoded back to Arrow. Each of those clients is tested
+//! against it below.
+use std::collections::HashMap;
+use std::io::{self, Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use arrow_array::RecordBatch;
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::error::ErrorKind;
+use crate::pg_schema::PostgresSchema;
+
+pub use crate::decode::decode_copy_binary;
+
+const SSL_REQUEST: i32 = 80877103;
+const GSSENC_REQUEST: i32 = 80877104;
+// the limits Postgres itself applies
+const MAX_STARTUP_PACKET_SIZE: usize = 10_000;
+const MAX_MESSAGE_SIZE: usize = 1 << 30;
+
+fn invalid_data(reason: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+/// Checks the length of a message, which includes the length itself, and returns the body size
+fn body_size(len: i32, min: usize, max: usize) -> io::Result<usize> {
+    match usize::try_from(len) {
+        Ok(len) if (min..=max).contains(&len) => Ok(len - 4),
+        _ => Err(invalid_data(format!("invalid message length {len}"))),
+    }
+}
+
+/// What the server received
+#[derive(Debug, Default)]
+struct MockState {
+    statements: Vec<String>,
+    batches: Vec<RecordBatch>,
+    errors: Vec<String>,
+}
+
+/// A Postgres server listening on localhost that accepts binary COPY data matching `schema`.
+/// Every other statement succeeds without doing anything.
+/// The server stops when dropped, closing every open connection.
+#[derive(Debug)]
+pub struct MockCopyServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<MockState>>,
+    shutdown: Arc<AtomicBool>,
+    accept_thread: Option<JoinHandle<()>>,
+}
+
+impl MockCopyServer {
+    pub fn start(schema: PostgresSchema) -> Result<Self, ErrorKind> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(MockState::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+        let schema = Arc::new(schema);
+        let accept_thread = {
+            let state = state.clone();
+            let shutdown = shutdown.clone();
+            thread::spawn(move || {
+                let mut connections: Vec<(TcpStream, JoinHandle<()>)> = vec![];
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { break };
+                    let Ok(handle) = stream.try_clone() else {
+                        continue;
+                    };
+                    let state = state.clone();
+                    let schema = schema.clone();
+                    let thread = thread::spawn(move || {
+                        let mut connection = Connection {
+                            stream,
+                            schema: &schema,
+                            state: &state,
+                        };
+                        // the connection ends when the client goes away,
+                        // sends something malformed or the server stops
+                        let _ = connection.serve();
+                        // the accept loop holds a clone, so close the socket explicitly
+                        let _ = connection.stream.shutdown(Shutdown::Both);
+                    });
+                    connections.retain(|(_, thread)| !thread.is_finished());
+                    connections.push((handle, thread));
+                }
+                for (stream, thread) in connections {
+                    let _ = stream.shutdown(Shutdown::Both);
+                    let _ = thread.join();
+                }
+            })
+        };
+        Ok(Self {
+            addr,
+            state,
+            shutdown,
+            accept_thread: Some(accept_thread),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// A connection string for `tokio-postgres` and `postgres`
+    pub fn connection_string(&self) -> String {
+        format!(
+            "host={} port={} user=postgres dbname=postgres",
+            self.addr.ip(),
+            self.addr.port()
+        )
+    }
+
+    /// A connection URL for `sqlx`
+    pub fn url(&self) -> String {
+        format!("postgres://postgres@{}/postgres?sslmode=disable", self.addr)
+    }
+
+    /// Every statement received, in order
+    pub fn statements(&self) -> Vec<String> {
+        self.state.lock().unwrap().statements.clone()
+    }
+
+    /// One batch for every successful COPY, in order
+    pub fn batches(&self) -> Vec<RecordBatch> {
+        self.state.lock().unwrap().batches.clone()
+    }
+
+    /// The reason every rejected COPY failed, in order
+    pub fn errors(&self) -> Vec<String> {
+        self.state.lock().unwrap().errors.clone()
+    }
+}
+
+impl Drop for MockCopyServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // wake up the accept loop so it sees the flag
+        let _ = TcpStream::connect(self.addr);
+        if let Some(thread) = self.accept_thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn is_copy_in(query: &str) -> bool {
+    let query = query.trim_start().to_uppercase();
+    query.starts_with("COPY") && query.contains("FROM STDIN")
+}
+
+fn read_cstr(buf: &mut &[u8]) -> String {
+    let end = buf.iter().position(|b| *b == 0).unwrap_or(buf.len());
+    let s = String::from_utf8_lossy(&buf[..end]).into_owned();
+    buf.advance((end + 1).min(buf.len()));
+    s
+}
+
+struct Connection<'a> {
+    stream: TcpStream,
+    schema: &'a PostgresSchema,
+    state: &'a Mutex<MockState>,
+}
+
+impl Connection<'_> {
+    fn read_message(&mut self) -> io::Result<(u8, Vec<u8>)> {
+        let mut header = [0; 5];
+        self.stream.read_exact(&mut header)?;
+        let len = i32::from_be_bytes([header[1], header[2], header[3], header[4]]);
+        let mut body = vec![0; body_size(len, 4, MAX_MESSAGE_SIZE)?];
+        self.stream.read_exact(&mut body)?;
+        Ok((header[0], body))
+    }
+
+    fn send(&mut self, tag: u8, body: &[u8]) -> io::Result<()> {
+        let mut buf = BytesMut::with_capacity(5 + body.len());
+        buf.put_u8(tag);
+        buf.put_i32(4 + body.len() as i32);
+        buf.put_slice(body);
+        self.stream.write_all(&buf)
+    }
+
+    fn ready_for_query(&mut self) -> io::Result<()> {
+        self.send(b'Z', b"I")
+    }
+
+    fn command_complete(&mut self, tag: &str) -> io::Result<()> {
+        self.send(b'C', format!("{tag}\0").as_bytes())
+    }
+
+    fn error_response(&mut self, code: &str, message: &str) -> io::Result<()> {
+        let body = format!("SERROR\0VERROR\0C{code}\0M{message}\0\0");
+        self.send(b'E', body.as_bytes())
+    }
+
+    fn startup(&mut self) -> io::Result<()> {
+        loop {
+            let mut len = [0; 4];
+            self.stream.read_exact(&mut len)?;
+            // the body starts with the protocol version or request code
+            let size = body_size(i32::from_be_bytes(len), 8, MAX_STARTUP_PACKET_SIZE)?;
+            let mut body = vec![0; size];
+            self.stream.read_exact(&mut body)?;
+            let code = i32::from_be_bytes([body[0], body[1], body[2], body[3]]);
+            if code == SSL_REQUEST || code == GSSENC_REQUEST {
+                // no encryption
+                self.stream.write_all(b"N")?;
+                continue;
+            }
+            break;
+        }
+        // AuthenticationOk
+        self.send(b'R', &0i32.to_be_bytes())?;
+        for (name, value) in [
+            ("server_version", "14.0"),
+            ("server_encoding", "UTF8"),
+            ("client_encoding", "UTF8"),
+            ("DateStyle", "ISO, MDY"),
+            ("integer_datetimes", "on"),
+            ("standard_conforming_strings", "on"),
+        ] {
+            self.send(b'S', format!("{name}\0{value}\0").as_bytes())?;
+        }
+        // BackendKeyData
+        self.send(b'K', &[0, 0, 0, 1, 0, 0, 0, 1])?;
+        self.ready_for_query()
+    }
+
+    fn serve(&mut self) -> io::Result<()> {
+        self.startup()?;
+        let mut prepared: HashMap<String, String> = HashMap::new();
+        let mut portal: Option<String> = None;
+        // after an error in the extended protocol everything up to the next Sync is ignored
+        let mut skip_until_sync = false;
+        loop {
+            let (tag, body) = self.read_message()?;
+            let mut body = &body[..];
+            if skip_until_sync && tag != b'S' {
+                continue;
+            }
+            match tag {
+                // simple query, possibly holding several statements
+                b'Q' => {
+                    let query = read_cstr(&mut body);
+                    self.record_statement(&query);
+                    if is_copy_in(&query) {
+                        self.copy_in(&query)?;
+                    } else {
+                        for statement in query.split(';').filter(|s| !s.trim().is_empty()) {
+                            let command = statement.split_whitespace().next().unwrap_or("");
+                            self.command_complete(&command.to_uppercase())?;
+                        }
+                    }
+                    self.ready_for_query()?;
+                }
+                // Parse
+                b'P' => {
+                    let name = read_cstr(&mut body);
+                    let query = read_cstr(&mut body);
+                    prepared.insert(name, query);
+                    self.send(b'1', b"")?;
+                }
+                // Describe
+                b'D' => {
+                    if body.first() == Some(&b'S') {
+                        // ParameterDescription without parameters
+                        self.send(b't', &0i16.to_be_bytes())?;
+                    }
+                    // NoData
+                    self.send(b'n', b"")?;
+                }
+                // Bind
+                b'B' => {
+                    let _portal = read_cstr(&mut body);
+                    let statement = read_cstr(&mut body);
+                    portal = prepared.get(&statement).cloned();
+                    self.send(b'2', b"")?;
+                }
+                // Execute
+                b'E' => {
+                    let query = portal.take().unwrap_or_default();
+                    self.record_statement(&query);
+                    if is_copy_in(&query) {
+                        skip_until_sync = !self.copy_in(&query)?;
+                    } else {
+                        let command = query.split_whitespace().next().unwrap_or("");
+                        self.command_complete(&command.to_uppercase())?;
+                    }
+                }
+                // Sync
+                b'S' => {
+                    skip_until_sync = false;
+                    self.ready_for_query()?;
+                }
+                // Close
+                b'C' => self.send(b'3', b"")?,
+                // Flush
+                b'H' => {}
+                // Terminate
+                b'X' => return Ok(()),
+                other => {
+                    self.error_response(
+                        "0A000",
+                        &format!(
+                            "message {:?} is not supported by the mock server",
+                            other as char
+                        ),
+                    )?;
+                    skip_until_sync = true;
+                }
+            }
+        }
+    }
+
+    fn record_statement(&self, query: &str) {
+        self.state
+            .lock()
+            .unwrap()
+            .statements
+            .push(query.to_string());
+    }
+
+    fn record_error(&self, error: String) {
+        self.state.lock().unwrap().errors.push(error);
+    }
+
+    /// Runs a COPY and returns whether it succeeded
+    fn copy_in(&mut self, query: &str) -> io::Result<bool> {
+        if !query.to_uppercase().contains("BINARY") {
+            let message = "the mock server only accepts COPY ... WITH (FORMAT BINARY)";
+            self.record_error(message.to_string());
+            self.error_response("0A000", message)?;
+            return Ok(false);
+        }
+        let mut response = BytesMut::new();
+        response.put_i8(1);
+        response.put_i16(self.schema.columns.len() as i16);
+        for _ in &self.schema.columns {
+            response.put_i16(1);
+        }
+        self.send(b'G', &response)?;
+
+        let mut data = vec![];
+        loop {
+            let (tag, body) = self.read_message()?;
+            match tag {
+                b'd' => data.extend_from_slice(&body),
+                b'c' => break,
+                b'f' => {
+                    let reason = read_cstr(&mut &body[..]);
+                    let message = format!("COPY from stdin failed: {reason}");
+                    self.record_error(message.clone());
+                    self.error_response("57014", &message)?;
+                    return Ok(false);
+                }
+                // ignored while copying
+                b'S' | b'H' => {}
+                other => {
+                    let message = format!("unexpected message {:?} during COPY", other as char);
+                    self.record_error(message.clone());
+                    self.error_response("08P01", &message)?;
+                    return Ok(false);
+                }
+            }
+        }
+        match decode_copy_binary(&data, self.schema) {
+            Ok(batch) => {
+                let rows = batch.num_rows();
+                self.state.lock().unwrap().batches.push(batch);
+                self.command_complete(&format!("COPY {rows}"))?;
+                Ok(true)
+            }
+            Err(e) => {
+                let message = e.to_string();
+                self.record_error(message.clone());
+                // bad_copy_file_format
+                self.error_response("22P04", &message)?;
+                Ok(false)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::tests::{encode, make_batch};
+    use crate::protocol::{write_copy_data, write_copy_done};
+
+    fn read_message(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+        let mut header = [0; 5];
+        stream.read_exact(&mut header).unwrap();
+        let len = i32::from_be_bytes(header[1..].try_into().unwrap()) as usize;
+        let mut body = vec![0; len - 4];
+        stream.read_exact(&mut body).unwrap();
+        (header[0], body)
+    }
+
+    fn read_until_ready(stream: &mut TcpStream) -> Vec<u8> {
+        let mut tags = vec![];
+        loop {
+            let (tag, _) = read_message(stream);
+            tags.push(tag);
+            if tag == b'Z' {
+                return tags;
+            }
+        }
+    }
+
+    #[test]
+    fn test_mock_copy_server() {
+        let batch = make_batch();
+        let (schema, data) = encode(&batch);
+        let server = MockCopyServer::start(schema).unwrap();
+
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        let mut startup = BytesMut::new();
+        let params = b"user\0postgres\0\0";
+        startup.put_i32(8 + params.len() as i32);
+        startup.put_i32(196608);
+        startup.put_slice(params);
+        stream.write_all(&startup).unwrap();
+        assert_eq!(read_until_ready(&mut stream)[0], b'R');
+
+        let query = |sql: &str| {
+            let mut buf = BytesMut::new();
+            buf.put_u8(b'Q');
+            buf.put_i32(4 + sql.len() as i32 + 1);
+            buf.put_slice(sql.as_bytes());
+            buf.put_u8(0);
+            buf
+        };
+        stream
+            .write_all(&query("CREATE TEMP TABLE t (a INT4); SELECT 1"))
+            .unwrap();
+        assert_eq!(read_until_ready(&mut stream), b"CCZ".to_vec());
+
+        let copy = "COPY t FROM STDIN WITH (FORMAT BINARY)";
+        for (payload, expected) in [(&data[..], &b"CZ"[..]), (&data[..10], &b"EZ"[..])] {
+            stream.write_all(&query(copy)).unwrap();
+            let (tag, body) = read_message(&mut stream);
+            assert_eq!(tag, b'G');
+            assert_eq!(body.len(), 3 + 2 * 10);
+            let mut buf = BytesMut::new();
+            write_copy_data(payload, 16, &mut buf);
+            write_copy_done(&mut buf);
+            stream.write_all(&buf).unwrap();
+            assert_eq!(read_until_ready(&mut stream), expected.to_vec());
+        }
+
+        assert_eq!(server.batches(), vec![batch]);
+        assert_eq!(server.errors().len(), 1);
+        assert_eq!(server.statements().len(), 3);
+    }
+
+    fn message(tag: u8, body: &[u8]) -> BytesMut {
+        let mut buf = BytesMut::new();
+        buf.put_u8(tag);
+        buf.put_i32(4 + body.len() as i32);
+        buf.put_slice(body);
+        buf
+    }
+
+    fn connect(server: &MockCopyServer) -> TcpStream {
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        let params = b"user\0postgres\0\0";
+        let mut startup = BytesMut::new();
+        startup.put_i32(8 + params.len() as i32);
+        startup.put_i32(196608);
+        startup.put_slice(params);
+        stream.write_all(&startup).unwrap();
+        read_until_ready(&mut stream);
+        stream
+    }
+
+    #[test]
+    fn test_mock_copy_server_extended_protocol() {
+        let batch = make_batch();
+        let (schema, data) = encode(&batch);
+        let server = MockCopyServer::start(schema).unwrap();
+        let mut stream = connect(&server);
+
+        let copy = "COPY t FROM STDIN WITH (FORMAT BINARY)";
+        let mut prepare = message(b'P', format!("s\0{copy}\0\0\0").as_bytes());
+        prepare.extend_from_slice(&message(b'D', b"Ss\0"));
+        prepare.extend_from_slice(&message(b'S', b""));
+        stream.write_all(&prepare).unwrap();
+        assert_eq!(read_until_ready(&mut stream), b"1tnZ".to_vec());
+
+        let mut execute = message(b'B', b"\0s\0\0\0\0\0\0\0");
+        execute.extend_from_slice(&message(b'E', b"\0\0\0\0\0"));
+        for (payload, expected) in [(&data[..], &b"CZ"[..]), (&data[..10], &b"EZ"[..])] {
+            stream.write_all(&execute).unwrap();
+            assert_eq!(read_message(&mut stream).0, b'2');
+            assert_eq!(read_message(&mut stream).0, b'G');
+            let mut buf = BytesMut::new();
+            write_copy_data(payload, 16, &mut buf);
+            write_copy_done(&mut buf);
+            // after a failed COPY the messages up to the Sync are skipped
+            if expected == b"EZ" {
+                buf.extend_from_slice(&execute);
+            }
+            buf.extend_from_slice(&message(b'S', b""));
+            stream.write_all(&buf).unwrap();
+            assert_eq!(read_until_ready(&mut stream), expected.to_vec());
+        }
+
+        // an unsupported message fails and the rest up to the Sync is skipped
+        let mut buf = message(b'F', b"");
+        buf.extend_from_slice(&execute);
+        buf.extend_from_slice(&message(b'S', b""));
+        stream.write_all(&buf).unwrap();
+        assert_eq!(read_until_ready(&mut stream), b"EZ".to_vec());
+
+        assert_eq!(server.batches(), vec![batch]);
+        assert_eq!(server.errors().len(), 1);
+        assert_eq!(server.statements(), vec![copy; 2]);
+    }
+
+    /// Whether the server closed `stream`
+    fn is_closed(stream: &mut TcpStream) -> bool {
+        match stream.read(&mut [0; 1]) {
+            Ok(n) => n == 0,
+            Err(e) => e.kind() == io::ErrorKind::ConnectionReset,
+        }
+    }
+
+    #[test]
+    fn test_mock_copy_server_closes_connections() {
+        let (schema, _) = encode(&make_batch());
+        let server = MockCopyServer::start(schema).unwrap();
+
+        // a startup packet too short to hold the protocol version
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        stream.write_all(&[0, 0, 0, 5, 0]).unwrap();
+        assert!(is_closed(&mut stream));
+
+        // a message claiming to be longer than Postgres allows
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        let mut startup = BytesMut::new();
+        startup.put_i32(8);
+        startup.put_i32(196608);
+        startup.put_slice(b"Q\x7f\xff\xff\xff");
+        stream.write_all(&startup).unwrap();
+        assert_eq!(read_until_ready(&mut stream)[0], b'R');
+        assert!(is_closed(&mut stream));
+
+        // dropping the server closes connections that are still open
+        let mut stream = TcpStream::connect(server.addr()).unwrap();
+        drop(server);
+        assert!(is_closed(&mut stream));
+    }
+
+    #[cfg(feature = "postgres")]
+    #[test]
+    fn test_mock_copy_server_with_postgres_client() {
+        use crate::load::{copy_batches_blocking, CopyOptions};
+
+        let batch = make_batch();
+        let (schema, _) = encode(&batch);
+        let server = MockCopyServer::start(schema).unwrap();
+        let mut client =
+            postgres::Client::connect(&server.connection_string(), postgres::NoTls).unwrap();
+        let rows = copy_batches_blocking(
+            &mut client,
+            "t",
+            vec![batch.clone()],
+            &CopyOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(rows, 2);
+        assert_eq!(server.batches(), vec![batch.clone()]);
+
+        // a COPY that fails on the server leaves the connection usable
+        let wrong = batch.project(&[0]).unwrap();
+        assert!(
+            copy_batches_blocking(&mut client, "t", vec![wrong], &CopyOptions::default()).is_err()
+        );
+        assert_eq!(server.errors().len(), 1);
+        client.batch_execute("SELECT 1").unwrap();
+    }
+
+    #[cfg(feature = "tokio-postgres")]
+    #[tokio::test]
+    async fn test_mock_copy_server_with_tokio_postgres_client() {
+        use futures_util::stream;
+
+        use crate::load::{copy_batches, CopyOptions};
+
+        let batch = make_batch();
+        let (schema, _) = encode(&batch);
+        let server = MockCopyServer::start(schema).unwrap();
+        let config: tokio_postgres::Config = server.connection_string().parse().unwrap();
+        let stream = tokio::net::TcpStream::connect(server.addr()).await.unwrap();
+        let (mut client, connection) = config
+            .connect_raw(stream, tokio_postgres::NoTls)
+            .await
+            .unwrap();
+        tokio::spawn(connection);
+
+        let options = CopyOptions::default();
+        let batches = stream::iter(vec![batch.clone()]);
+        let rows = copy_batches(&mut client, "t", batches, &options)
+            .await
+            .unwrap();
+        assert_eq!(rows, 2);
+        assert_eq!(server.batches(), vec![batch.clone()]);
+
+        // a COPY that fails on the server drops the staging table
+        let wrong = stream::iter(vec![batch.project(&[0]).unwrap()]);
+        assert!(matches!(
+            copy_batches(&mut client, "t", wrong, &options).await,
+            Err(ErrorKind::Client(_))
+        ));
+        assert_eq!(server.errors().len(), 1);
+        let statements = server.statements();
+        assert!(statements
+            .last()
+            .unwrap()
+            .starts_with("DROP TABLE IF EXISTS"));
+    }
+
+    #[cfg(feature = "sqlx")]
+    #[tokio::test]
+    async fn test_mock_copy_server_with_sqlx_client() {
+        use futures_util::stream::{self, StreamExt};
+        use sqlx::Connection;
+
+        use crate::load::{copy_batches, staging_statements, CopyOptions};
+
+        let batch = make_batch();
+        let (schema, _) = encode(&batch);
+        let server = MockCopyServer::start(schema.clone()).unwrap();
+        let mut conn = sqlx::PgConnection::connect(&server.url()).await.unwrap();
+
+        let options = CopyOptions::default().with_chunk_size(64);
+        let batches = stream::iter(vec![batch.clone(), batch.clone()]);
+        let rows = copy_batches(&mut conn, "t", batches, &options)
+            .await
+            .unwrap();
+        assert_eq!(rows, 4);
+        assert_eq!(
+            server.batches(),
+            vec![arrow_select::concat::concat_batches(&batch.schema(), [&batch, &batch]).unwrap()]
+        );
+        // the staging statements go through the simple protocol in a single message
+        let statements = server.statements();
+        assert_eq!(statements[0], staging_statements(&schema, &options));
+        assert!(statements[2].starts_with("INSERT INTO t"));
+
+        // a batch that fails to encode aborts the COPY and drops the staging table
+        let wrong = batch.project(&[0]).unwrap();
+        let batches = stream::iter(vec![batch.clone(), wrong]);
+        assert!(matches!(
+            copy_batches(&mut conn, "t", batches, &options).await,
+            Err(ErrorKind::SchemaMismatch { .. })
+        ));
+        let errors = server.errors();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("COPY from stdin failed"));
+        let statements = server.statements();
+        assert!(statements
+            .last()
+            .unwrap()
+            .starts_with("DROP TABLE IF EXISTS"));
+        assert_eq!(server.batches().len(), 1);
+
+        // losing the connection while sending data fails the load with the client's error
+        let mut server = Some(server);
+        let batches = stream::iter(0..100).map(move |i| {
+            if i == 1 {
+                // stops the server and closes the connection
+                server.take();
+            }
+            batch.clone()
+        });
+        assert!(matches!(
+            copy_batches(&mut conn, "t", batches, &options).await,
+            Err(ErrorKind::Client(_))
+        ));
+    }
+}
@@ -0,0 +1,906 @@
+//! Text and CSV COPY encoders. They encode each batch with an `ArrowToPostgresBinaryEncoder`
+//! and transcode its output, so columns are mapped, encoded and checked exactly as for
+//! binary COPY, using the columns, encoders and error policy of the wrapped encoder,
+//! and both report the same `PostgresSchema`.
+
+use std::fmt::Write as _;
+
+use arrow_array::RecordBatch;
+use arrow_schema::Schema;
+use bytes::{Buf, BufMut, BytesMut};
+
+use crate::encoders::{PG_BASE_DATE_OFFSET, PG_BASE_TIMESTAMP_OFFSET_US};
+use crate::error::ErrorKind;
+use crate::pg_schema::{PostgresSchema, PostgresType};
+use crate::ArrowToPostgresBinaryEncoder;
+
+const NUMERIC_NEG: u16 = 0x4000;
+const NUMERIC_NAN: u16 = 0xC000;
+const NUMERIC_PINF: u16 = 0xD000;
+const NUMERIC_NINF: u16 = 0xF000;
+const NUM_US_PER_DAY: i64 = 86_400_000_000;
+const PG_MONEY_SCALE: usize = 2;
+
+fn malformed(what: &str) -> ErrorKind {
+    ErrorKind::Encode {
+        reason: format!("malformed binary {what} value"),
+    }
+}
+
+/// Reads `N` bytes, failing instead of panicking on short input
+fn take<'a>(buf: &mut &'a [u8], n: usize, what: &str) -> Result<&'a [u8], ErrorKind> {
+    if buf.len() < n {
+        return Err(malformed(what));
+    }
+    let (head, tail) = buf.split_at(n);
+    *buf = tail;
+    Ok(head)
+}
+
+fn fixed<const N: usize>(value: &[u8], what: &str) -> Result<[u8; N], ErrorKind> {
+    value.try_into().map_err(|_| malformed(what))
+}
+
+/// Writes `v` with the shortest digits that round trip in its own precision,
+/// so a `float4` 0.1 stays 0.1
+fn write_float<F: Into<f64> + Copy + std::fmt::Debug>(v: F, out: &mut String) {
+    let wide: f64 = v.into();
+    if wide.is_nan() {
+        out.push_str("NaN");
+    } else if wide.is_infinite() {
+        out.push_str(if wide > 0.0 { "Infinity" } else { "-Infinity" });
+    } else {
+        let _ = write!(out, "{v:?}");
+    }
+}
+
+/// Converts days since 1970-01-01 to a (year, month, day) civil date
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// Writes the date and returns whether it is before the common era
+fn write_date(days_since_unix_epoch: i64, out: &mut String) -> bool {
+    let (year, month, day) = civil_from_days(days_since_unix_epoch);
+    let bc = year <= 0;
+    let year = if bc { 1 - year } else { year };
+    let _ = write!(out, "{year:04}-{month:02}-{day:02}");
+    bc
+}
+
+fn write_time(micros: i64, out: &mut String) {
+    let secs = micros / 1_000_000;
+    let frac = micros % 1_000_000;
+    let _ = write!(
+        out,
+        "{:02}:{:02}:{:02}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    );
+    if frac != 0 {
+        let frac = format!("{frac:06}");
+        out.push('.');
+        out.push_str(frac.trim_end_matches('0'));
+    }
+}
+
+fn write_numeric(value: &[u8], out: &mut String) -> Result<(), ErrorKind> {
+    let mut buf = value;
+    let header = take(&mut buf, 8, "numeric")?;
+    let ndigits = i16::from_be_bytes([header[0], header[1]]) as i64;
+    let weight = i16::from_be_bytes([header[2], header[3]]) as i64;
+    let sign = u16::from_be_bytes([header[4], header[5]]);
+    let dscale = u16::from_be_bytes([header[6], header[7]]) as usize;
+    match sign {
+        NUMERIC_NAN => {
+            out.push_str("NaN");
+            return Ok(());
+        }
+        NUMERIC_PINF => {
+            out.push_str("Infinity");
+            return Ok(());
+        }
+        NUMERIC_NINF => {
+            out.push_str("-Infinity");
+            return Ok(());
+        }
+        _ => {}
+    }
+    let digits = (0..ndigits)
+        .map(|_| take(&mut buf, 2, "numeric").map(|d| i16::from_be_bytes([d[0], d[1]])))
+        .collect::<Result<Vec<_>, _>>()?;
+    let digit = |i: i64| {
+        if i >= 0 && i < ndigits {
+            digits[i as usize]
+        } else {
+            0
+        }
+    };
+    if sign == NUMERIC_NEG {
+        out.push('-');
+    }
+    if weight < 0 {
+        out.push('0');
+    } else {
+        for i in 0..=weight {
+            if i == 0 {
+                let _ = write!(out, "{}", digit(i));
+            } else {
+                let _ = write!(out, "{:04}", digit(i));
+            }
+        }
+    }
+    if dscale > 0 {
+        let mut frac = String::with_capacity(dscale + 4);
+        let mut i = weight + 1;
+        while frac.len() < dscale {
+            let _ = write!(frac, "{:04}", digit(i));
+            i += 1;
+        }
+        frac.truncate(dscale);
+        out.push('.');
+        out.push_str(&frac);
+    }
+    Ok(())
+}
+
+fn write_point(buf: &mut &[u8], out: &mut String) -> Result<(), ErrorKind> {
+    let x = f64::from_be_bytes(fixed(take(buf, 8, "point")?, "point")?);
+    let y = f64::from_be_bytes(fixed(take(buf, 8, "point")?, "point")?);
+    out.push('(');
+    write_float(x, out);
+    out.push(',');
+    write_float(y, out);
+    out.push(')');
+    Ok(())
+}
+
+fn write_points(buf: &mut &[u8], n: usize, out: &mut String) -> Result<(), ErrorKind> {
+    for i in 0..n {
+        if i > 0 {
+            out.push(',');
+        }
+        write_point(buf, out)?;
+    }
+    Ok(())
+}
+
+/// Quotes an array element or composite field if it would otherwise be misread
+fn write_quoted_element(value: &str, specials: &[char], out: &mut String) {
+    let needs_quotes = value.is_empty()
+        || value.eq_ignore_ascii_case("NULL")
+        || value
+            .chars()
+            .any(|c| c.is_whitespace() || c == '"' || c == '\\' || specials.contains(&c));
+    if !needs_quotes {
+        out.push_str(value);
+        return;
+    }
+    out.push('"');
+    for c in value.chars() {
+        if c == '"' || c == '\\' {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out.push('"');
+}
+
+fn write_array(value: &[u8], element: &PostgresType, out: &mut String) -> Result<(), ErrorKind> {
+    let mut buf = value;
+    let header = take(&mut buf, 12, "array")?;
+    let ndim = i32::from_be_bytes(fixed(&header[..4], "array")?);
+    if ndim == 0 {
+        out.push_str("{}");
+        return Ok(());
+    }
+    let mut dims = Vec::with_capacity(ndim as usize);
+    for _ in 0..ndim {
+        let dim = take(&mut buf, 8, "array")?;
+        let len = i32::from_be_bytes(fixed(&dim[..4], "array")?);
+        let lower = i32::from_be_bytes(fixed(&dim[4..], "array")?);
+        dims.push((len.max(0) as usize, lower));
+    }
+    if dims.iter().any(|(_, lower)| *lower != 1) {
+        for (len, lower) in &dims {
+            let _ = write!(out, "[{lower}:{}]", *lower as i64 + *len as i64 - 1);
+        }
+        out.push('=');
+    }
+    write_array_dim(&mut buf, &dims, element, out)
+}
+
+fn write_array_dim(
+    buf: &mut &[u8],
+    dims: &[(usize, i32)],
+    element: &PostgresType,
+    out: &mut String,
+) -> Result<(), ErrorKind> {
+    out.push('{');
+    for i in 0..dims[0].0 {
+        if i > 0 {
+            out.push(',');
+        }
+        if dims.len() > 1 {
+            write_array_dim(buf, &dims[1..], element, out)?;
+            continue;
+        }
+        let len = i32::from_be_bytes(fixed(take(buf, 4, "array")?, "array")?);
+        if len < 0 {
+            out.push_str("NULL");
+            continue;
+        }
+        let mut rendered = String::new();
+        write_value(element, take(buf, len as usize, "array")?, &mut rendered)?;
+        write_quoted_element(&rendered, &['{', '}', ','], out);
+    }
+    out.push('}');
+    Ok(())
+}
+
+fn write_composite(
+    value: &[u8],
+    fields: &[Box<crate::pg_schema::Column>],
+    out: &mut String,
+) -> Result<(), ErrorKind> {
+    let mut buf = value;
+    let n = i32::from_be_bytes(fixed(take(&mut buf, 4, "composite")?, "composite")?);
+    if n as usize != fields.len() {
+        return Err(malformed("composite"));
+    }
+    out.push('(');
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let _oid = take(&mut buf, 4, "composite")?;
+        let len = i32::from_be_bytes(fixed(take(&mut buf, 4, "composite")?, "composite")?);
+        // a NULL field is left empty
+        if len < 0 {
+            continue;
+        }
+        let mut rendered = String::new();
+        write_value(
+            &field.data_type,
+            take(&mut buf, len as usize, "composite")?,
+            &mut rendered,
+        )?;
+        write_quoted_element(&rendered, &['(', ')', ','], out);
+    }
+    out.push(')');
+    Ok(())
+}
+
+fn write_tsvector(value: &[u8], out: &mut String) -> Result<(), ErrorKind> {
+    let mut buf = value;
+    let n = i32::from_be_bytes(fixed(take(&mut buf, 4, "tsvector")?, "tsvector")?);
+    for i in 0..n {
+        if i > 0 {
+            out.push(' ');
+        }
+        let end = buf
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or_else(|| malformed("tsvector"))?;
+        let lexeme = std::str::from_utf8(&buf[..end]).map_err(|_| malformed("tsvector"))?;
+        buf = &buf[end + 1..];
+        out.push('\'');
+        for c in lexeme.chars() {
+            if c == '\'' || c == '\\' {
+                out.push(c);
+            }
+            out.push(c);
+        }
+        out.push('\'');
+        let npos = u16::from_be_bytes(fixed(take(&mut buf, 2, "tsvector")?, "tsvector")?);
+        for j in 0..npos {
+            out.push(if j == 0 { ':' } else { ',' });
+            let p = u16::from_be_bytes(fixed(take(&mut buf, 2, "tsvector")?, "tsvector")?);
+            let _ = write!(out, "{}", p & 0x3FFF);
+            match p >> 14 {
+                3 => out.push('A'),
+                2 => out.push('B'),
+                1 => out.push('C'),
+                _ => {}
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes Postgres' text representation of a non-NULL value given in the binary format
+fn write_value(tp: &PostgresType, value: &[u8], out: &mut String) -> Result<(), ErrorKind> {
+    match tp {
+        PostgresType::Bool => out.push(if fixed::<1>(value, "bool")?[0] != 0 {
+            't'
+        } else {
+            'f'
+        }),
+        PostgresType::Char => {
+            let b = fixed::<1>(value, "char")?[0];
+            if b.is_ascii() && !b.is_ascii_control() {
+                out.push(b as char);
+            } else {
+                let _ = write!(out, "\\{b:03o}");
+            }
+        }
+        PostgresType::Int2 => {
+            let _ = write!(out, "{}", i16::from_be_bytes(fixed(value, "int2")?));
+        }
+        PostgresType::Int4 => {
+            let _ = write!(out, "{}", i32::from_be_bytes(fixed(value, "int4")?));
+        }
+        PostgresType::Int8 => {
+            let _ = write!(out, "{}", i64::from_be_bytes(fixed(value, "int8")?));
+        }
+        PostgresType::Oid | PostgresType::Regclass => {
+            let _ = write!(out, "{}", u32::from_be_bytes(fixed(value, "oid")?));
+        }
+        PostgresType::Float4 => write_float(f32::from_be_bytes(fixed(value, "float4")?), out),
+        PostgresType::Float8 => write_float(f64::from_be_bytes(fixed(value, "float8")?), out),
+        PostgresType::Numeric => write_numeric(value, out)?,
+        PostgresType::Money => {
+            let cents = i64::from_be_bytes(fixed(value, "money")?);
+            let scale = 10i64.pow(PG_MONEY_SCALE as u32);
+            let sign = if cents < 0 { "-" } else { "" };
+            let cents = cents.unsigned_abs();
+            let _ = write!(
+                out,
+                "{sign}{}.{:0width$}",
+                cents / scale as u64,
+                cents % scale as u64,
+                width = PG_MONEY_SCALE
+            );
+        }
+        PostgresType::Text | PostgresType::Json | PostgresType::Xml => {
+            out.push_str(std::str::from_utf8(value).map_err(|_| malformed("text"))?)
+        }
+        PostgresType::Jsonb => match value.split_first() {
+            Some((1, rest)) => {
+                out.push_str(std::str::from_utf8(rest).map_err(|_| malformed("jsonb"))?)
+            }
+            _ => return Err(malformed("jsonb")),
+        },
+        PostgresType::Bytea => {
+            out.push_str("\\x");
+            for b in value {
+                let _ = write!(out, "{b:02x}");
+            }
+        }
+        PostgresType::Uuid => {
+            let bytes = fixed::<16>(value, "uuid")?;
+            for (i, b) in bytes.iter().enumerate() {
+                if matches!(i, 4 | 6 | 8 | 10) {
+                    out.push('-');
+                }
+                let _ = write!(out, "{b:02x}");
+            }
+        }
+        PostgresType::Date => match i32::from_be_bytes(fixed(value, "date")?) {
+            i32::MAX => out.push_str("infinity"),
+            i32::MIN => out.push_str("-infinity"),
+            days => {
+                if write_date(days as i64 + PG_BASE_DATE_OFFSET as i64, out) {
+                    out.push_str(" BC");
+                }
+            }
+        },
+        PostgresType::Time => write_time(i64::from_be_bytes(fixed(value, "time")?), out),
+        PostgresType::Timestamp => match i64::from_be_bytes(fixed(value, "timestamp")?) {
+            i64::MAX => out.push_str("infinity"),
+            i64::MIN => out.push_str("-infinity"),
+            micros => {
+                let micros = micros as i128 + PG_BASE_TIMESTAMP_OFFSET_US as i128;
+                let days = micros.div_euclid(NUM_US_PER_DAY as i128) as i64;
+                let time = micros.rem_euclid(NUM_US_PER_DAY as i128) as i64;
+                let bc = write_date(days, out);
+                out.push(' ');
+                write_time(time, out);
+                if bc {
+                    out.push_str(" BC");
+                }
+            }
+        },
+        PostgresType::Interval => {
+            let mut buf = &fixed::<16>(value, "interval")?[..];
+            let micros = buf.get_i64();
+            let days = buf.get_i32();
+            let months = buf.get_i32();
+            let _ = write!(out, "{months} mons {days} days ");
+            if micros < 0 {
+                out.push('-');
+            }
+            write_time(micros.unsigned_abs() as i64, out);
+        }
+        PostgresType::Point => write_point(&mut &value[..], out)?,
+        PostgresType::Lseg => {
+            out.push('[');
+            write_points(&mut &value[..], 2, out)?;
+            out.push(']');
+        }
+        PostgresType::Box => write_points(&mut &value[..], 2, out)?,
+        PostgresType::Circle => {
+            let mut buf = value;
+            out.push('<');
+            write_point(&mut buf, out)?;
+            out.push(',');
+            write_float(
+                f64::from_be_bytes(fixed(take(&mut buf, 8, "circle")?, "circle")?),
+                out,
+            );
+            out.push('>');
+        }
+        PostgresType::Path => {
+            let mut buf = value;
+            let closed = take(&mut buf, 1, "path")?[0] != 0;
+            let n = i32::from_be_bytes(fixed(take(&mut buf, 4, "path")?, "path")?);
+            out.push(if closed { '(' } else { '[' });
+            write_points(&mut buf, n.max(0) as usize, out)?;
+            out.push(if closed { ')' } else { ']' });
+        }
+        PostgresType::Polygon => {
+            let mut buf = value;
+            let n = i32::from_be_bytes(fixed(take(&mut buf, 4, "polygon")?, "polygon")?);
+            out.push('(');
+            write_points(&mut buf, n.max(0) as usize, out)?;
+            out.push(')');
+        }
+        PostgresType::Tsvector => write_tsvector(value, out)?,
+        PostgresType::List(inner) => write_array(value, &inner.data_type, out)?,
+        PostgresType::UserDefined { fields } => write_composite(value, fields, out)?,
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TextFormat {
+    Text,
+    Csv,
+}
+
+impl TextFormat {
+    fn delimiter(&self) -> char {
+        match self {
+            TextFormat::Text => '\t',
+            TextFormat::Csv => ',',
+        }
+    }
+
+    fn write_null(&self, out: &mut BytesMut) {
+        if let TextFormat::Text = self {
+            out.put_slice(b"\\N");
+        }
+    }
+
+    fn write_field(&self, value: &str, out: &mut BytesMut) {
+        match self {
+            TextFormat::Text => {
+                for c in value.chars() {
+                    match c {
+                        '\\' => out.put_slice(b"\\\\"),
+                        '\n' => out.put_slice(b"\\n"),
+                        '\r' => out.put_slice(b"\\r"),
+                        '\t' => out.put_slice(b"\\t"),
+                        '\x08' => out.put_slice(b"\\b"),
+                        '\x0c' => out.put_slice(b"\\f"),
+                        '\x0b' => out.put_slice(b"\\v"),
+                        c => {
+                            let mut utf8 = [0; 4];
+                            out.put_slice(c.encode_utf8(&mut utf8).as_bytes());
+                        }
+                    }
+                }
+            }
+            TextFormat::Csv => {
+                // empty strings are quoted to tell them apart from NULL
+                let needs_quotes =
+                    value.is_empty() || value == "\\." || value.contains([',', '"', '\n', '\r']);
+                if needs_quotes {
+                    out.put_u8(b'"');
+                    out.put_slice(value.replace('"', "\"\"").as_bytes());
+                    out.put_u8(b'"');
+                } else {
+                    out.put_slice(value.as_bytes());
+                }
+            }
+        }
+    }
+}
+
+/// Re-renders the binary encoder's output in a text format, one line per row
+#[derive(Debug)]
+struct Transcoder {
+    encoder: ArrowToPostgresBinaryEncoder,
+    types: Vec<PostgresType>,
+    format: TextFormat,
+    binary: BytesMut,
+    value: String,
+}
+
+impl Transcoder {
+    fn new(encoder: ArrowToPostgresBinaryEncoder, format: TextFormat) -> Self {
+        let types = encoder
+            .schema()
+            .columns
+            .into_iter()
+            .map(|c| c.data_type)
+            .collect();
+        Self {
+            encoder,
+            types,
+            format,
+            binary: BytesMut::new(),
+            value: String::new(),
+        }
+    }
+
+    fn write_header(&mut self) -> Result<(), ErrorKind> {
+        // the binary header is not part of the text output
+        self.encoder.write_header(&mut self.binary)?;
+        self.binary.clear();
+        Ok(())
+    }
+
+    /// Like the binary `write_batch`, nothing from a batch that fails is left in `out`
+    fn write_batch(&mut self, batch: &RecordBatch, out: &mut BytesMut) -> Result<(), ErrorKind> {
+        self.binary.clear();
+        self.encoder.write_batch(batch, &mut self.binary)?;
+        let start = out.len();
+        let result = self.write_rows(out);
+        if result.is_err() {
+            out.truncate(start);
+        }
+        result
+    }
+
+    /// Transcodes the rows in `self.binary`
+    fn write_rows(&mut self, out: &mut BytesMut) -> Result<(), ErrorKind> {
+        let mut buf = &self.binary[..];
+        let delimiter = self.format.delimiter();
+        while buf.has_remaining() {
+            let n_fields = buf.get_i16() as usize;
+            for (i, tp) in self.types.iter().enumerate().take(n_fields) {
+                if i > 0 {
+                    out.put_u8(delimiter as u8);
+                }
+                let len = buf.get_i32();
+                if len < 0 {
+                    self.format.write_null(out);
+                    continue;
+                }
+                let (value, rest) = buf.split_at(len as usize);
+                buf = rest;
+                self.value.clear();
+                write_value(tp, value, &mut self.value)?;
+                self.format.write_field(&self.value, out);
+            }
+            out.put_u8(b'\n');
+        }
+        Ok(())
+    }
+
+    fn write_footer(&mut self) -> Result<(), ErrorKind> {
+        // the binary trailer is not part of the text output
+        self.encoder.write_footer(&mut self.binary)?;
+        self.binary.clear();
+        Ok(())
+    }
+}
+
+/// Encodes batches in the `COPY ... WITH (FORMAT TEXT)` format, for targets that
+/// do not accept binary COPY.
+#[derive(Debug)]
+pub struct ArrowToPostgresTextEncoder {
+    inner: Transcoder,
+}
+
+impl ArrowToPostgresTextEncoder {
+    pub fn try_new(schema: &Schema) -> Result<Self, ErrorKind> {
+        Ok(Self::from_binary(ArrowToPostgresBinaryEncoder::try_new(
+            schema,
+        )?))
+    }
+
+    /// Wraps an encoder that has not written its header yet,
+    /// for example one made by `try_new_for_target`
+    pub fn from_binary(encoder: ArrowToPostgresBinaryEncoder) -> Self {
+        Self {
+            inner: Transcoder::new(encoder, TextFormat::Text),
+        }
+    }
+
+    pub fn encoder(&self) -> &ArrowToPostgresBinaryEncoder {
+        &self.inner.encoder
+    }
+
+    pub fn encoder_mut(&mut self) -> &mut ArrowToPostgresBinaryEncoder {
+        &mut self.inner.encoder
+    }
+
+    pub fn schema(&self) -> PostgresSchema {
+        self.inner.encoder.schema()
+    }
+
+    /// The text format has no header, but this must still be called first
+    pub fn write_header(&mut self, _out: &mut BytesMut) -> Result<(), ErrorKind> {
+        self.inner.write_header()
+    }
+
+    pub fn write_batch(
+        &mut self,
+        batch: &RecordBatch,
+        out: &mut BytesMut,
+    ) -> Result<(), ErrorKind> {
+        self.inner.write_batch(batch, out)
+    }
+
+    /// The text format has no footer, but this must still be called last
+    pub fn write_footer(&mut self, _out: &mut BytesMut) -> Result<(), ErrorKind> {
+        self.inner.write_footer()
+    }
+}
+
+/// Encodes batches in the `COPY ... WITH (FORMAT CSV)` format, for targets that
+/// do not accept binary COPY.
+#[derive(Debug)]
+pub struct ArrowToPostgresCsvEncoder {
+    inner: Transcoder,
+    header: bool,
+}
+
+impl ArrowToPostgresCsvEncoder {
+    pub fn try_new(schema: &Schema) -> Result<Self, ErrorKind> {
+        Ok(Self::from_binary(ArrowToPostgresBinaryEncoder::try_new(
+            schema,
+        )?))
+    }
+
+    /// Like `ArrowToPostgresTextEncoder::from_binary`
+    pub fn from_binary(encoder: ArrowToPostgresBinaryEncoder) -> Self {
+        Self {
+            inner: Transcoder::new(encoder, TextFormat::Csv),
+            header: false,
+        }
+    }
+
+    /// Writes the column names as the first line, for `COPY ... WITH (FORMAT CSV, HEADER)`
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn encoder(&self) -> &ArrowToPostgresBinaryEncoder {
+        &self.inner.encoder
+    }
+
+    pub fn encoder_mut(&mut self) -> &mut ArrowToPostgresBinaryEncoder {
+        &mut self.inner.encoder
+    }
+
+    pub fn schema(&self) -> PostgresSchema {
+        self.inner.encoder.schema()
+    }
+
+    pub fn write_header(&mut self, out: &mut BytesMut) -> Result<(), ErrorKind> {
+        self.inner.write_header()?;
+        if self.header {
+            for (i, column) in self.schema().columns.iter().enumerate() {
+                if i > 0 {
+                    out.put_u8(b',');
+                }
+                TextFormat::Csv.write_field(&column.name, out);
+            }
+            out.put_u8(b'\n');
+        }
+        Ok(())
+    }
+
+    pub fn write_batch(
+        &mut self,
+        batch: &RecordBatch,
+        out: &mut BytesMut,
+    ) -> Result<(), ErrorKind> {
+        self.inner.write_batch(batch, out)
+    }
+
+    /// CSV has no footer, but this must still be called last
+    pub fn write_footer(&mut self, _out: &mut BytesMut) -> Result<(), ErrorKind> {
+        self.inner.write_footer()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::builder::{ListBuilder, StringBuilder};
+    use arrow_array::{
+        ArrayRef, BinaryArray, BooleanArray, Date32Array, Decimal128Array,
+        DurationMicrosecondArray, Float64Array, Int32Array, StringArray, StructArray,
+        TimestampMicrosecondArray,
+    };
+    use arrow_schema::{DataType, Field, Fields};
+
+    use super::*;
+
+    fn make_batch() -> RecordBatch {
+        let mut tags = ListBuilder::new(StringBuilder::new());
+        tags.values().append_value("a b");
+        tags.values().append_null();
+        tags.values().append_value("c");
+        tags.append(true);
+        tags.append(false);
+        let point_fields = Fields::from(vec![
+            Field::new("x", DataType::Int32, false),
+            Field::new("s", DataType::Utf8, true),
+        ]);
+        let columns: Vec<(&str, ArrayRef)> = vec![
+            ("b", Arc::new(BooleanArray::from(vec![true, false]))),
+            ("i", Arc::new(Int32Array::from(vec![Some(-7), None]))),
+            ("f", Arc::new(Float64Array::from(vec![0.5, f64::NAN]))),
+            (
+                "n",
+                Arc::new(
+                    Decimal128Array::from(vec![12345, -5])
+                        .with_precision_and_scale(10, 3)
+                        .unwrap(),
+                ),
+            ),
+            (
+                "t",
+                Arc::new(StringArray::from(vec!["tab\there \\ \"q\", x", ""])),
+            ),
+            (
+                "bytes",
+                Arc::new(BinaryArray::from(vec![&b"\xde\xad"[..], &b""[..]])),
+            ),
+            ("d", Arc::new(Date32Array::from(vec![19_000, -719_528]))),
+            (
+                "ts",
+                Arc::new(TimestampMicrosecondArray::from(vec![
+                    1_700_000_000_123_000,
+                    0,
+                ])),
+            ),
+            (
+                "dur",
+                Arc::new(DurationMicrosecondArray::from(vec![-3_723_000_000, 1])),
+            ),
+            ("tags", Arc::new(tags.finish())),
+            (
+                "s",
+                Arc::new(StructArray::new(
+                    point_fields,
+                    vec![
+                        Arc::new(Int32Array::from(vec![1, 2])),
+                        Arc::new(StringArray::from(vec![Some("x,y"), None])),
+                    ],
+                    None,
+                )),
+            ),
+        ];
+        RecordBatch::try_from_iter(columns).unwrap()
+    }
+
+    fn encode_text(batch: &RecordBatch) -> String {
+        let mut encoder = ArrowToPostgresTextEncoder::try_new(&batch.schema()).unwrap();
+        let mut out = BytesMut::new();
+        encoder.write_header(&mut out).unwrap();
+        encoder.write_batch(batch, &mut out).unwrap();
+        encoder.write_footer(&mut out).unwrap();
+        String::from_utf8(out.to_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_text_encoder() {
+        let batch = make_batch();
+        let expected = [
+            "t",
+            "-7",
+            "0.5",
+            "12.345",
+            "tab\\there \\\\ \"q\", x",
+            "\\\\xdead",
+            "2022-01-08",
+            "2023-11-14 22:13:20.123",
+            "0 mons 0 days -01:02:03",
+            "{\"a b\",NULL,c}",
+            "(1,\"x,y\")",
+        ]
+        .join("\t")
+            + "\n"
+            + &[
+                "f",
+                "\\N",
+                "NaN",
+                "-0.005",
+                "",
+                "\\\\x",
+                "0001-01-01 BC",
+                "1970-01-01 00:00:00",
+                "0 mons 0 days 00:00:00.000001",
+                "\\N",
+                "(2,)",
+            ]
+            .join("\t")
+            + "\n";
+        assert_eq!(encode_text(&batch), expected);
+    }
+
+    #[test]
+    fn test_csv_encoder() {
+        let batch = make_batch().project(&[1, 4, 9]).unwrap();
+        let mut encoder = ArrowToPostgresCsvEncoder::try_new(&batch.schema())
+            .unwrap()
+            .with_header(true);
+        let mut out = BytesMut::new();
+        encoder.write_header(&mut out).unwrap();
+        encoder.write_batch(&batch, &mut out).unwrap();
+        encoder.write_footer(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out.to_vec()).unwrap(),
+            "i,t,tags\n-7,\"tab\there \\ \"\"q\"\", x\",\"{\"\"a b\"\",NULL,c}\"\n,\"\",\n"
+        );
+    }
+
+    #[test]
+    fn test_text_encoder_rollback() {
+        let batch = make_batch().project(&[1, 1]).unwrap();
+        let mut encoder = ArrowToPostgresTextEncoder::try_new(&batch.schema()).unwrap();
+        // a type that does not match the binary values, so the second field fails
+        encoder.inner.types[1] = PostgresType::Bool;
+        let mut out = BytesMut::from(&b"earlier\n"[..]);
+        encoder.write_header(&mut out).unwrap();
+        assert!(encoder.write_batch(&batch, &mut out).is_err());
+        assert_eq!(&out[..], b"earlier\n");
+    }
+
+    #[test]
+    fn test_float4_text() {
+        let mut out = String::new();
+        write_float(0.1f32, &mut out);
+        assert_eq!(out, "0.1");
+    }
+
+    #[test]
+    fn test_text_encoders_share_schema() {
+        let batch = make_batch();
+        let binary = ArrowToPostgresBinaryEncoder::try_new(&batch.schema()).unwrap();
+        let text = ArrowToPostgresTextEncoder::try_new(&batch.schema()).unwrap();
+        let csv = ArrowToPostgresCsvEncoder::try_new(&batch.schema()).unwrap();
+        assert_eq!(text.schema().columns, binary.schema().columns);
+        assert_eq!(csv.schema().columns, binary.schema().columns);
+    }
+
+    #[test]
+    fn test_write_numeric() {
+        let render = |digits: &[i16], weight: i16, sign: u16, dscale: u16| {
+            let mut buf = BytesMut::new();
+            buf.put_i16(digits.len() as i16);
+            buf.put_i16(weight);
+            buf.put_u16(sign);
+            buf.put_u16(dscale);
+            for d in digits {
+                buf.put_i16(*d);
+            }
+            let mut out = String::new();
+            write_numeric(&buf, &mut out).unwrap();
+            out
+        };
+        assert_eq!(render(&[1, 2345], 1, 0, 0), "12345");
+        assert_eq!(render(&[12], -1, NUMERIC_NEG, 4), "-0.0012");
+        assert_eq!(render(&[5], -2, 0, 8), "0.00000005");
+        assert_eq!(render(&[1], 2, 0, 2), "100000000.00");
+        assert_eq!(render(&[], 0, NUMERIC_PINF, 0), "Infinity");
+    }
+}
@@ -0,0 +1,756 @@
+use std::io::Write;
+
+use arrow_array::{RecordBatch, RecordBatchReader};
+use bytes::{Bytes, BytesMut};
+
+use crate::error::ErrorKind;
+use crate::ArrowToPostgresBinaryEncoder;
+
+/// The number of buffered bytes after which `CopyWriter` writes to its sink
+pub const DEFAULT_FLUSH_THRESHOLD: usize = 1024 * 1024;
+
+/// The size of the COPY footer, a field count of -1
+const FOOTER_SIZE: usize = 2;
+
+/// The error for using a writer after a write to its sink failed
+pub(crate) fn sink_failed() -> std::io::Error {
+    std::io::Error::other("an earlier write to the sink failed, so the COPY stream is incomplete")
+}
+
+/// What `ChunkEncoder` produces next
+#[derive(Debug)]
+pub(crate) enum Output {
+    /// The next chunk of the COPY stream
+    Chunk(Bytes),
+    /// A row that does not fit in a chunk on its own, as a batch of that row and its
+    /// row number in the input batch. It follows the previous chunk in the stream.
+    LargeRow(RecordBatch, usize),
+    /// The next batch or the end of the input is needed to go on
+    NeedsInput,
+    /// The footer has been produced
+    Done,
+}
+
+/// A batch being written and the first of its rows not yet written
+#[derive(Debug)]
+struct PendingBatch {
+    batch: RecordBatch,
+    first_row: usize,
+    // the end offset of each row, computed once per batch when chunks have a maximum size
+    offsets: Option<Vec<usize>>,
+}
+
+/// Encodes batches into a COPY stream cut into chunks of at least `chunk_size` bytes,
+/// or at most `max_chunk_size` bytes when that is set, for the writers and chunk iterators.
+/// Batches are pushed in one at a time as `next_output` asks for them.
+#[derive(Debug)]
+pub(crate) struct ChunkEncoder {
+    encoder: ArrowToPostgresBinaryEncoder,
+    buf: BytesMut,
+    chunk_size: usize,
+    max_chunk_size: Option<usize>,
+    pending: Option<PendingBatch>,
+    // where the pending batch starts in `buf`, or 0 once part of it went out in a chunk
+    batch_start: usize,
+    started: bool,
+    end_of_input: bool,
+    done: bool,
+}
+
+impl ChunkEncoder {
+    pub(crate) fn new(encoder: ArrowToPostgresBinaryEncoder, chunk_size: usize) -> Self {
+        Self {
+            encoder,
+            buf: BytesMut::new(),
+            chunk_size,
+            max_chunk_size: None,
+            pending: None,
+            batch_start: 0,
+            started: false,
+            end_of_input: false,
+            done: false,
+        }
+    }
+
+    pub(crate) fn set_chunk_size(&mut self, chunk_size: usize) {
+        self.chunk_size = chunk_size;
+    }
+
+    pub(crate) fn set_max_chunk_size(&mut self, max_chunk_size: usize) {
+        self.max_chunk_size = Some(max_chunk_size);
+    }
+
+    pub(crate) fn encoder(&self) -> &ArrowToPostgresBinaryEncoder {
+        &self.encoder
+    }
+
+    pub(crate) fn encoder_mut(&mut self) -> &mut ArrowToPostgresBinaryEncoder {
+        &mut self.encoder
+    }
+
+    /// Writes the header unless that has been done
+    pub(crate) fn start(&mut self) -> Result<(), ErrorKind> {
+        if !self.started {
+            self.encoder.write_header(&mut self.buf)?;
+            self.started = true;
+        }
+        Ok(())
+    }
+
+    /// Queues the batch `next_output` asked for
+    pub(crate) fn push_batch(&mut self, batch: RecordBatch) {
+        debug_assert!(self.pending.is_none());
+        self.batch_start = self.buf.len();
+        self.pending = Some(PendingBatch {
+            batch,
+            first_row: 0,
+            offsets: None,
+        });
+    }
+
+    /// Marks the end of the input, after which `next_output` writes the footer
+    pub(crate) fn end_input(&mut self) {
+        self.end_of_input = true;
+    }
+
+    /// Everything buffered so far, even if it is less than a chunk
+    pub(crate) fn take_buffered(&mut self) -> Bytes {
+        self.batch_start = 0;
+        self.buf.split().freeze()
+    }
+
+    /// Drops the batch being written after it failed, along with whatever of it is still
+    /// buffered. Only the parts of it already taken out as chunks remain.
+    pub(crate) fn abort_batch(&mut self) {
+        self.pending = None;
+        self.buf.truncate(self.batch_start);
+    }
+
+    /// Encodes queued batches until a chunk is complete.
+    /// If a batch fails to encode it is dropped as by `abort_batch`.
+    pub(crate) fn next_output(&mut self) -> Result<Output, ErrorKind> {
+        if self.done {
+            return Ok(Output::Done);
+        }
+        self.start()?;
+        while let Some(pending) = self.pending.take() {
+            match self.write_pending(pending) {
+                Ok(Some(output)) => return Ok(output),
+                Ok(None) => {}
+                Err(e) => {
+                    self.abort_batch();
+                    return Err(e);
+                }
+            }
+        }
+        if !self.end_of_input {
+            return Ok(Output::NeedsInput);
+        }
+        if let Some(max_chunk_size) = self.max_chunk_size {
+            if !self.buf.is_empty() && self.buf.len() + FOOTER_SIZE > max_chunk_size {
+                return Ok(Output::Chunk(self.take_buffered()));
+            }
+        }
+        self.encoder.write_footer(&mut self.buf)?;
+        self.done = true;
+        Ok(Output::Chunk(self.take_buffered()))
+    }
+
+    /// Like `next_output`, but a large row is encoded into a chunk of its own
+    pub(crate) fn next_chunk(&mut self) -> Result<Output, ErrorKind> {
+        match self.next_output()? {
+            Output::LargeRow(row, first_row) => {
+                let res = self
+                    .encoder
+                    .write_batch_part(&row, first_row, &mut self.buf);
+                if res.is_err() {
+                    self.abort_batch();
+                }
+                res?;
+                Ok(Output::Chunk(self.take_buffered()))
+            }
+            output => Ok(output),
+        }
+    }
+
+    /// Encodes as much of `batch` from `first_row` on as belongs in the current chunk,
+    /// leaving the rest pending, and returns the chunk once it is complete
+    fn write_pending(&mut self, mut pending: PendingBatch) -> Result<Option<Output>, ErrorKind> {
+        let max_chunk_size = match self.max_chunk_size {
+            Some(max_chunk_size) if pending.batch.num_rows() > 0 => max_chunk_size,
+            // empty batches are still written so that the encoder counts them
+            _ => {
+                self.encoder.write_batch(&pending.batch, &mut self.buf)?;
+                return Ok(self.full_chunk());
+            }
+        };
+        let offsets = match pending.offsets.take() {
+            Some(offsets) => offsets,
+            None => self.encoder.row_offsets(&pending.batch)?,
+        };
+        let first_row = pending.first_row;
+        let space = max_chunk_size.saturating_sub(self.buf.len());
+        // the rows from `first_row` on whose encoding fits in `space`
+        let start = offsets[first_row];
+        let n_rows = offsets[first_row..].partition_point(|end| end - start <= space) - 1;
+        let n_batch_rows = pending.batch.num_rows();
+        pending.offsets = Some(offsets);
+        if n_rows == 0 {
+            if !self.buf.is_empty() {
+                self.pending = Some(pending);
+                return Ok(Some(Output::Chunk(self.take_buffered())));
+            }
+            let row = pending.batch.slice(first_row, 1);
+            if first_row + 1 < n_batch_rows {
+                pending.first_row += 1;
+                self.pending = Some(pending);
+            }
+            return Ok(Some(Output::LargeRow(row, first_row)));
+        }
+        let part = pending.batch.slice(first_row, n_rows);
+        self.encoder
+            .write_batch_part(&part, first_row, &mut self.buf)?;
+        if first_row + n_rows < n_batch_rows {
+            pending.first_row += n_rows;
+            self.pending = Some(pending);
+            return Ok(Some(Output::Chunk(self.take_buffered())));
+        }
+        Ok(self.full_chunk())
+    }
+
+    fn full_chunk(&mut self) -> Option<Output> {
+        (self.buf.len() >= self.chunk_size).then(|| Output::Chunk(self.take_buffered()))
+    }
+}
+
+/// Writes a complete COPY stream to an `io::Write` sink.
+/// The header is written on creation and the footer by `finish`.
+#[derive(Debug)]
+pub struct CopyWriter<W: Write> {
+    chunks: ChunkEncoder,
+    inner: W,
+    memory_limit: Option<usize>,
+    // set once a write to the sink fails, after which the stream is incomplete
+    failed: bool,
+}
+
+impl<W: Write> CopyWriter<W> {
+    pub fn try_new(encoder: ArrowToPostgresBinaryEncoder, inner: W) -> Result<Self, ErrorKind> {
+        let mut chunks = ChunkEncoder::new(encoder, DEFAULT_FLUSH_THRESHOLD);
+        chunks.start()?;
+        Ok(Self {
+            chunks,
+            inner,
+            memory_limit: None,
+            failed: false,
+        })
+    }
+
+    /// Writes to the sink whenever at least `flush_threshold` bytes are buffered
+    pub fn with_flush_threshold(mut self, flush_threshold: usize) -> Self {
+        self.chunks.set_chunk_size(flush_threshold);
+        self
+    }
+
+    /// Buffers at most `memory_limit` bytes of output instead of a whole batch at a time.
+    /// Batches are written in parts of as many rows as fit. A row larger than the limit
+    /// is written on its own, with its text and binary values going straight from the
+    /// Arrow buffers to the sink, and fails with `ErrorKind::MemoryLimitExceeded`,
+    /// whatever the error policy, if its other fields alone exceed the limit.
+    /// When a batch fails part way, the parts of it written to the sink before the failing
+    /// row stay there, see `write_batch`.
+    pub fn with_memory_limit(mut self, memory_limit: usize) -> Self {
+        self.chunks.set_max_chunk_size(memory_limit);
+        self.memory_limit = Some(memory_limit);
+        self
+    }
+
+    pub fn encoder(&self) -> &ArrowToPostgresBinaryEncoder {
+        self.chunks.encoder()
+    }
+
+    pub fn encoder_mut(&mut self) -> &mut ArrowToPostgresBinaryEncoder {
+        self.chunks.encoder_mut()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Fails without writing anything once a write to the sink has failed.
+    /// If the batch fails to encode, nothing of it is left buffered and the writer can go on
+    /// with the next batch. Only with a memory limit can part of it have reached the sink:
+    /// chunks are written as soon as they fill up, so rows of the batch before the failing
+    /// one stay in the sink if a chunk holding them was completed before it.
+    pub fn write_batch(&mut self, batch: &RecordBatch) -> Result<(), ErrorKind> {
+        if self.failed {
+            return Err(sink_failed().into());
+        }
+        self.chunks.push_batch(batch.clone());
+        self.write_output()
+    }
+
+    /// Writes everything buffered so far to the sink and flushes it
+    pub fn flush(&mut self) -> Result<(), ErrorKind> {
+        let buffered = self.chunks.take_buffered();
+        self.write_to_sink(&buffered)?;
+        self.inner.flush()?;
+        Ok(())
+    }
+
+    /// Writes the footer and returns the sink
+    pub fn finish(mut self) -> Result<W, ErrorKind> {
+        if self.failed {
+            return Err(sink_failed().into());
+        }
+        self.chunks.end_input();
+        self.write_output()?;
+        self.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_output(&mut self) -> Result<(), ErrorKind> {
+        loop {
+            match self.chunks.next_output()? {
+                Output::Chunk(chunk) => self.write_to_sink(&chunk)?,
+                Output::LargeRow(row, first_row) => {
+                    let memory_limit = self.memory_limit.unwrap_or(usize::MAX);
+                    let res = self.chunks.encoder_mut().stream_row(
+                        &row,
+                        first_row,
+                        &mut self.inner,
+                        memory_limit,
+                    );
+                    if let Err(e) = res {
+                        self.failed = matches!(e, ErrorKind::Io(_));
+                        self.chunks.abort_batch();
+                        return Err(e);
+                    }
+                }
+                Output::NeedsInput | Output::Done => return Ok(()),
+            }
+        }
+    }
+
+    fn write_to_sink(&mut self, chunk: &[u8]) -> Result<(), ErrorKind> {
+        if self.failed {
+            return Err(sink_failed().into());
+        }
+        // a chunk that was partly written is gone from the buffer, so it is never resent
+        if let Err(e) = self.inner.write_all(chunk) {
+            self.failed = true;
+            return Err(e.into());
+        }
+        Ok(())
+    }
+}
+
+/// An iterator of COPY chunks encoded from a `RecordBatchReader`,
+/// starting with the header and ending with the footer.
+/// Each chunk holds at least `chunk_size` bytes except for the last one,
+/// or at most `max_chunk_size` bytes when that is set.
+/// Iteration ends after the first error.
+#[derive(Debug)]
+pub struct EncodeReader<R> {
+    chunks: ChunkEncoder,
+    reader: R,
+    done: bool,
+}
+
+impl<R: RecordBatchReader> EncodeReader<R> {
+    pub fn new(encoder: ArrowToPostgresBinaryEncoder, reader: R, chunk_size: usize) -> Self {
+        Self {
+            chunks: ChunkEncoder::new(encoder, chunk_size),
+            reader,
+            done: false,
+        }
+    }
+
+    /// Never yields chunks larger than `max_chunk_size`, splitting batches between rows
+    /// to stay under it. A row that is larger on its own gets a chunk to itself.
+    /// Chunks still end as soon as they hold `chunk_size` bytes.
+    pub fn with_max_chunk_size(mut self, max_chunk_size: usize) -> Self {
+        self.chunks.set_max_chunk_size(max_chunk_size);
+        self
+    }
+
+    pub fn encoder(&self) -> &ArrowToPostgresBinaryEncoder {
+        self.chunks.encoder()
+    }
+
+    fn next_chunk(&mut self) -> Result<Option<Bytes>, ErrorKind> {
+        loop {
+            match self.chunks.next_chunk()? {
+                Output::Chunk(chunk) => return Ok(Some(chunk)),
+                Output::NeedsInput => match self.reader.next() {
+                    Some(batch) => self.chunks.push_batch(batch?),
+                    None => self.chunks.end_input(),
+                },
+                Output::LargeRow(..) | Output::Done => return Ok(None),
+            }
+        }
+    }
+}
+
+impl<R: RecordBatchReader> Iterator for EncodeReader<R> {
+    type Item = Result<Bytes, ErrorKind>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let chunk = self.next_chunk();
+        if !matches!(chunk, Ok(Some(_))) {
+            self.done = true;
+        }
+        chunk.transpose()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::builder::{ListBuilder, StringBuilder};
+    use arrow_array::types::Int32Type;
+    use arrow_array::{ArrayRef, Int32Array, ListArray, RecordBatchIterator, StringArray};
+    use arrow_schema::ArrowError;
+    use arrow_schema::{DataType, Field, Schema};
+
+    use super::*;
+
+    fn make_batch(values: Vec<i32>) -> RecordBatch {
+        let schema = Schema::new(vec![Field::new("a", DataType::Int32, false)]);
+        RecordBatch::try_new(Arc::new(schema), vec![Arc::new(Int32Array::from(values))]).unwrap()
+    }
+
+    #[test]
+    fn test_copy_writer() {
+        let batches = vec![make_batch(vec![1, 2]), make_batch(vec![3])];
+        let schema = batches[0].schema();
+
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&schema).unwrap();
+        let mut expected = BytesMut::new();
+        encoder.write_header(&mut expected).unwrap();
+        for batch in &batches {
+            encoder.write_batch(batch, &mut expected).unwrap();
+        }
+        encoder.write_footer(&mut expected).unwrap();
+
+        let encoder = ArrowToPostgresBinaryEncoder::try_new(&schema).unwrap();
+        let mut writer = CopyWriter::try_new(encoder, vec![])
+            .unwrap()
+            .with_flush_threshold(30);
+        writer.write_batch(&batches[0]).unwrap();
+        // the header and two rows exceed the threshold
+        assert_eq!(writer.get_ref().len(), 19 + 2 * 10);
+        writer.write_batch(&batches[1]).unwrap();
+        assert_eq!(writer.get_ref().len(), 19 + 2 * 10);
+        let out = writer.finish().unwrap();
+        assert_eq!(out, expected.to_vec());
+    }
+
+    /// A sink that records the size of every write
+    #[derive(Default)]
+    struct RecordingSink {
+        data: Vec<u8>,
+        writes: Vec<usize>,
+    }
+
+    impl Write for RecordingSink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.data.extend_from_slice(buf);
+            self.writes.push(buf.len());
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_copy_writer_memory_limit() {
+        let big = "x".repeat(1000);
+        let columns: Vec<(&str, ArrayRef)> = vec![
+            ("id", Arc::new(Int32Array::from(vec![1, 2, 3, 4]))),
+            (
+                "doc",
+                Arc::new(StringArray::from(vec![
+                    Some("a"),
+                    Some(big.as_str()),
+                    None,
+                    Some("b"),
+                ])),
+            ),
+        ];
+        let batch = RecordBatch::try_from_iter(columns).unwrap();
+
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&batch.schema()).unwrap();
+        let mut expected = BytesMut::new();
+        encoder.write_header(&mut expected).unwrap();
+        encoder.write_batch(&batch, &mut expected).unwrap();
+        encoder.write_footer(&mut expected).unwrap();
+
+        let encoder = ArrowToPostgresBinaryEncoder::try_new(&batch.schema()).unwrap();
+        let mut writer = CopyWriter::try_new(encoder, RecordingSink::default())
+            .unwrap()
+            .with_memory_limit(100);
+        writer.write_batch(&batch).unwrap();
+        let sink = writer.finish().unwrap();
+        assert_eq!(sink.data, expected.to_vec());
+        // the large value goes to the sink straight from the Arrow buffer
+        assert!(sink.writes.contains(&1000));
+        assert!(sink.writes.iter().all(|len| *len <= 1000));
+    }
+
+    #[test]
+    fn test_copy_writer_memory_limit_exceeded() {
+        let batch = make_batch(vec![1, 2]);
+        let encoder = ArrowToPostgresBinaryEncoder::try_new(&batch.schema()).unwrap();
+        let mut writer = CopyWriter::try_new(encoder, vec![])
+            .unwrap()
+            .with_memory_limit(5);
+        let err = writer.write_batch(&batch).unwrap_err();
+        assert!(matches!(
+            err.root_cause(),
+            ErrorKind::MemoryLimitExceeded { size: 10, limit: 5 }
+        ));
+        let location = err.location().unwrap();
+        assert_eq!((location.path.as_str(), location.row), ("a", Some(0)));
+    }
+
+    #[test]
+    fn test_copy_writer_memory_limit_oversized_list() {
+        let mut values = ListBuilder::new(StringBuilder::new());
+        values.append_value([Some("small")]);
+        values.append_value(vec![Some("x".repeat(100)); 10_000]);
+        let batch =
+            RecordBatch::try_from_iter(vec![("a", Arc::new(values.finish()) as ArrayRef)]).unwrap();
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&batch.schema()).unwrap();
+        // a row over the limit is not skipped like a row that fails to encode
+        encoder.set_error_policy(crate::ErrorPolicy::SkipRow);
+        let mut writer = CopyWriter::try_new(encoder, vec![])
+            .unwrap()
+            .with_memory_limit(1000);
+        let err = writer.write_batch(&batch).unwrap_err();
+        assert!(matches!(
+            err.root_cause(),
+            ErrorKind::MemoryLimitExceeded { size, limit: 1000 } if *size > 1_000_000
+        ));
+        // the value is sized from its offsets and not rendered
+        let location = err.location().unwrap();
+        assert_eq!((location.path.as_str(), location.row), ("a", Some(1)));
+        assert_eq!(location.value, None);
+        assert_eq!(writer.encoder().rows_skipped(), 0);
+    }
+
+    #[test]
+    fn test_copy_writer_memory_limit_failure_mid_batch() {
+        let list = |len: usize| Some(vec![Some(1); len]);
+        let values =
+            ListArray::from_iter_primitive::<Int32Type, _, _>(vec![list(1), list(20), list(1)]);
+        let batch = RecordBatch::try_from_iter(vec![("a", Arc::new(values) as ArrayRef)]).unwrap();
+        let next = RecordBatch::try_from_iter(vec![(
+            "a",
+            Arc::new(ListArray::from_iter_primitive::<Int32Type, _, _>(vec![
+                list(2),
+            ])) as ArrayRef,
+        )])
+        .unwrap();
+
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&batch.schema()).unwrap();
+        let mut expected = BytesMut::new();
+        encoder.write_header(&mut expected).unwrap();
+        encoder
+            .write_batch(&batch.slice(0, 1), &mut expected)
+            .unwrap();
+        encoder.write_batch(&next, &mut expected).unwrap();
+        encoder.write_footer(&mut expected).unwrap();
+
+        let encoder = ArrowToPostgresBinaryEncoder::try_new(&batch.schema()).unwrap();
+        let mut writer = CopyWriter::try_new(encoder, vec![])
+            .unwrap()
+            .with_memory_limit(100);
+        let err = writer.write_batch(&batch).unwrap_err();
+        assert!(matches!(
+            err.root_cause(),
+            ErrorKind::MemoryLimitExceeded { .. }
+        ));
+        // the chunk with the first row was complete before the second row failed
+        assert_eq!(writer.get_ref().len(), 19 + 34);
+        // the rest of the failed batch is dropped
+        writer.write_batch(&next).unwrap();
+        let out = writer.finish().unwrap();
+        assert_eq!(out, expected.to_vec());
+    }
+
+    /// A sink that fails once after accepting `fail_at` bytes, then accepts everything.
+    /// Shared by the tests of every writer.
+    pub(crate) struct FailingSink {
+        pub(crate) data: Vec<u8>,
+        fail_at: Option<usize>,
+    }
+
+    impl FailingSink {
+        pub(crate) fn new(fail_at: usize) -> Self {
+            Self {
+                data: vec![],
+                fail_at: Some(fail_at),
+            }
+        }
+
+        /// How many bytes of `buf` to accept, or the error to fail with
+        fn accept(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            let mut n = buf.len();
+            if let Some(fail_at) = self.fail_at {
+                n = n.min(fail_at - self.data.len());
+                if n == 0 {
+                    self.fail_at = None;
+                    return Err(std::io::Error::other("connection reset"));
+                }
+            }
+            self.data.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+    }
+
+    impl Write for FailingSink {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.accept(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(feature = "tokio")]
+    impl tokio::io::AsyncWrite for FailingSink {
+        fn poll_write(
+            mut self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+            buf: &[u8],
+        ) -> std::task::Poll<std::io::Result<usize>> {
+            std::task::Poll::Ready(self.accept(buf))
+        }
+        fn poll_flush(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+        fn poll_shutdown(
+            self: std::pin::Pin<&mut Self>,
+            _cx: &mut std::task::Context<'_>,
+        ) -> std::task::Poll<std::io::Result<()>> {
+            std::task::Poll::Ready(Ok(()))
+        }
+    }
+
+    #[test]
+    fn test_copy_writer_sink_failure() {
+        let batch = make_batch(vec![1, 2]);
+        let encoder = ArrowToPostgresBinaryEncoder::try_new(&batch.schema()).unwrap();
+        let sink = FailingSink::new(25);
+        let mut writer = CopyWriter::try_new(encoder, sink)
+            .unwrap()
+            .with_flush_threshold(1);
+        assert!(matches!(writer.write_batch(&batch), Err(ErrorKind::Io(_))));
+        // the partly written buffer is not sent again
+        assert!(matches!(writer.write_batch(&batch), Err(ErrorKind::Io(_))));
+        assert!(matches!(writer.flush(), Err(ErrorKind::Io(_))));
+        assert_eq!(writer.get_ref().data.len(), 25);
+        assert!(writer.finish().is_err());
+    }
+
+    #[test]
+    fn test_encode_reader() {
+        let batches = vec![make_batch(vec![1, 2]), make_batch(vec![3])];
+        let schema = batches[0].schema();
+
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&schema).unwrap();
+        let mut expected = BytesMut::new();
+        encoder.write_header(&mut expected).unwrap();
+        for batch in &batches {
+            encoder.write_batch(batch, &mut expected).unwrap();
+        }
+        encoder.write_footer(&mut expected).unwrap();
+
+        let reader = RecordBatchIterator::new(batches.into_iter().map(Ok), schema);
+        let chunks = ArrowToPostgresBinaryEncoder::encode_reader(reader, 30)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        // the header and the first batch, then the second batch and the footer
+        assert_eq!(
+            chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+            vec![19 + 2 * 10, 10 + 2]
+        );
+        assert_eq!(chunks.concat(), expected.to_vec());
+    }
+
+    #[test]
+    fn test_encode_reader_max_chunk_size() {
+        // empty batches are valid input
+        let batches = vec![
+            make_batch(vec![1, 2, 3, 4, 5]),
+            make_batch(vec![]),
+            make_batch(vec![6]),
+        ];
+        let schema = batches[0].schema();
+
+        let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&schema).unwrap();
+        let mut expected = BytesMut::new();
+        encoder.write_header(&mut expected).unwrap();
+        for batch in &batches {
+            encoder.write_batch(batch, &mut expected).unwrap();
+        }
+        encoder.write_footer(&mut expected).unwrap();
+
+        let reader = RecordBatchIterator::new(batches.into_iter().map(Ok), schema);
+        let chunks = ArrowToPostgresBinaryEncoder::encode_reader(reader, 1024)
+            .unwrap()
+            .with_max_chunk_size(40)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        // rows are 10 bytes, so the first batch is split after two rows,
+        // and the footer does not fit after the remaining four
+        assert_eq!(
+            chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+            vec![19 + 2 * 10, 4 * 10, 2]
+        );
+        assert_eq!(chunks.concat(), expected.to_vec());
+    }
+
+    #[test]
+    fn test_encode_reader_oversized_row() {
+        let batches = vec![make_batch(vec![1, 2])];
+        let schema = batches[0].schema();
+        let reader = RecordBatchIterator::new(batches.into_iter().map(Ok), schema);
+        let chunks = ArrowToPostgresBinaryEncoder::encode_reader(reader, 1024)
+            .unwrap()
+            .with_max_chunk_size(5)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        // rows never share a chunk with anything else when none fit
+        assert_eq!(
+            chunks.iter().map(|c| c.len()).collect::<Vec<_>>(),
+            vec![19, 10, 10, 2]
+        );
+    }
+
+    #[test]
+    fn test_encode_reader_stops_after_error() {
+        let schema = make_batch(vec![]).schema();
+        let items = vec![
+            Ok(make_batch(vec![1])),
+            Err(ArrowError::ComputeError("boom".to_string())),
+            Ok(make_batch(vec![2])),
+        ];
+        let reader = RecordBatchIterator::new(items, schema);
+        let chunks: Vec<_> = ArrowToPostgresBinaryEncoder::encode_reader(reader, 1024)
+            .unwrap()
+            .collect();
+        assert_eq!(chunks.len(), 1);
+        assert!(matches!(chunks[0], Err(ErrorKind::Read(_))));
+    }
+}
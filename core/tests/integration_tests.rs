@@ -22,9 +22,12 @@ fn run_test_case(case: &str) {
     let (batches, schema) = read_batches(path);
     let mut encoder = ArrowToPostgresBinaryEncoder::try_new(&schema).unwrap();
     let mut buf = BytesMut::new();
-    encoder.write_header(&mut buf);
+    encoder.write_header(&mut buf).unwrap();
     for batch in batches {
+        let size = encoder.encoded_size(&batch).unwrap();
+        let start = buf.len();
         encoder.write_batch(&batch, &mut buf).unwrap();
+        assert_eq!(buf.len() - start, size, "encoded_size is not exact");
     }
     encoder.write_footer(&mut buf).unwrap();
 
@@ -1,4 +1,4 @@
-from typing import Mapping, Union
+from typing import Literal, Mapping, Union
 
 import pyarrow
 
@@ -44,6 +44,45 @@ class Timestamp:
 class Interval:
     def ddl(self) -> str | None: ...
 
+class Point:
+    def ddl(self) -> str | None: ...
+
+class Lseg:
+    def ddl(self) -> str | None: ...
+
+class Box:
+    def ddl(self) -> str | None: ...
+
+class Circle:
+    def ddl(self) -> str | None: ...
+
+class Path:
+    def ddl(self) -> str | None: ...
+
+class Polygon:
+    def ddl(self) -> str | None: ...
+
+class Money:
+    def ddl(self) -> str | None: ...
+
+class Oid:
+    def ddl(self) -> str | None: ...
+
+class Regclass:
+    def ddl(self) -> str | None: ...
+
+class Xml:
+    def ddl(self) -> str | None: ...
+
+class Tsvector:
+    def ddl(self) -> str | None: ...
+
+class Numeric:
+    def ddl(self) -> str | None: ...
+
+class Uuid:
+    def ddl(self) -> str | None: ...
+
 class List:
     def __init__(self, __type: Column) -> None: ...
     def ddl(self) -> str | None: ...
@@ -75,6 +114,19 @@ PostgresType = Union[
     Time,
     Timestamp,
     Interval,
+    Point,
+    Lseg,
+    Box,
+    Circle,
+    Path,
+    Polygon,
+    Money,
+    Oid,
+    Regclass,
+    Xml,
+    Tsvector,
+    Numeric,
+    Uuid,
     List,
 ]
 
@@ -84,12 +136,40 @@ class ArrowToPostgresBinaryEncoder:
     def new_with_encoders(
         __schema: pyarrow.Schema, __encoders: Mapping[str, EncoderBuilder]
     ) -> ArrowToPostgresBinaryEncoder: ...
+    @staticmethod
+    def new_with_mapping(
+        __schema: pyarrow.Schema,
+        __columns: Mapping[str, str],
+        __encoders: Mapping[str, EncoderBuilder] | None = None,
+        constants: Mapping[str, pyarrow.Array] | None = None,
+        row_number: str | None = None,
+    ) -> ArrowToPostgresBinaryEncoder: ...
+    @staticmethod
+    def new_for_target(
+        __schema: pyarrow.Schema, __target: PostgresSchema
+    ) -> ArrowToPostgresBinaryEncoder: ...
     def write_header(self) -> bytes: ...
     def write_batch(self, __batch: pyarrow.RecordBatch) -> bytes: ...
     def finish(self) -> bytes: ...
     def schema(self) -> PostgresSchema: ...
+    def set_error_policy(
+        self, __policy: Literal["fail", "skip_row", "quarantine"]
+    ) -> None: ...
+    def set_parallelism(self, __threads: int) -> None: ...
+    def rows_skipped(self) -> int: ...
+    def take_quarantined(self) -> list[pyarrow.RecordBatch]: ...
     @staticmethod
     def infer_encoder(__field: pyarrow.Field) -> EncoderBuilder: ...
+    @staticmethod
+    def encode_reader(
+        __reader: pyarrow.RecordBatchReader,
+        chunk_size: int = ...,
+        max_chunk_size: int | None = None,
+    ) -> CopyChunks: ...
+
+class CopyChunks:
+    def __iter__(self) -> CopyChunks: ...
+    def __next__(self) -> bytes: ...
 
 class BooleanEncoderBuilder:
     def __init__(self, field: pyarrow.Field) -> None: ...
@@ -102,6 +182,10 @@ class UInt16EncoderBuilder:
 
 class UInt32EncoderBuilder:
     def __init__(self, field: pyarrow.Field) -> None: ...
+    @classmethod
+    def new_with_output(
+        cls, field: pyarrow.Field, output: Int8 | Oid | Regclass
+    ) -> UInt32EncoderBuilder: ...
 
 class Int8EncoderBuilder:
     def __init__(self, field: pyarrow.Field) -> None: ...
@@ -118,6 +202,10 @@ class Int32EncoderBuilder:
 
 class Int64EncoderBuilder:
     def __init__(self, field: pyarrow.Field) -> None: ...
+    @classmethod
+    def new_with_output(
+        cls, field: pyarrow.Field, output: Int8 | Money
+    ) -> Int64EncoderBuilder: ...
 
 class Float16EncoderBuilder:
     def __init__(self, field: pyarrow.Field) -> None: ...
@@ -128,6 +216,13 @@ class Float32EncoderBuilder:
 class Float64EncoderBuilder:
     def __init__(self, field: pyarrow.Field) -> None: ...
 
+class Decimal128EncoderBuilder:
+    def __init__(self, field: pyarrow.Field) -> None: ...
+    @classmethod
+    def new_with_output(
+        cls, field: pyarrow.Field, output: Money
+    ) -> Decimal128EncoderBuilder: ...
+
 class TimestampMicrosecondEncoderBuilder:
     def __init__(self, field: pyarrow.Field) -> None: ...
 
@@ -162,14 +257,14 @@ class StringEncoderBuilder:
     def __init__(self, field: pyarrow.Field) -> None: ...
     @classmethod
     def new_with_output(
-        cls, field: pyarrow.Field, output: Text | Jsonb
+        cls, field: pyarrow.Field, output: Text | Jsonb | Xml | Tsvector | Uuid
     ) -> Int8EncoderBuilder: ...
 
 class LargeStringEncoderBuilder:
     def __init__(self, field: pyarrow.Field) -> None: ...
     @classmethod
     def new_with_output(
-        cls, field: pyarrow.Field, output: Text | Jsonb
+        cls, field: pyarrow.Field, output: Text | Jsonb | Xml | Tsvector | Uuid
     ) -> Int8EncoderBuilder: ...
 
 class BinaryEncoderBuilder:
@@ -192,6 +287,27 @@ class LargeListEncoderBuilder:
         cls, field: pyarrow.Field, inner_encoder_builder: EncoderBuilder
     ) -> LargeListEncoderBuilder: ...
 
+class GeometricEncoderBuilder:
+    def __init__(self, field: pyarrow.Field) -> None: ...
+    @classmethod
+    def new_with_output(
+        cls, field: pyarrow.Field, output: Point | Lseg | Box | Circle | Path | Polygon
+    ) -> GeometricEncoderBuilder: ...
+
+class NumericEncoderBuilder:
+    def __init__(self, field: pyarrow.Field) -> None: ...
+    @classmethod
+    def new_with_output(
+        cls, field: pyarrow.Field, output: Numeric
+    ) -> NumericEncoderBuilder: ...
+
+class CastEncoderBuilder:
+    def __init__(self, field: pyarrow.Field) -> None: ...
+    @classmethod
+    def new_with_output(
+        cls, field: pyarrow.Field, output: Int2 | Int4 | Int8 | Float8 | Timestamp
+    ) -> CastEncoderBuilder: ...
+
 EncoderBuilder = (
     BooleanEncoderBuilder
     | UInt8EncoderBuilder
@@ -204,6 +320,7 @@ EncoderBuilder = (
     | Float16EncoderBuilder
     | Float32EncoderBuilder
     | Float64EncoderBuilder
+    | Decimal128EncoderBuilder
     | TimestampMicrosecondEncoderBuilder
     | TimestampMillisecondEncoderBuilder
     | TimestampSecondEncoderBuilder
@@ -220,4 +337,7 @@ EncoderBuilder = (
     | LargeBinaryEncoderBuilder
     | ListEncoderBuilder
     | LargeListEncoderBuilder
+    | GeometricEncoderBuilder
+    | NumericEncoderBuilder
+    | CastEncoderBuilder
 )
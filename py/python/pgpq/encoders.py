@@ -10,6 +10,7 @@ from pgpq._pgpq import (
     Float16EncoderBuilder,
     Float32EncoderBuilder,
     Float64EncoderBuilder,
+    Decimal128EncoderBuilder,
     TimestampMicrosecondEncoderBuilder,
     TimestampMillisecondEncoderBuilder,
     TimestampSecondEncoderBuilder,
@@ -26,6 +27,9 @@ from pgpq._pgpq import (
     LargeBinaryEncoderBuilder,
     ListEncoderBuilder,
     LargeListEncoderBuilder,
+    GeometricEncoderBuilder,
+    NumericEncoderBuilder,
+    CastEncoderBuilder,
 )
 
 __all__ = (
@@ -40,6 +44,7 @@ __all__ = (
     "Float16EncoderBuilder",
     "Float32EncoderBuilder",
     "Float64EncoderBuilder",
+    "Decimal128EncoderBuilder",
     "TimestampMicrosecondEncoderBuilder",
     "TimestampMillisecondEncoderBuilder",
     "TimestampSecondEncoderBuilder",
@@ -56,4 +61,7 @@ __all__ = (
     "LargeBinaryEncoderBuilder",
     "ListEncoderBuilder",
     "LargeListEncoderBuilder",
+    "GeometricEncoderBuilder",
+    "NumericEncoderBuilder",
+    "CastEncoderBuilder",
 )
@@ -19,6 +19,19 @@ from pgpq._pgpq import (
     Time,
     Timestamp,
     Interval,
+    Point,
+    Lseg,
+    Box,
+    Circle,
+    Path,
+    Polygon,
+    Money,
+    Oid,
+    Regclass,
+    Xml,
+    Tsvector,
+    Numeric,
+    Uuid,
     List,
     Column,
     PostgresSchema,
@@ -40,6 +53,19 @@ __all__ = (
     "Time",
     "Timestamp",
     "Interval",
+    "Point",
+    "Lseg",
+    "Box",
+    "Circle",
+    "Path",
+    "Polygon",
+    "Money",
+    "Oid",
+    "Regclass",
+    "Xml",
+    "Tsvector",
+    "Numeric",
+    "Uuid",
     "List",
     "Column",
     "PostgresSchema",
@@ -176,9 +176,14 @@ impl_passthrough_encoder_builder!(UInt16EncoderBuilder);
 #[derive(Debug, Clone)]
 pub struct UInt32EncoderBuilder {
     field: Py<PyAny>,
+    output: PostgresType,
     inner: pgpq::encoders::EncoderBuilder,
 }
-impl_passthrough_encoder_builder!(UInt32EncoderBuilder);
+impl_passthrough_encoder_builder_variable_output!(
+    UInt32EncoderBuilder,
+    pgpq::encoders::UInt32EncoderBuilder,
+    pgpq::encoders::EncoderBuilder::UInt32
+);
 
 #[pyclass(module = "pgpq._pgpq")]
 #[derive(Debug, Clone)]
@@ -213,9 +218,14 @@ impl_passthrough_encoder_builder!(Int32EncoderBuilder);
 #[derive(Debug, Clone)]
 pub struct Int64EncoderBuilder {
     field: Py<PyAny>,
+    output: PostgresType,
     inner: pgpq::encoders::EncoderBuilder,
 }
-impl_passthrough_encoder_builder!(Int64EncoderBuilder);
+impl_passthrough_encoder_builder_variable_output!(
+    Int64EncoderBuilder,
+    pgpq::encoders::Int64EncoderBuilder,
+    pgpq::encoders::EncoderBuilder::Int64
+);
 
 #[pyclass(module = "pgpq._pgpq")]
 #[derive(Debug, Clone)]
@@ -241,6 +251,19 @@ pub struct Float64EncoderBuilder {
 }
 impl_passthrough_encoder_builder!(Float64EncoderBuilder);
 
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone)]
+pub struct Decimal128EncoderBuilder {
+    field: Py<PyAny>,
+    output: PostgresType,
+    inner: pgpq::encoders::EncoderBuilder,
+}
+impl_passthrough_encoder_builder_variable_output!(
+    Decimal128EncoderBuilder,
+    pgpq::encoders::Decimal128EncoderBuilder,
+    pgpq::encoders::EncoderBuilder::Decimal128
+);
+
 #[pyclass(module = "pgpq._pgpq")]
 #[derive(Debug, Clone)]
 pub struct TimestampMicrosecondEncoderBuilder {
@@ -371,6 +394,45 @@ pub struct StructEncoderBuilder {
 }
 impl_passthrough_encoder_builder!(StructEncoderBuilder);
 
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone)]
+pub struct GeometricEncoderBuilder {
+    field: Py<PyAny>,
+    output: crate::pg_schema::PostgresType,
+    inner: pgpq::encoders::EncoderBuilder,
+}
+impl_passthrough_encoder_builder_variable_output!(
+    GeometricEncoderBuilder,
+    pgpq::encoders::GeometricEncoderBuilder,
+    pgpq::encoders::EncoderBuilder::Geometric
+);
+
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone)]
+pub struct NumericEncoderBuilder {
+    field: Py<PyAny>,
+    output: crate::pg_schema::PostgresType,
+    inner: pgpq::encoders::EncoderBuilder,
+}
+impl_passthrough_encoder_builder_variable_output!(
+    NumericEncoderBuilder,
+    pgpq::encoders::NumericEncoderBuilder,
+    pgpq::encoders::EncoderBuilder::Numeric
+);
+
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone)]
+pub struct CastEncoderBuilder {
+    field: Py<PyAny>,
+    output: crate::pg_schema::PostgresType,
+    inner: pgpq::encoders::EncoderBuilder,
+}
+impl_passthrough_encoder_builder_variable_output!(
+    CastEncoderBuilder,
+    pgpq::encoders::CastEncoderBuilder,
+    pgpq::encoders::EncoderBuilder::Cast
+);
+
 macro_rules! impl_list {
     ($struct:ident, $encoder_builder_enum_variant:path, $encoder_builder_new_with_inner:expr) => {
         #[pymethods]
@@ -487,6 +549,7 @@ pub enum EncoderBuilder {
     Float16(Float16EncoderBuilder),
     Float32(Float32EncoderBuilder),
     Float64(Float64EncoderBuilder),
+    Decimal128(Decimal128EncoderBuilder),
     TimestampMicrosecond(TimestampMicrosecondEncoderBuilder),
     TimestampMillisecond(TimestampMillisecondEncoderBuilder),
     TimestampSecond(TimestampSecondEncoderBuilder),
@@ -504,6 +567,9 @@ pub enum EncoderBuilder {
     List(ListEncoderBuilder),
     LargeList(LargeListEncoderBuilder),
     Struct(StructEncoderBuilder),
+    Geometric(GeometricEncoderBuilder),
+    Numeric(NumericEncoderBuilder),
+    Cast(CastEncoderBuilder),
 }
 
 impl crate::utils::PythonRepr for EncoderBuilder {
@@ -520,6 +586,7 @@ impl crate::utils::PythonRepr for EncoderBuilder {
             EncoderBuilder::Float16(inner) => inner.py_repr(py),
             EncoderBuilder::Float32(inner) => inner.py_repr(py),
             EncoderBuilder::Float64(inner) => inner.py_repr(py),
+            EncoderBuilder::Decimal128(inner) => inner.py_repr(py),
             EncoderBuilder::TimestampMicrosecond(inner) => inner.py_repr(py),
             EncoderBuilder::TimestampMillisecond(inner) => inner.py_repr(py),
             EncoderBuilder::TimestampSecond(inner) => inner.py_repr(py),
@@ -537,6 +604,9 @@ impl crate::utils::PythonRepr for EncoderBuilder {
             EncoderBuilder::List(inner) => inner.py_repr(py),
             EncoderBuilder::LargeList(inner) => inner.py_repr(py),
             EncoderBuilder::Struct(inner) => inner.py_repr(py),
+            EncoderBuilder::Geometric(inner) => inner.py_repr(py),
+            EncoderBuilder::Numeric(inner) => inner.py_repr(py),
+            EncoderBuilder::Cast(inner) => inner.py_repr(py),
         }
     }
 }
@@ -576,6 +646,7 @@ impl EncoderBuilder {
             pgpq::encoders::EncoderBuilder::UInt32(_) => {
                 EncoderBuilder::UInt32(UInt32EncoderBuilder {
                     field: py_field.to_object(py),
+                    output: pg_output_type,
                     inner,
                 })
             }
@@ -599,6 +670,7 @@ impl EncoderBuilder {
             pgpq::encoders::EncoderBuilder::Int64(_) => {
                 EncoderBuilder::Int64(Int64EncoderBuilder {
                     field: py_field.to_object(py),
+                    output: pg_output_type,
                     inner,
                 })
             }
@@ -620,6 +692,13 @@ impl EncoderBuilder {
                     inner,
                 })
             }
+            pgpq::encoders::EncoderBuilder::Decimal128(_) => {
+                EncoderBuilder::Decimal128(Decimal128EncoderBuilder {
+                    field: py_field.to_object(py),
+                    output: pg_output_type,
+                    inner,
+                })
+            }
             pgpq::encoders::EncoderBuilder::TimestampMicrosecond(_) => {
                 EncoderBuilder::TimestampMicrosecond(TimestampMicrosecondEncoderBuilder {
                     field: py_field.to_object(py),
@@ -722,6 +801,25 @@ impl EncoderBuilder {
                     inner,
                 })
             }
+            pgpq::encoders::EncoderBuilder::Geometric(_) => {
+                EncoderBuilder::Geometric(GeometricEncoderBuilder {
+                    field: py_field.to_object(py),
+                    output: pg_output_type,
+                    inner,
+                })
+            }
+            pgpq::encoders::EncoderBuilder::Numeric(_) => {
+                EncoderBuilder::Numeric(NumericEncoderBuilder {
+                    field: py_field.to_object(py),
+                    output: pg_output_type,
+                    inner,
+                })
+            }
+            pgpq::encoders::EncoderBuilder::Cast(_) => EncoderBuilder::Cast(CastEncoderBuilder {
+                field: py_field.to_object(py),
+                output: pg_output_type,
+                inner,
+            }),
         };
         Ok(wrapped)
     }
@@ -753,9 +851,11 @@ impl From<pgpq::encoders::EncoderBuilder> for EncoderBuilder {
             }
             pgpq::encoders::EncoderBuilder::UInt32(inner) => {
                 let field = inner.field();
+                let output: crate::pg_schema::PostgresType = inner.schema().data_type.into();
                 EncoderBuilder::UInt32(UInt32EncoderBuilder {
                     field: field.to_pyarrow(py).unwrap(),
                     inner: value,
+                    output,
                 })
             }
             pgpq::encoders::EncoderBuilder::Int8(inner) => {
@@ -783,9 +883,11 @@ impl From<pgpq::encoders::EncoderBuilder> for EncoderBuilder {
             }
             pgpq::encoders::EncoderBuilder::Int64(inner) => {
                 let field = inner.field();
+                let output: crate::pg_schema::PostgresType = inner.schema().data_type.into();
                 EncoderBuilder::Int64(Int64EncoderBuilder {
                     field: field.to_pyarrow(py).unwrap(),
                     inner: value,
+                    output,
                 })
             }
             pgpq::encoders::EncoderBuilder::Float16(inner) => {
@@ -809,6 +911,15 @@ impl From<pgpq::encoders::EncoderBuilder> for EncoderBuilder {
                     inner: value,
                 })
             }
+            pgpq::encoders::EncoderBuilder::Decimal128(inner) => {
+                let field = inner.field();
+                let output: crate::pg_schema::PostgresType = inner.schema().data_type.into();
+                EncoderBuilder::Decimal128(Decimal128EncoderBuilder {
+                    field: field.to_pyarrow(py).unwrap(),
+                    inner: value,
+                    output,
+                })
+            }
             pgpq::encoders::EncoderBuilder::TimestampMicrosecond(inner) => {
                 let field = inner.field();
                 EncoderBuilder::TimestampMicrosecond(TimestampMicrosecondEncoderBuilder {
@@ -932,6 +1043,33 @@ impl From<pgpq::encoders::EncoderBuilder> for EncoderBuilder {
                     inner: value,
                 })
             }
+            pgpq::encoders::EncoderBuilder::Geometric(inner) => {
+                let field = inner.field();
+                let output: crate::pg_schema::PostgresType = inner.schema().data_type.into();
+                EncoderBuilder::Geometric(GeometricEncoderBuilder {
+                    field: field.to_pyarrow(py).unwrap(),
+                    inner: value,
+                    output,
+                })
+            }
+            pgpq::encoders::EncoderBuilder::Numeric(inner) => {
+                let field = inner.field();
+                let output: crate::pg_schema::PostgresType = inner.schema().data_type.into();
+                EncoderBuilder::Numeric(NumericEncoderBuilder {
+                    field: field.to_pyarrow(py).unwrap(),
+                    inner: value,
+                    output,
+                })
+            }
+            pgpq::encoders::EncoderBuilder::Cast(inner) => {
+                let field = inner.field();
+                let output: crate::pg_schema::PostgresType = inner.schema().data_type.into();
+                EncoderBuilder::Cast(CastEncoderBuilder {
+                    field: field.to_pyarrow(py).unwrap(),
+                    inner: value,
+                    output,
+                })
+            }
         })
     }
 }
@@ -950,6 +1088,7 @@ impl From<EncoderBuilder> for pgpq::encoders::EncoderBuilder {
             EncoderBuilder::Float16(inner) => inner.inner,
             EncoderBuilder::Float32(inner) => inner.inner,
             EncoderBuilder::Float64(inner) => inner.inner,
+            EncoderBuilder::Decimal128(inner) => inner.inner,
             EncoderBuilder::TimestampMicrosecond(inner) => inner.inner,
             EncoderBuilder::TimestampMillisecond(inner) => inner.inner,
             EncoderBuilder::TimestampSecond(inner) => inner.inner,
@@ -967,6 +1106,9 @@ impl From<EncoderBuilder> for pgpq::encoders::EncoderBuilder {
             EncoderBuilder::List(inner) => inner.inner,
             EncoderBuilder::LargeList(inner) => inner.inner,
             EncoderBuilder::Struct(inner) => inner.inner,
+            EncoderBuilder::Geometric(inner) => inner.inner,
+            EncoderBuilder::Numeric(inner) => inner.inner,
+            EncoderBuilder::Cast(inner) => inner.inner,
         }
     }
 }
@@ -985,6 +1127,7 @@ impl IntoPy<PyObject> for EncoderBuilder {
             EncoderBuilder::Float16(inner) => inner.into_py(py),
             EncoderBuilder::Float32(inner) => inner.into_py(py),
             EncoderBuilder::Float64(inner) => inner.into_py(py),
+            EncoderBuilder::Decimal128(inner) => inner.into_py(py),
             EncoderBuilder::TimestampMicrosecond(inner) => inner.into_py(py),
             EncoderBuilder::TimestampMillisecond(inner) => inner.into_py(py),
             EncoderBuilder::TimestampSecond(inner) => inner.into_py(py),
@@ -1002,6 +1145,9 @@ impl IntoPy<PyObject> for EncoderBuilder {
             EncoderBuilder::List(inner) => inner.into_py(py),
             EncoderBuilder::LargeList(inner) => inner.into_py(py),
             EncoderBuilder::Struct(inner) => inner.into_py(py),
+            EncoderBuilder::Geometric(inner) => inner.into_py(py),
+            EncoderBuilder::Numeric(inner) => inner.into_py(py),
+            EncoderBuilder::Cast(inner) => inner.into_py(py),
         }
     }
 }
@@ -1,12 +1,18 @@
+// pyo3 0.19 macros emit impl blocks inside functions
+#![allow(non_local_definitions)]
+
 use std::collections::HashMap;
 
 use encoders::EncoderBuilder;
+use pyo3::exceptions::PyValueError;
 use pyo3::prelude::*;
 use pyo3::types::{PyBytes, PyDict};
 use pyo3::Python;
 
+use arrow::array::{make_array, ArrayData};
 use arrow::datatypes::Schema as ArrowSchema;
-use arrow::pyarrow::FromPyArrow;
+use arrow::ffi_stream::ArrowArrayStreamReader;
+use arrow::pyarrow::{FromPyArrow, ToPyArrow};
 use arrow::record_batch::RecordBatch;
 use bytes::BytesMut;
 
@@ -61,32 +67,161 @@ impl ArrowToPostgresBinaryEncoder {
             empty: PyBytes::new(py, &vec![][..]).into(),
         })
     }
-    fn write_header(&mut self, py: Python) -> Py<PyAny> {
-        self.encoder.write_header(&mut self.buf);
-        PyBytes::new(py, &self.buf.split()[..]).into()
+    #[staticmethod]
+    #[pyo3(signature = (py_schema, columns, py_encoders=None, constants=None, row_number=None))]
+    fn new_with_mapping(
+        py: Python,
+        py_schema: &PyAny,
+        columns: &PyDict,
+        py_encoders: Option<&PyDict>,
+        constants: Option<&PyDict>,
+        row_number: Option<String>,
+    ) -> PyResult<Self> {
+        let mut mapping = pgpq::mapping::ColumnMapping::default();
+        for item in columns.items() {
+            let (source, name): (String, String) = item.extract()?;
+            let mut column = pgpq::mapping::MappedColumn::new(&source).renamed(&name);
+            if let Some(py_builder) = py_encoders.and_then(|e| e.get_item(&source)) {
+                let builder: crate::encoders::EncoderBuilder = py_builder.extract()?;
+                column = column.with_encoder(builder.into());
+            }
+            mapping.columns.push(column);
+        }
+        for (name, py_value) in constants.into_iter().flat_map(|c| c.iter()) {
+            let value = make_array(ArrayData::from_pyarrow(py_value)?);
+            mapping = mapping.constant(name.extract()?, value);
+        }
+        if let Some(name) = row_number {
+            mapping = mapping.row_number(&name, 1);
+        }
+        let schema = &ArrowSchema::from_pyarrow(py_schema)?;
+        let encoder = pgpq::ArrowToPostgresBinaryEncoder::try_new_with_mapping(schema, &mapping)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self {
+            encoder,
+            buf: BytesMut::with_capacity(BUFF_SIZE),
+            empty: PyBytes::new(py, &vec![][..]).into(),
+        })
+    }
+    #[staticmethod]
+    fn new_for_target(
+        py: Python,
+        py_schema: &PyAny,
+        target: crate::pg_schema::PostgresSchema,
+    ) -> PyResult<Self> {
+        let schema = &ArrowSchema::from_pyarrow(py_schema)?;
+        let encoder =
+            pgpq::ArrowToPostgresBinaryEncoder::try_new_for_target(schema, &target.into())
+                .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self {
+            encoder,
+            buf: BytesMut::with_capacity(BUFF_SIZE),
+            empty: PyBytes::new(py, &vec![][..]).into(),
+        })
     }
-    fn write_batch(&mut self, py_batch: &PyAny) -> Py<PyAny> {
-        let batch = &RecordBatch::from_pyarrow(py_batch).unwrap();
-        self.encoder.write_batch(batch, &mut self.buf).unwrap();
+    fn write_header(&mut self, py: Python) -> PyResult<Py<PyAny>> {
+        self.encoder
+            .write_header(&mut self.buf)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PyBytes::new(py, &self.buf.split()[..]).into())
+    }
+    fn write_batch(&mut self, py: Python, py_batch: &PyAny) -> PyResult<Py<PyAny>> {
+        let batch = &RecordBatch::from_pyarrow(py_batch)?;
+        // other Python threads can run while the batch is encoded
+        let (encoder, buf) = (&mut self.encoder, &mut self.buf);
+        py.allow_threads(|| encoder.write_batch(batch, buf))
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
 
         if self.buf.len() > BUFF_SIZE {
-            Python::with_gil(|py| PyBytes::new(py, &self.buf.split()[..]).into())
+            Ok(PyBytes::new(py, &self.buf.split()[..]).into())
         } else {
-            self.empty.clone()
+            Ok(self.empty.clone())
         }
     }
-    fn finish(&mut self) -> &[u8] {
-        self.encoder.write_footer(&mut self.buf).unwrap();
-        &self.buf[..]
+    fn finish(&mut self) -> PyResult<&[u8]> {
+        self.encoder
+            .write_footer(&mut self.buf)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(&self.buf[..])
+    }
+    /// One of "fail", "skip_row" or "quarantine"
+    fn set_error_policy(&mut self, policy: &str) -> PyResult<()> {
+        let policy = match policy {
+            "fail" => pgpq::ErrorPolicy::Fail,
+            "skip_row" => pgpq::ErrorPolicy::SkipRow,
+            "quarantine" => pgpq::ErrorPolicy::Quarantine,
+            other => {
+                return Err(PyValueError::new_err(format!(
+                    "unknown error policy {other:?}; expected fail, skip_row or quarantine"
+                )))
+            }
+        };
+        self.encoder.set_error_policy(policy);
+        Ok(())
+    }
+    /// Encode each batch on a pool of `threads` threads; the output is unchanged
+    fn set_parallelism(&mut self, threads: usize) -> PyResult<()> {
+        self.encoder
+            .set_parallelism(threads)
+            .map_err(|e| PyValueError::new_err(e.to_string()))
+    }
+    fn rows_skipped(&self) -> usize {
+        self.encoder.rows_skipped()
+    }
+    fn take_quarantined(&mut self, py: Python) -> PyResult<Vec<PyObject>> {
+        self.encoder
+            .take_quarantined()
+            .iter()
+            .map(|batch| batch.to_pyarrow(py))
+            .collect()
     }
     fn schema(&self) -> crate::pg_schema::PostgresSchema {
         self.encoder.schema().into()
     }
+    /// Iterates over the COPY chunks encoded from every batch of a pyarrow RecordBatchReader,
+    /// splitting batches between rows to keep chunks under max_chunk_size if given
+    #[staticmethod]
+    #[pyo3(signature = (py_reader, chunk_size=BUFF_SIZE, max_chunk_size=None))]
+    fn encode_reader(
+        py_reader: &PyAny,
+        chunk_size: usize,
+        max_chunk_size: Option<usize>,
+    ) -> PyResult<CopyChunks> {
+        let reader = ArrowArrayStreamReader::from_pyarrow(py_reader)?;
+        let mut chunks = pgpq::ArrowToPostgresBinaryEncoder::encode_reader(reader, chunk_size)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        if let Some(max_chunk_size) = max_chunk_size {
+            chunks = chunks.with_max_chunk_size(max_chunk_size);
+        }
+        Ok(CopyChunks { chunks })
+    }
+}
+
+#[pyclass(module = "pgpq._pgpq")]
+struct CopyChunks {
+    chunks: pgpq::writer::EncodeReader<ArrowArrayStreamReader>,
+}
+
+#[pymethods]
+impl CopyChunks {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+    fn __next__(&mut self, py: Python) -> PyResult<Option<Py<PyAny>>> {
+        match self.chunks.next() {
+            Some(chunk) => {
+                let chunk = chunk.map_err(|e| PyValueError::new_err(e.to_string()))?;
+                Ok(Some(PyBytes::new(py, &chunk[..]).into()))
+            }
+            None => Ok(None),
+        }
+    }
 }
 
 #[pymodule]
 fn _pgpq(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<ArrowToPostgresBinaryEncoder>()?;
+    m.add_class::<CopyChunks>()?;
     m.add_class::<crate::encoders::Int8EncoderBuilder>()?;
     m.add_class::<crate::encoders::ListEncoderBuilder>()?;
     m.add_class::<crate::pg_schema::Char>()?;
@@ -103,6 +238,7 @@ fn _pgpq(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<crate::encoders::Float16EncoderBuilder>()?;
     m.add_class::<crate::encoders::Float32EncoderBuilder>()?;
     m.add_class::<crate::encoders::Float64EncoderBuilder>()?;
+    m.add_class::<crate::encoders::Decimal128EncoderBuilder>()?;
     m.add_class::<crate::encoders::TimestampMicrosecondEncoderBuilder>()?;
     m.add_class::<crate::encoders::TimestampMillisecondEncoderBuilder>()?;
     m.add_class::<crate::encoders::TimestampSecondEncoderBuilder>()?;
@@ -120,6 +256,9 @@ fn _pgpq(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<crate::encoders::ListEncoderBuilder>()?;
     m.add_class::<crate::encoders::LargeListEncoderBuilder>()?;
     m.add_class::<crate::encoders::StructEncoderBuilder>()?;
+    m.add_class::<crate::encoders::GeometricEncoderBuilder>()?;
+    m.add_class::<crate::encoders::NumericEncoderBuilder>()?;
+    m.add_class::<crate::encoders::CastEncoderBuilder>()?;
 
     m.add_class::<crate::pg_schema::Bool>()?;
     m.add_class::<crate::pg_schema::Bytea>()?;
@@ -135,6 +274,19 @@ fn _pgpq(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<crate::pg_schema::Time>()?;
     m.add_class::<crate::pg_schema::Timestamp>()?;
     m.add_class::<crate::pg_schema::Interval>()?;
+    m.add_class::<crate::pg_schema::Point>()?;
+    m.add_class::<crate::pg_schema::Lseg>()?;
+    m.add_class::<crate::pg_schema::GeoBox>()?;
+    m.add_class::<crate::pg_schema::Circle>()?;
+    m.add_class::<crate::pg_schema::Path>()?;
+    m.add_class::<crate::pg_schema::Polygon>()?;
+    m.add_class::<crate::pg_schema::Money>()?;
+    m.add_class::<crate::pg_schema::Oid>()?;
+    m.add_class::<crate::pg_schema::Regclass>()?;
+    m.add_class::<crate::pg_schema::Xml>()?;
+    m.add_class::<crate::pg_schema::Tsvector>()?;
+    m.add_class::<crate::pg_schema::Numeric>()?;
+    m.add_class::<crate::pg_schema::Uuid>()?;
     m.add_class::<crate::pg_schema::List>()?;
     m.add_class::<crate::pg_schema::Column>()?;
     m.add_class::<crate::pg_schema::PostgresSchema>()?;
@@ -5,6 +5,9 @@ use pyo3::types::PyList;
 
 macro_rules! impl_simple {
     ($struct:ident, $pg_type:path) => {
+        impl_simple!($struct, $pg_type, stringify!($struct));
+    };
+    ($struct:ident, $pg_type:path, $py_name:expr) => {
         #[pymethods]
         impl $struct {
             #[new]
@@ -41,7 +44,7 @@ macro_rules! impl_simple {
         }
         impl PythonRepr for $struct {
             fn py_repr(&self, _py: Python) -> String {
-                format!("{}()", stringify!($struct))
+                format!("{}()", $py_name)
             }
         }
     };
@@ -122,6 +125,72 @@ impl_simple!(Timestamp, pgpq::pg_schema::PostgresType::Timestamp);
 pub struct Interval;
 impl_simple!(Interval, pgpq::pg_schema::PostgresType::Interval);
 
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point;
+impl_simple!(Point, pgpq::pg_schema::PostgresType::Point);
+
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lseg;
+impl_simple!(Lseg, pgpq::pg_schema::PostgresType::Lseg);
+
+// Named GeoBox to avoid shadowing std's Box
+#[pyclass(module = "pgpq._pgpq", name = "Box")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoBox;
+impl_simple!(GeoBox, pgpq::pg_schema::PostgresType::Box, "Box");
+
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Circle;
+impl_simple!(Circle, pgpq::pg_schema::PostgresType::Circle);
+
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Path;
+impl_simple!(Path, pgpq::pg_schema::PostgresType::Path);
+
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon;
+impl_simple!(Polygon, pgpq::pg_schema::PostgresType::Polygon);
+
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Money;
+impl_simple!(Money, pgpq::pg_schema::PostgresType::Money);
+
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Oid;
+impl_simple!(Oid, pgpq::pg_schema::PostgresType::Oid);
+
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regclass;
+impl_simple!(Regclass, pgpq::pg_schema::PostgresType::Regclass);
+
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Xml;
+impl_simple!(Xml, pgpq::pg_schema::PostgresType::Xml);
+
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tsvector;
+impl_simple!(Tsvector, pgpq::pg_schema::PostgresType::Tsvector);
+
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Numeric;
+impl_simple!(Numeric, pgpq::pg_schema::PostgresType::Numeric);
+
+#[pyclass(module = "pgpq._pgpq")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Uuid;
+impl_simple!(Uuid, pgpq::pg_schema::PostgresType::Uuid);
+
 #[pyclass(module = "pgpq._pgpq")]
 #[derive(Debug, Clone, PartialEq)]
 pub struct List {
@@ -231,6 +300,19 @@ pub enum PostgresType {
     Time(Time),
     Timestamp(Timestamp),
     Interval(Interval),
+    Point(Point),
+    Lseg(Lseg),
+    Box(GeoBox),
+    Circle(Circle),
+    Path(Path),
+    Polygon(Polygon),
+    Money(Money),
+    Oid(Oid),
+    Regclass(Regclass),
+    Xml(Xml),
+    Tsvector(Tsvector),
+    Numeric(Numeric),
+    Uuid(Uuid),
     List(List),
     UserDefined(UserDefined),
 }
@@ -253,6 +335,19 @@ impl From<PostgresType> for pgpq::pg_schema::PostgresType {
             PostgresType::Time(inner) => inner.into(),
             PostgresType::Timestamp(inner) => inner.into(),
             PostgresType::Interval(inner) => inner.into(),
+            PostgresType::Point(inner) => inner.into(),
+            PostgresType::Lseg(inner) => inner.into(),
+            PostgresType::Box(inner) => inner.into(),
+            PostgresType::Circle(inner) => inner.into(),
+            PostgresType::Path(inner) => inner.into(),
+            PostgresType::Polygon(inner) => inner.into(),
+            PostgresType::Money(inner) => inner.into(),
+            PostgresType::Oid(inner) => inner.into(),
+            PostgresType::Regclass(inner) => inner.into(),
+            PostgresType::Xml(inner) => inner.into(),
+            PostgresType::Tsvector(inner) => inner.into(),
+            PostgresType::Numeric(inner) => inner.into(),
+            PostgresType::Uuid(inner) => inner.into(),
             PostgresType::List(inner) => inner.into(),
             PostgresType::UserDefined(inner) => inner.into(),
         }
@@ -277,6 +372,19 @@ impl From<pgpq::pg_schema::PostgresType> for PostgresType {
             pgpq::pg_schema::PostgresType::Time => PostgresType::Time(Time),
             pgpq::pg_schema::PostgresType::Timestamp => PostgresType::Timestamp(Timestamp),
             pgpq::pg_schema::PostgresType::Interval => PostgresType::Interval(Interval),
+            pgpq::pg_schema::PostgresType::Point => PostgresType::Point(Point),
+            pgpq::pg_schema::PostgresType::Lseg => PostgresType::Lseg(Lseg),
+            pgpq::pg_schema::PostgresType::Box => PostgresType::Box(GeoBox),
+            pgpq::pg_schema::PostgresType::Circle => PostgresType::Circle(Circle),
+            pgpq::pg_schema::PostgresType::Path => PostgresType::Path(Path),
+            pgpq::pg_schema::PostgresType::Polygon => PostgresType::Polygon(Polygon),
+            pgpq::pg_schema::PostgresType::Money => PostgresType::Money(Money),
+            pgpq::pg_schema::PostgresType::Oid => PostgresType::Oid(Oid),
+            pgpq::pg_schema::PostgresType::Regclass => PostgresType::Regclass(Regclass),
+            pgpq::pg_schema::PostgresType::Xml => PostgresType::Xml(Xml),
+            pgpq::pg_schema::PostgresType::Tsvector => PostgresType::Tsvector(Tsvector),
+            pgpq::pg_schema::PostgresType::Numeric => PostgresType::Numeric(Numeric),
+            pgpq::pg_schema::PostgresType::Uuid => PostgresType::Uuid(Uuid),
             pgpq::pg_schema::PostgresType::List(inner) => {
                 PostgresType::List(List::new((*inner).into()))
             }
@@ -307,6 +415,19 @@ impl PythonRepr for PostgresType {
             PostgresType::Time(inner) => inner.py_repr(py),
             PostgresType::Timestamp(inner) => inner.py_repr(py),
             PostgresType::Interval(inner) => inner.py_repr(py),
+            PostgresType::Point(inner) => inner.py_repr(py),
+            PostgresType::Lseg(inner) => inner.py_repr(py),
+            PostgresType::Box(inner) => inner.py_repr(py),
+            PostgresType::Circle(inner) => inner.py_repr(py),
+            PostgresType::Path(inner) => inner.py_repr(py),
+            PostgresType::Polygon(inner) => inner.py_repr(py),
+            PostgresType::Money(inner) => inner.py_repr(py),
+            PostgresType::Oid(inner) => inner.py_repr(py),
+            PostgresType::Regclass(inner) => inner.py_repr(py),
+            PostgresType::Xml(inner) => inner.py_repr(py),
+            PostgresType::Tsvector(inner) => inner.py_repr(py),
+            PostgresType::Numeric(inner) => inner.py_repr(py),
+            PostgresType::Uuid(inner) => inner.py_repr(py),
             PostgresType::List(inner) => inner.py_repr(py),
             PostgresType::UserDefined(inner) => inner.py_repr(py),
         }
@@ -350,6 +471,19 @@ impl Column {
             PostgresType::Time(inner) => inner.clone().into_py(py),
             PostgresType::Timestamp(inner) => inner.clone().into_py(py),
             PostgresType::Interval(inner) => inner.clone().into_py(py),
+            PostgresType::Point(inner) => inner.clone().into_py(py),
+            PostgresType::Lseg(inner) => inner.clone().into_py(py),
+            PostgresType::Box(inner) => inner.clone().into_py(py),
+            PostgresType::Circle(inner) => inner.clone().into_py(py),
+            PostgresType::Path(inner) => inner.clone().into_py(py),
+            PostgresType::Polygon(inner) => inner.clone().into_py(py),
+            PostgresType::Money(inner) => inner.clone().into_py(py),
+            PostgresType::Oid(inner) => inner.clone().into_py(py),
+            PostgresType::Regclass(inner) => inner.clone().into_py(py),
+            PostgresType::Xml(inner) => inner.clone().into_py(py),
+            PostgresType::Tsvector(inner) => inner.clone().into_py(py),
+            PostgresType::Numeric(inner) => inner.clone().into_py(py),
+            PostgresType::Uuid(inner) => inner.clone().into_py(py),
             PostgresType::List(inner) => inner.clone().into_py(py),
             PostgresType::UserDefined(inner) => inner.clone().into_py(py),
         }